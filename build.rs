@@ -0,0 +1,58 @@
+//! Build script - generates the registry of manifests embedded in the binary
+//!
+//! Every `examples/manifests/<name>.toml` becomes an embedded app that
+//! launches as `void_<name>`.
+
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+fn main() {
+    let manifest_dir = PathBuf::from(env::var("CARGO_MANIFEST_DIR").unwrap());
+    let manifests_dir = manifest_dir.join("examples/manifests");
+    println!("cargo:rerun-if-changed={}", manifests_dir.display());
+
+    let mut apps: Vec<(String, PathBuf)> = fs::read_dir(&manifests_dir)
+        .map(|entries| {
+            entries
+                .flatten()
+                .map(|entry| entry.path())
+                .filter(|path| path.extension().is_some_and(|ext| ext == "toml"))
+                .filter_map(|path| Some((app_name(&path)?, path)))
+                .collect()
+        })
+        .unwrap_or_default();
+    apps.sort();
+
+    let mut code = String::from("/// Names of all embedded apps - used to create symlinks\n");
+    code.push_str("pub const EMBEDDED_APPS: &[&str] = &[\n");
+    for (name, _) in &apps {
+        code.push_str(&format!("    {:?},\n", name));
+    }
+    code.push_str("];\n\n");
+
+    code.push_str("/// Embedded manifest content for each app in `EMBEDDED_APPS`\n");
+    code.push_str("const EMBEDDED_MANIFESTS: &[(&str, &str)] = &[\n");
+    for (name, path) in &apps {
+        println!("cargo:rerun-if-changed={}", path.display());
+        code.push_str(&format!(
+            "    ({:?}, include_str!({:?})),\n",
+            name,
+            path.display().to_string()
+        ));
+    }
+    code.push_str("];\n");
+
+    let out_path = PathBuf::from(env::var("OUT_DIR").unwrap()).join("embedded_apps.rs");
+    fs::write(out_path, code).unwrap();
+}
+
+/// App name from a manifest file name (`brave.toml` -> `brave`)
+fn app_name(path: &Path) -> Option<String> {
+    let stem = path.file_stem()?.to_str()?;
+    let valid = !stem.is_empty()
+        && stem
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_');
+    valid.then(|| stem.to_string())
+}
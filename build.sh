@@ -2,10 +2,10 @@
 # Build voidbox and create app launcher binaries
 cargo build --release
 
-# Create app launcher copies (users can distribute these)
-cp target/release/voidbox target/release/void_brave
-cp target/release/voidbox target/release/void_discord
-cp target/release/voidbox target/release/void_vscode
+# Create app launcher copies (users can distribute these), one per embedded manifest
+for manifest in examples/manifests/*.toml; do
+    cp target/release/voidbox "target/release/void_$(basename "$manifest" .toml)"
+done
 
 echo "Built:"
 ls -la target/release/void* target/release/voidbox
@@ -1,33 +1,68 @@
 //! Self-extracting .voidbox bundle support
-
-use crate::manifest::parse_manifest_str;
+//!
+//! A bundle is the voidbox binary followed by a payload (manifest and app
+//! archive), a signature block and a footer (magic, format version, payload
+//! length). The signature block was added in version 2: a flags byte, the
+//! signer's ed25519 public key and a signature over the SHA-256 of the
+//! payload, zero when the bundle is unsigned. Version 1 bundles have no
+//! signature block and are still accepted as unsigned.
+//!
+//! Archives that aren't compressed already (plain zips) are stored as a zstd
+//! stream, marked by `FLAG_ZSTD`; the manifest is always stored as-is.
+
+use crate::manifest::{flatten_manifest_file, parse_manifest_str};
+use crate::storage::paths;
+use ed25519_dalek::{Signature, Signer, SigningKey, VerifyingKey};
+use sha2::{Digest, Sha256};
 use std::fs::{self, File};
 use std::io::{Read, Seek, SeekFrom, Write};
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
 use std::path::{Path, PathBuf};
-use std::time::{SystemTime, UNIX_EPOCH};
 use thiserror::Error;
 
 const BUNDLE_MAGIC: &[u8; 8] = b"VBOXBNDL";
-const BUNDLE_VERSION: u8 = 1;
+const BUNDLE_VERSION: u8 = 2;
 const FOOTER_LEN: u64 = 8 + 1 + 8;
+const SIGNATURE_BLOCK_LEN: u64 = 1 + 32 + 64;
+/// Signature block flags: the bundle is signed
+const FLAG_SIGNED: u8 = 1;
+/// Signature block flags: the archive is zstd-compressed
+const FLAG_ZSTD: u8 = 2;
+const ZSTD_LEVEL: i32 = 9;
+/// Prefix of the signed message, so a bundle signature can't be replayed
+/// as a signature over anything else
+const SIGNATURE_CONTEXT: &[u8] = b"voidbox-bundle-v2\0";
 
 #[derive(Debug, Clone)]
 pub struct BundleManifestInfo {
     pub app_name: String,
     pub display_name: String,
     pub manifest_content: String,
+    /// Bundle format version
+    pub format_version: u8,
+    /// Size of the manifest and archive inside the bundle
+    pub payload_size: u64,
+    /// Archive type (file extension, e.g. ".tar.gz")
+    pub archive_ext: String,
+    /// Size of the archive as stored in the bundle
+    pub archive_size: u64,
+    /// Whether the archive is stored zstd-compressed
+    pub compressed: bool,
 }
 
-#[derive(Debug, Clone)]
+/// Bundle contents extracted to a private temp dir, removed on drop
+#[derive(Debug)]
 pub struct BundleExtracted {
     pub manifest_content: String,
     pub archive_path: PathBuf,
     pub archive_ext: String,
+    /// Signature of exactly the bytes that were extracted
+    pub signature: BundleSignature,
     temp_dir: PathBuf,
 }
 
-impl BundleExtracted {
-    pub fn cleanup(&self) {
+impl Drop for BundleExtracted {
+    fn drop(&mut self) {
         let _ = fs::remove_dir_all(&self.temp_dir);
     }
 }
@@ -48,11 +83,51 @@ pub enum BundleError {
 
     #[error("UTF-8 error: {0}")]
     Utf8Error(#[from] std::string::FromUtf8Error),
+
+    #[error("Invalid signature: {0}")]
+    InvalidSignature(String),
+
+    #[error("Invalid key: {0}")]
+    InvalidKey(String),
+}
+
+/// Result of checking a bundle's signature
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum BundleSignature {
+    /// No signature (v1 bundle, or created without `--sign-key`)
+    Unsigned,
+    /// Valid signature from a key that is not in the trusted keys file
+    Untrusted(String),
+    /// Valid signature from a trusted key (hex public key)
+    Trusted(String),
 }
 
 struct BundleFooter {
     payload_len: u64,
     version: u8,
+    /// `FLAG_*` bits from the signature block (always 0 for v1)
+    flags: u8,
+    /// Signer public key and signature (v2 signed bundles)
+    signature: Option<([u8; 32], [u8; 64])>,
+}
+
+impl BundleFooter {
+    /// Offset of the payload in a file of `file_len` bytes
+    fn payload_start(&self, file_len: u64) -> u64 {
+        file_len - FOOTER_LEN - signature_block_len(self.version) - self.payload_len
+    }
+
+    fn check_version(&self) -> Result<(), BundleError> {
+        if self.version == 0 || self.version > BUNDLE_VERSION {
+            return Err(BundleError::UnsupportedVersion(self.version));
+        }
+        Ok(())
+    }
+}
+
+/// Size of the signature block for a bundle format version
+fn signature_block_len(version: u8) -> u64 {
+    if version >= 2 { SIGNATURE_BLOCK_LEN } else { 0 }
 }
 
 pub fn embedded_manifest_info() -> Result<Option<BundleManifestInfo>, BundleError> {
@@ -60,53 +135,273 @@ pub fn embedded_manifest_info() -> Result<Option<BundleManifestInfo>, BundleErro
     manifest_info_from_file(&exe_path)
 }
 
-pub fn extract_embedded_bundle() -> Result<Option<BundleExtracted>, BundleError> {
+pub fn extract_embedded_bundle(
+    trusted_keys: &[VerifyingKey],
+) -> Result<Option<BundleExtracted>, BundleError> {
     let exe_path = std::env::current_exe()?;
     let mut file = File::open(&exe_path)?;
     if read_footer(&mut file)?.is_none() {
         return Ok(None);
     }
-    extract_bundle_from_file(&exe_path).map(Some)
+    extract_bundle(&exe_path, file, trusted_keys).map(Some)
 }
 
-pub fn extract_bundle_from_file(path: &Path) -> Result<BundleExtracted, BundleError> {
-    let mut file = File::open(path)?;
+/// Extract a bundle and check its signature against `trusted_keys`
+///
+/// The payload is hashed as it is extracted, from the one open file, so the
+/// signature always covers the files that were written out. A signature that
+/// doesn't match is an error, as with `verify_signature`.
+pub fn extract_bundle_from_file(
+    path: &Path,
+    trusted_keys: &[VerifyingKey],
+) -> Result<BundleExtracted, BundleError> {
+    extract_bundle(path, File::open(path)?, trusted_keys)
+}
+
+fn extract_bundle(
+    path: &Path,
+    mut file: File,
+    trusted_keys: &[VerifyingKey],
+) -> Result<BundleExtracted, BundleError> {
     let footer = read_footer(&mut file)?.ok_or_else(|| {
         BundleError::InvalidBundle("bundle footer not found".to_string())
     })?;
-    if footer.version != BUNDLE_VERSION {
-        return Err(BundleError::UnsupportedVersion(footer.version));
-    }
+    footer.check_version()?;
 
     let file_len = file.metadata()?.len();
-    let payload_start = file_len - FOOTER_LEN - footer.payload_len;
-    let payload = read_payload_header(&mut file, payload_start, footer.payload_len)?;
+    file.seek(SeekFrom::Start(footer.payload_start(file_len)))?;
+    let mut payload_reader = HashingReader::new(file.take(footer.payload_len));
+    let payload = read_payload_header(&mut payload_reader, &footer)?;
 
+    // Owns the temp dir from here on, so a failed extraction cleans up too
     let temp_dir = create_temp_dir()?;
-    let archive_path = temp_dir.join(format!("app{}", payload.archive_ext));
-
-    file.seek(SeekFrom::Start(payload.archive_offset))?;
-    let mut take = file.take(payload.archive_len);
-    let mut out = File::create(&archive_path)?;
-    let written = std::io::copy(&mut take, &mut out)?;
-    if written != payload.archive_len {
-        return Err(BundleError::InvalidBundle(
-            "archive payload truncated".to_string(),
-        ));
-    }
-
-    Ok(BundleExtracted {
+    let mut extracted = BundleExtracted {
         manifest_content: payload.manifest_content,
-        archive_path,
+        archive_path: temp_dir.join(format!("app{}", payload.archive_ext)),
         archive_ext: payload.archive_ext,
+        signature: BundleSignature::Unsigned,
         temp_dir,
-    })
+    };
+
+    let mut take = (&mut payload_reader).take(payload.archive_len);
+    let mut out = File::create(&extracted.archive_path)?;
+    if payload.compressed {
+        zstd::stream::copy_decode(&mut take, &mut out).map_err(|e| {
+            BundleError::InvalidBundle(format!("decompress archive payload: {}", e))
+        })?;
+    } else {
+        let written = std::io::copy(&mut take, &mut out)?;
+        if written != payload.archive_len {
+            return Err(BundleError::InvalidBundle(
+                "archive payload truncated".to_string(),
+            ));
+        }
+    }
+
+    // Hash whatever the decoder left unread, so the whole payload is covered
+    std::io::copy(&mut payload_reader, &mut std::io::sink())?;
+    if payload_reader.len != footer.payload_len {
+        return Err(BundleError::InvalidBundle("payload truncated".to_string()));
+    }
+    let message = signature_message(footer.flags, &payload_reader.hasher.finalize());
+    extracted.signature = check_footer_signature(path, &footer, &message, trusted_keys)?;
+
+    Ok(extracted)
+}
+
+/// Reader that hashes everything read through it
+struct HashingReader<R> {
+    inner: R,
+    hasher: Sha256,
+    /// Bytes read so far
+    len: u64,
+}
+
+impl<R: Read> HashingReader<R> {
+    fn new(inner: R) -> Self {
+        Self {
+            inner,
+            hasher: Sha256::new(),
+            len: 0,
+        }
+    }
+}
+
+impl<R: Read> Read for HashingReader<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let n = self.inner.read(buf)?;
+        self.hasher.update(&buf[..n]);
+        self.len += n as u64;
+        Ok(n)
+    }
+}
+
+/// Check the signature of a bundle file against `trusted_keys`
+///
+/// A signature that does not match the payload is an error (the bundle was
+/// modified after signing); a valid one from an unknown key is reported as
+/// `Untrusted`.
+pub fn verify_signature(
+    path: &Path,
+    trusted_keys: &[VerifyingKey],
+) -> Result<BundleSignature, BundleError> {
+    let mut file = File::open(path)?;
+    let footer = read_footer(&mut file)?
+        .ok_or_else(|| BundleError::InvalidBundle("bundle footer not found".to_string()))?;
+    footer.check_version()?;
+
+    let payload_start = footer.payload_start(file.metadata()?.len());
+    let message = signed_message(&mut file, payload_start, &footer)?;
+    check_footer_signature(path, &footer, &message, trusted_keys)
+}
+
+/// Check the footer's signature over `message` (see `signed_message`)
+fn check_footer_signature(
+    path: &Path,
+    footer: &BundleFooter,
+    message: &[u8],
+    trusted_keys: &[VerifyingKey],
+) -> Result<BundleSignature, BundleError> {
+    if footer.version < 2 {
+        crate::warn!(
+            "Warning: {} uses the deprecated v1 bundle format (unsigned).",
+            path.display()
+        );
+    }
+    let Some((public_key, signature)) = footer.signature else {
+        return Ok(BundleSignature::Unsigned);
+    };
+
+    let key = VerifyingKey::from_bytes(&public_key)
+        .map_err(|e| BundleError::InvalidSignature(format!("bad signer key: {}", e)))?;
+    key.verify_strict(message, &Signature::from_bytes(&signature))
+        .map_err(|_| {
+            BundleError::InvalidSignature("payload does not match its signature".to_string())
+        })?;
+
+    let key_hex = hex::encode(public_key);
+    if trusted_keys.contains(&key) {
+        Ok(BundleSignature::Trusted(key_hex))
+    } else {
+        Ok(BundleSignature::Untrusted(key_hex))
+    }
+}
+
+/// Read the trusted bundle signing keys (hex public keys, one per line)
+pub fn load_trusted_keys() -> Result<Vec<VerifyingKey>, BundleError> {
+    let path = paths::trusted_keys_path();
+    if !path.exists() {
+        return Ok(Vec::new());
+    }
+
+    fs::read_to_string(&path)?
+        .lines()
+        .map(|line| line.split('#').next().unwrap_or_default().trim())
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            let bytes = decode_key(line)?;
+            VerifyingKey::from_bytes(&bytes)
+                .map_err(|e| BundleError::InvalidKey(format!("{}: {}", path.display(), e)))
+        })
+        .collect()
+}
+
+/// Create a new signing key at `path` (and its public key at `<path>.pub`)
+///
+/// Returns the hex public key to add to the trusted keys file.
+pub fn generate_signing_key(path: &Path) -> Result<String, BundleError> {
+    if path.exists() {
+        return Err(BundleError::InvalidKey(format!(
+            "{} already exists",
+            path.display()
+        )));
+    }
+
+    let mut secret = [0u8; 32];
+    File::open("/dev/urandom")?.read_exact(&mut secret)?;
+    let key = SigningKey::from_bytes(&secret);
+    let public_hex = hex::encode(key.verifying_key().as_bytes());
+
+    let mut file = fs::OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path)?;
+    writeln!(file, "{}", hex::encode(secret))?;
+
+    let mut public_path = path.as_os_str().to_os_string();
+    public_path.push(".pub");
+    fs::write(&public_path, format!("{}\n", public_hex))?;
+    Ok(public_hex)
+}
+
+/// Load a signing key written by `generate_signing_key`
+pub fn load_signing_key(path: &Path) -> Result<SigningKey, BundleError> {
+    let content = fs::read_to_string(path)
+        .map_err(|e| BundleError::InvalidKey(format!("read {}: {}", path.display(), e)))?;
+    Ok(SigningKey::from_bytes(&decode_key(content.trim())?))
+}
+
+fn decode_key(hex_key: &str) -> Result<[u8; 32], BundleError> {
+    hex::decode(hex_key)
+        .ok()
+        .and_then(|bytes| bytes.try_into().ok())
+        .ok_or_else(|| BundleError::InvalidKey(format!("expected 64 hex characters: {}", hex_key)))
+}
+
+/// Message covered by a bundle signature: context prefix, the flags other
+/// than `FLAG_SIGNED`, and the payload SHA-256
+fn signed_message(
+    file: &mut File,
+    payload_start: u64,
+    footer: &BundleFooter,
+) -> Result<Vec<u8>, BundleError> {
+    file.seek(SeekFrom::Start(payload_start))?;
+    let mut hasher = Sha256::new();
+    let hashed = std::io::copy(&mut file.take(footer.payload_len), &mut hasher)?;
+    if hashed != footer.payload_len {
+        return Err(BundleError::InvalidBundle("payload truncated".to_string()));
+    }
+    Ok(signature_message(footer.flags, &hasher.finalize()))
+}
+
+fn signature_message(flags: u8, payload_hash: &[u8]) -> Vec<u8> {
+    let mut message = SIGNATURE_CONTEXT.to_vec();
+    message.push(flags & !FLAG_SIGNED);
+    message.extend_from_slice(payload_hash);
+    message
+}
+
+/// Check whether an archive is compressed already (zstd would gain little)
+///
+/// Tarballs are always compressed; zips count as compressed when deflating
+/// saved at least a tenth of their size.
+fn is_compressed_archive(path: &Path, archive_ext: &str) -> bool {
+    if archive_ext != ".zip" {
+        return true;
+    }
+    let Some(mut archive) = File::open(path)
+        .ok()
+        .and_then(|file| zip::ZipArchive::new(file).ok())
+    else {
+        return true;
+    };
+
+    let (mut compressed, mut original) = (0u64, 0u64);
+    for i in 0..archive.len() {
+        if let Ok(entry) = archive.by_index_raw(i) {
+            compressed += entry.compressed_size();
+            original += entry.size();
+        }
+    }
+    compressed.saturating_mul(10) <= original.saturating_mul(9)
 }
 
 pub fn create_bundle(
     manifest_path: &Path,
     archive_path: &Path,
     output_path: &Path,
+    signing_key: Option<&SigningKey>,
 ) -> Result<(), BundleError> {
     let current_exe = std::env::args()
         .next()
@@ -118,8 +413,25 @@ pub fn create_bundle(
             "cannot create bundle from an existing bundle".to_string(),
         ));
     }
+    write_bundle(
+        &current_exe,
+        manifest_path,
+        archive_path,
+        output_path,
+        signing_key,
+    )
+}
 
-    let manifest_content = fs::read_to_string(manifest_path).map_err(|e| {
+/// Write a bundle of `stub` (the voidbox binary) followed by the payload
+fn write_bundle(
+    stub: &Path,
+    manifest_path: &Path,
+    archive_path: &Path,
+    output_path: &Path,
+    signing_key: Option<&SigningKey>,
+) -> Result<(), BundleError> {
+    // A bundle can't carry the manifests this one extends
+    let manifest_content = flatten_manifest_file(manifest_path).map_err(|e| {
         BundleError::InvalidBundle(format!(
             "read manifest {}: {}",
             manifest_path.display(),
@@ -129,15 +441,10 @@ pub fn create_bundle(
     let archive_ext = detect_archive_extension(archive_path);
 
     let manifest_bytes = manifest_content.as_bytes();
-    let archive_len = fs::metadata(archive_path)
-        .map_err(|e| {
-            BundleError::InvalidBundle(format!(
-                "stat archive {}: {}",
-                archive_path.display(),
-                e
-            ))
-        })?
-        .len();
+    fs::metadata(archive_path).map_err(|e| {
+        BundleError::InvalidBundle(format!("stat archive {}: {}", archive_path.display(), e))
+    })?;
+    let compress = !is_compressed_archive(archive_path, &archive_ext);
     let ext_bytes = archive_ext.as_bytes();
 
     if manifest_bytes.len() > u32::MAX as usize {
@@ -158,13 +465,8 @@ pub fn create_bundle(
             e
         ))
     })?;
-    let mut self_file = File::open(&current_exe).map_err(|e| {
-        BundleError::InvalidBundle(format!(
-            "open self {}: {}",
-            current_exe.display(),
-            e
-        ))
-    })?;
+    let mut self_file = File::open(stub)
+        .map_err(|e| BundleError::InvalidBundle(format!("open self {}: {}", stub.display(), e)))?;
     std::io::copy(&mut self_file, &mut out).map_err(|e| {
         BundleError::InvalidBundle(format!(
             "copy self to {}: {}",
@@ -173,6 +475,7 @@ pub fn create_bundle(
         ))
     })?;
 
+    let payload_start = out.stream_position()?;
     out.write_all(&(manifest_bytes.len() as u32).to_le_bytes())
         .map_err(|e| BundleError::InvalidBundle(format!("write manifest len: {}", e)))?;
     out.write_all(manifest_bytes)
@@ -189,7 +492,12 @@ pub fn create_bundle(
             e
         ))
     })?;
-    std::io::copy(&mut archive_file, &mut out).map_err(|e| {
+    let appended = if compress {
+        zstd::stream::copy_encode(&mut archive_file, &mut out, ZSTD_LEVEL)
+    } else {
+        std::io::copy(&mut archive_file, &mut out).map(drop)
+    };
+    appended.map_err(|e| {
         BundleError::InvalidBundle(format!(
             "append archive {}: {}",
             archive_path.display(),
@@ -197,8 +505,26 @@ pub fn create_bundle(
         ))
     })?;
 
-    let payload_len =
-        4u64 + manifest_bytes.len() as u64 + 2u64 + ext_bytes.len() as u64 + archive_len;
+    let mut footer = BundleFooter {
+        payload_len: out.stream_position()? - payload_start,
+        version: BUNDLE_VERSION,
+        flags: if compress { FLAG_ZSTD } else { 0 },
+        signature: None,
+    };
+    let payload_len = footer.payload_len;
+
+    let mut signature_block = vec![0u8; SIGNATURE_BLOCK_LEN as usize];
+    if let Some(key) = signing_key {
+        let mut written = File::open(output_path)?;
+        let message = signed_message(&mut written, payload_start, &footer)?;
+        footer.flags |= FLAG_SIGNED;
+        signature_block[1..33].copy_from_slice(key.verifying_key().as_bytes());
+        signature_block[33..].copy_from_slice(&key.sign(&message).to_bytes());
+    }
+    signature_block[0] = footer.flags;
+    out.write_all(&signature_block)
+        .map_err(|e| BundleError::InvalidBundle(format!("write signature: {}", e)))?;
+
     out.write_all(BUNDLE_MAGIC)
         .map_err(|e| BundleError::InvalidBundle(format!("write magic: {}", e)))?;
     out.write_all(&[BUNDLE_VERSION])
@@ -228,19 +554,22 @@ pub fn manifest_info_from_file(path: &Path) -> Result<Option<BundleManifestInfo>
         Some(footer) => footer,
         None => return Ok(None),
     };
-    if footer.version != BUNDLE_VERSION {
-        return Err(BundleError::UnsupportedVersion(footer.version));
-    }
+    footer.check_version()?;
 
     let file_len = file.metadata()?.len();
-    let payload_start = file_len - FOOTER_LEN - footer.payload_len;
-    let payload = read_payload_header(&mut file, payload_start, footer.payload_len)?;
+    file.seek(SeekFrom::Start(footer.payload_start(file_len)))?;
+    let payload = read_payload_header(&mut file, &footer)?;
 
     let manifest = parse_manifest_str(&payload.manifest_content)?;
     Ok(Some(BundleManifestInfo {
         app_name: manifest.app.name,
         display_name: manifest.app.display_name,
         manifest_content: payload.manifest_content,
+        format_version: footer.version,
+        payload_size: footer.payload_len,
+        archive_ext: payload.archive_ext,
+        archive_size: payload.archive_len,
+        compressed: payload.compressed,
     }))
 }
 
@@ -249,19 +578,22 @@ fn has_bundle(path: &Path) -> Result<bool, BundleError> {
     Ok(read_footer(&mut file)?.is_some())
 }
 
+/// Manifest and archive type at the start of the payload; the archive
+/// follows right after
 struct PayloadHeader {
     manifest_content: String,
     archive_ext: String,
-    archive_offset: u64,
+    /// Stored size of the archive (compressed size if `compressed`)
     archive_len: u64,
+    compressed: bool,
 }
 
+/// Read the payload header from `file`, positioned at the payload start
 fn read_payload_header(
-    file: &mut File,
-    payload_start: u64,
-    payload_len: u64,
+    file: &mut impl Read,
+    footer: &BundleFooter,
 ) -> Result<PayloadHeader, BundleError> {
-    file.seek(SeekFrom::Start(payload_start))?;
+    let payload_len = footer.payload_len;
 
     let mut len_buf = [0u8; 4];
     file.read_exact(&mut len_buf)?;
@@ -289,10 +621,7 @@ fn read_payload_header(
     file.read_exact(&mut ext_bytes)?;
     let archive_ext = String::from_utf8(ext_bytes)?;
 
-    let current_pos = file.seek(SeekFrom::Current(0))?;
-    let header_len = current_pos
-        .checked_sub(payload_start)
-        .ok_or_else(|| BundleError::InvalidBundle("invalid payload offsets".to_string()))?;
+    let header_len = 4 + manifest_len + 2 + ext_len;
     let archive_len = payload_len
         .checked_sub(header_len)
         .ok_or_else(|| BundleError::InvalidBundle("invalid payload size".to_string()))?;
@@ -300,8 +629,8 @@ fn read_payload_header(
     Ok(PayloadHeader {
         manifest_content,
         archive_ext,
-        archive_offset: current_pos,
         archive_len,
+        compressed: footer.flags & FLAG_ZSTD != 0,
     })
 }
 
@@ -326,28 +655,53 @@ fn read_footer(file: &mut File) -> Result<Option<BundleFooter>, BundleError> {
     file.read_exact(&mut payload_buf)?;
     let payload_len = u64::from_le_bytes(payload_buf);
 
-    if payload_len + FOOTER_LEN > len {
+    let trailer_len = FOOTER_LEN + signature_block_len(version);
+    if payload_len.checked_add(trailer_len).is_none_or(|end| end > len) {
         return Err(BundleError::InvalidBundle(
             "payload length out of bounds".to_string(),
         ));
     }
 
-    Ok(Some(BundleFooter { payload_len, version }))
+    let mut flags = 0;
+    let mut signature = None;
+    if version >= 2 {
+        let mut block = [0u8; SIGNATURE_BLOCK_LEN as usize];
+        file.seek(SeekFrom::End(-(trailer_len as i64)))?;
+        file.read_exact(&mut block)?;
+        flags = block[0];
+        if flags & FLAG_SIGNED != 0 {
+            let mut public_key = [0u8; 32];
+            let mut sig = [0u8; 64];
+            public_key.copy_from_slice(&block[1..33]);
+            sig.copy_from_slice(&block[33..]);
+            signature = Some((public_key, sig));
+        }
+    }
+
+    Ok(Some(BundleFooter {
+        payload_len,
+        version,
+        flags,
+        signature,
+    }))
 }
 
+/// Create a fresh private (0700) temp dir with a random name
+///
+/// Never reuses an existing dir, so concurrent installs can't clobber each
+/// other's archive and other users can't plant files in ours.
 fn create_temp_dir() -> Result<PathBuf, BundleError> {
-    let mut dir = std::env::temp_dir();
-    let since_epoch = SystemTime::now()
-        .duration_since(UNIX_EPOCH)
-        .unwrap_or_default()
-        .as_millis();
-    dir.push(format!(
-        "voidbox-bundle-{}-{}",
-        std::process::id(),
-        since_epoch
-    ));
-    fs::create_dir_all(&dir)?;
-    Ok(dir)
+    let mut urandom = File::open("/dev/urandom")?;
+    loop {
+        let mut suffix = [0u8; 8];
+        urandom.read_exact(&mut suffix)?;
+        let dir = std::env::temp_dir().join(format!("voidbox-bundle-{}", hex::encode(suffix)));
+        match fs::DirBuilder::new().mode(0o700).create(&dir) {
+            Ok(()) => return Ok(dir),
+            Err(e) if e.kind() == std::io::ErrorKind::AlreadyExists => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
 }
 
 fn detect_archive_extension(path: &Path) -> String {
@@ -364,3 +718,142 @@ fn detect_archive_extension(path: &Path) -> String {
         ".zip".to_string()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Cursor;
+
+    const MANIFEST: &str = r#"[app]
+name = "demo"
+display_name = "Demo"
+
+[source]
+type = "local"
+path = "demo.zip"
+
+[runtime]
+base = "ubuntu:24.04"
+
+[binary]
+name = "demo"
+"#;
+
+    /// Scratch directory removed when dropped
+    struct Scratch(PathBuf);
+
+    impl Scratch {
+        fn new(name: &str) -> Self {
+            let dir =
+                std::env::temp_dir().join(format!("voidbox-test-{}-{}", name, std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+    }
+
+    impl Drop for Scratch {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    /// Zip with one uncompressed entry, which the bundle stores zstd-compressed
+    fn stored_zip() -> Vec<u8> {
+        let mut writer = zip::ZipWriter::new(Cursor::new(Vec::new()));
+        let options = zip::write::SimpleFileOptions::default()
+            .compression_method(zip::CompressionMethod::Stored);
+        writer.start_file("demo/demo", options).unwrap();
+        writer.write_all(&[b'x'; 64 * 1024]).unwrap();
+        writer.finish().unwrap().into_inner()
+    }
+
+    /// Bundle `archive` (named `archive_name`) with a small stub binary
+    fn bundle(scratch: &Scratch, archive_name: &str, archive: &[u8]) -> PathBuf {
+        let stub = scratch.0.join("stub");
+        let manifest = scratch.0.join("demo.toml");
+        let archive_path = scratch.0.join(archive_name);
+        let output = scratch.0.join("demo.voidbox");
+        fs::write(&stub, b"#!/bin/sh\nexit 0\n").unwrap();
+        fs::write(&manifest, MANIFEST).unwrap();
+        fs::write(&archive_path, archive).unwrap();
+        write_bundle(&stub, &manifest, &archive_path, &output, None).unwrap();
+        output
+    }
+
+    #[test]
+    fn compressed_payload_round_trips() {
+        let scratch = Scratch::new("bundle-zstd");
+        let archive = stored_zip();
+        let output = bundle(&scratch, "demo.zip", &archive);
+
+        let info = manifest_info_from_file(&output).unwrap().unwrap();
+        assert!(info.compressed);
+        assert!(info.archive_size < archive.len() as u64);
+
+        let extracted = extract_bundle_from_file(&output, &[]).unwrap();
+        assert_eq!(extracted.manifest_content, MANIFEST);
+        assert_eq!(extracted.archive_ext, ".zip");
+        assert_eq!(fs::read(&extracted.archive_path).unwrap(), archive);
+    }
+
+    #[test]
+    fn uncompressed_payload_round_trips() {
+        let scratch = Scratch::new("bundle-raw");
+        let archive = b"already compressed tarball bytes".to_vec();
+        let output = bundle(&scratch, "demo.tar.gz", &archive);
+
+        let info = manifest_info_from_file(&output).unwrap().unwrap();
+        assert!(!info.compressed);
+        assert_eq!(info.archive_size, archive.len() as u64);
+
+        let extracted = extract_bundle_from_file(&output, &[]).unwrap();
+        assert_eq!(extracted.manifest_content, MANIFEST);
+        assert_eq!(extracted.archive_ext, ".tar.gz");
+        assert_eq!(fs::read(&extracted.archive_path).unwrap(), archive);
+    }
+
+    #[test]
+    fn extraction_checks_the_signature_of_what_it_extracts() {
+        let scratch = Scratch::new("bundle-signed");
+        let stub = scratch.0.join("stub");
+        let manifest = scratch.0.join("demo.toml");
+        let archive = scratch.0.join("demo.tar.gz");
+        let output = scratch.0.join("demo.voidbox");
+        fs::write(&stub, b"#!/bin/sh\nexit 0\n").unwrap();
+        fs::write(&manifest, MANIFEST).unwrap();
+        fs::write(&archive, b"archive").unwrap();
+        let key = SigningKey::from_bytes(&[7; 32]);
+        write_bundle(&stub, &manifest, &archive, &output, Some(&key)).unwrap();
+
+        let trusted = [key.verifying_key()];
+        let extracted = extract_bundle_from_file(&output, &trusted).unwrap();
+        assert!(matches!(extracted.signature, BundleSignature::Trusted(_)));
+        let extracted = extract_bundle_from_file(&output, &[]).unwrap();
+        assert!(matches!(extracted.signature, BundleSignature::Untrusted(_)));
+
+        // Flip a byte of the archive: the signature no longer matches
+        let mut bytes = fs::read(&output).unwrap();
+        let at = bytes.windows(7).rposition(|w| w == b"archive").unwrap();
+        bytes[at] ^= 1;
+        fs::write(&output, bytes).unwrap();
+        assert!(matches!(
+            extract_bundle_from_file(&output, &trusted),
+            Err(BundleError::InvalidSignature(_))
+        ));
+    }
+
+    #[test]
+    fn extracted_temp_dir_is_removed_on_drop() {
+        let scratch = Scratch::new("bundle-drop");
+        let output = bundle(&scratch, "demo.tar.gz", b"archive");
+
+        let extracted = extract_bundle_from_file(&output, &[]).unwrap();
+        let temp_dir = extracted.temp_dir.clone();
+        assert!(extracted.archive_path.starts_with(&temp_dir));
+        assert!(extracted.archive_path.is_file());
+
+        drop(extracted);
+        assert!(!temp_dir.exists());
+    }
+}
@@ -1,8 +1,9 @@
 //! Bundle command implementation
 
-use crate::bundle;
-use crate::manifest::parse_manifest_str;
+use crate::bundle::{self, BundleSignature};
+use crate::manifest::{AppManifest, InstallSource, SourceConfig, parse_manifest_str};
 use crate::storage::paths;
+use indicatif::HumanBytes;
 use std::path::Path;
 use thiserror::Error;
 
@@ -22,50 +23,237 @@ pub enum BundleCliError {
 
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
+
+    #[error("Bundle not verified: {0}")]
+    Unverified(String),
 }
 
 pub fn bundle_create(
     manifest_path: &Path,
     archive_path: &Path,
     output_path: Option<&Path>,
+    sign_key: Option<&Path>,
 ) -> Result<(), BundleCliError> {
-    let output = output_path
-        .map(|p| p.to_path_buf())
-        .unwrap_or_else(|| {
-            let name = manifest_path
-                .file_stem()
-                .and_then(|s| s.to_str())
-                .unwrap_or("app");
-            Path::new(&format!("{}.voidbox", name)).to_path_buf()
-        });
+    let signing_key = sign_key.map(bundle::load_signing_key).transpose()?;
+    let output = output_path.map(|p| p.to_path_buf()).unwrap_or_else(|| {
+        let name = manifest_path
+            .file_stem()
+            .and_then(|s| s.to_str())
+            .unwrap_or("app");
+        Path::new(&format!("{}.voidbox", name)).to_path_buf()
+    });
+
+    crate::info!("Creating bundle: {}", output.to_string_lossy());
+    bundle::create_bundle(manifest_path, archive_path, &output, signing_key.as_ref())?;
+    if signing_key.is_none() {
+        crate::info!("Note: bundle is unsigned (use --sign-key to sign it).");
+    }
+    crate::info!("Bundle created successfully.");
+    Ok(())
+}
+
+/// Show what a bundle contains and asks for, without extracting it
+pub fn bundle_info(bundle_path: &Path) -> Result<(), BundleCliError> {
+    let info = bundle::manifest_info_from_file(bundle_path)?
+        .ok_or_else(|| bundle::BundleError::InvalidBundle("bundle footer not found".to_string()))?;
+    let manifest = parse_manifest_str(&info.manifest_content)?;
+
+    println!("{}", info.display_name);
+    println!("{}", "=".repeat(info.display_name.len()));
+    println!();
+    println!("Name:        {}", info.app_name);
+    if !manifest.app.description.is_empty() {
+        println!("Description: {}", manifest.app.description);
+    }
+    if let Some(version) = &manifest.app.version {
+        println!("Version:     {}", version);
+    }
+    println!("Source:      {}", describe_source(&manifest.source));
+    println!("Base:        {}", manifest.runtime.base);
 
+    println!();
+    println!("Format:      v{}", info.format_version);
+    println!("Payload:     {}", HumanBytes(info.payload_size));
     println!(
-        "[voidbox] Creating bundle: {}",
-        output.to_string_lossy()
+        "Archive:     {} ({}{})",
+        HumanBytes(info.archive_size),
+        info.archive_ext,
+        if info.compressed { ", zstd" } else { "" }
     );
-    bundle::create_bundle(manifest_path, archive_path, &output)?;
-    println!("[voidbox] Bundle created successfully.");
+    let trusted_keys = bundle::load_trusted_keys()?;
+    let signature = match bundle::verify_signature(bundle_path, &trusted_keys) {
+        Ok(BundleSignature::Trusted(key)) => format!("trusted ({})", key),
+        Ok(BundleSignature::Untrusted(key)) => format!("untrusted key ({})", key),
+        Ok(BundleSignature::Unsigned) => "unsigned".to_string(),
+        Err(e) => format!("INVALID ({})", e),
+    };
+    println!("Signature:   {}", signature);
+
+    print_requested_access(&manifest);
     Ok(())
 }
 
-pub fn bundle_install(bundle_path: &Path, run: bool) -> Result<(), BundleCliError> {
-    let extracted = bundle::extract_bundle_from_file(bundle_path)?;
+fn describe_source(source: &SourceConfig) -> String {
+    match source {
+        SourceConfig::Github { owner, repo, .. } => format!("GitHub {}/{}", owner, repo),
+        SourceConfig::Gitlab { project_id, .. } => format!("GitLab {}", project_id),
+        SourceConfig::Direct { url, .. } => url.clone(),
+        SourceConfig::Local { path, .. } => format!("local {}", path.display()),
+    }
+}
+
+/// Permissions, mounts and install hooks the bundle's manifest asks for
+fn print_requested_access(manifest: &AppManifest) {
+    let yes_no = |value: bool| if value { "yes" } else { "no" };
+    let perms = &manifest.permissions;
+
+    println!();
+    println!("Permissions:");
+    println!("  Network:         {}", yes_no(perms.network));
+    println!("  Audio:           {}", yes_no(perms.audio));
+    println!("  Microphone:      {}", yes_no(perms.microphone));
+    println!("  GPU:             {}", yes_no(perms.gpu));
+    println!("  Camera:          {}", yes_no(perms.camera));
+    println!("  Home:            {}", yes_no(perms.home));
+    println!("  Downloads:       {}", yes_no(perms.downloads));
+    println!("  Removable media: {}", yes_no(perms.removable_media));
+    println!("  Dev mode:        {}", yes_no(perms.dev_mode));
+    println!("  Native mode:     {}", yes_no(perms.native_mode));
+    println!("  D-Bus filter:    {}", yes_no(perms.dbus_filter));
+    println!(
+        "  Seccomp:         {}",
+        format!("{:?}", manifest.security.seccomp).to_lowercase()
+    );
+
+    if !manifest.mounts.is_empty() {
+        println!();
+        println!("Extra mounts:");
+        for mount in &manifest.mounts {
+            let access = if mount.readonly {
+                "read-only"
+            } else {
+                "read-write"
+            };
+            println!(
+                "  {} -> /{} ({})",
+                mount.source,
+                mount.target.trim_start_matches('/'),
+                access
+            );
+        }
+    }
+
+    if perms.native_mode {
+        println!();
+        if manifest.bridge.allowed_commands.is_empty() {
+            println!("Host bridge: any command");
+        } else {
+            println!(
+                "Host bridge: {}",
+                manifest.bridge.allowed_commands.join(", ")
+            );
+        }
+    }
+
+    let hooks = [
+        ("pre_install", &manifest.hooks.pre_install),
+        ("post_install", &manifest.hooks.post_install),
+    ];
+    for (name, script) in hooks {
+        if let Some(script) = script {
+            println!();
+            println!("Install hook {} (runs as root in the sandbox):", name);
+            for line in script.lines() {
+                println!("  {}", line);
+            }
+        }
+    }
+}
+
+/// Create a bundle signing key and print its public key
+pub fn bundle_keygen(key_path: &Path) -> Result<(), BundleCliError> {
+    let public_key = bundle::generate_signing_key(key_path)?;
+    crate::info!("Signing key written to {}", key_path.display());
+    crate::info!("Public key: {}", public_key);
+    crate::info!(
+        "To trust it, add the public key to {}",
+        paths::trusted_keys_path().display()
+    );
+    Ok(())
+}
+
+pub fn bundle_install(
+    bundle_path: &Path,
+    run: bool,
+    allow_unsigned: bool,
+) -> Result<Option<crate::cli::RunOutcome>, BundleCliError> {
+    let trusted_keys = bundle::load_trusted_keys()?;
+    let extracted = bundle::extract_bundle_from_file(bundle_path, &trusted_keys)?;
+    check_signature(&extracted.signature, |_| allow_unsigned)?;
     let manifest_content = extracted.manifest_content.clone();
     let manifest = parse_manifest_str(&manifest_content)?;
 
     paths::ensure_dirs()?;
+    let options = crate::cli::InstallOptions {
+        source: Some(InstallSource::Bundle {
+            path: std::fs::canonicalize(bundle_path)?,
+        }),
+        ..Default::default()
+    };
     let install_result = crate::cli::install_app_from_bundle(
         &manifest_content,
         &extracted.archive_path,
         &extracted.archive_ext,
-        false,
+        &options,
     );
-    extracted.cleanup();
+    drop(extracted);
     install_result?;
 
-    if run {
-        crate::cli::run_app(&manifest.app.name, &[], None, false)?;
+    if !run {
+        return Ok(None);
     }
+    let outcome = crate::cli::run_app(&manifest.app.name, &[], &crate::cli::RunOptions::default())?;
+    Ok(Some(outcome))
+}
 
-    Ok(())
+/// Check a self-extracting bundle's signature before it installs itself
+///
+/// A bundle run directly has no `--allow-unsigned`, so the user is asked
+/// instead; nobody to ask means no.
+pub fn check_embedded_signature(signature: &BundleSignature) -> Result<(), BundleCliError> {
+    check_signature(signature, |problem| {
+        crate::gui::ask_yes_no(
+            "Unverified bundle",
+            &format!("This {}.\n\nInstall it anyway?", problem),
+        )
+    })
+}
+
+/// Refuse bundles not signed by a trusted key unless `allow_unsigned` agrees
+///
+/// `allow_unsigned` is only called for such bundles, with what is wrong.
+fn check_signature(
+    signature: &BundleSignature,
+    allow_unsigned: impl FnOnce(&str) -> bool,
+) -> Result<(), BundleCliError> {
+    let problem = match signature {
+        BundleSignature::Trusted(key) => {
+            crate::info!("Signature verified (key {}).", key);
+            return Ok(());
+        }
+        BundleSignature::Untrusted(key) => {
+            format!("bundle is signed by an untrusted key ({})", key)
+        }
+        BundleSignature::Unsigned => "bundle is not signed".to_string(),
+    };
+
+    if allow_unsigned(&problem) {
+        crate::warn!("Warning: {} - installing anyway.", problem);
+        return Ok(());
+    }
+    Err(BundleCliError::Unverified(format!(
+        "{}. Trust the signer in {} or pass --allow-unsigned.",
+        problem,
+        paths::trusted_keys_path().display()
+    )))
 }
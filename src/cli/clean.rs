@@ -0,0 +1,124 @@
+//! Clean command implementation
+
+use crate::cli::update::get_all_deps_ids;
+use crate::storage::{
+    InstallLock, dir_size, paths, read_base_info_for_rootfs, remove_dir_all_force, store_ids_in_use,
+};
+use indicatif::HumanBytes;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum CleanError {
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Clean failed: {0}")]
+    Failed(String),
+}
+
+/// Remove base images, dependency layers and stored app files no installed
+/// app references
+pub fn clean(dry_run: bool) -> Result<(), CleanError> {
+    // An install could otherwise start using a base, deps layer or store
+    // entry found unused here
+    let _lock = InstallLock::acquire()?;
+    let live_bases = get_all_base_ids()?;
+    let live_deps = get_all_deps_ids()?;
+    let live_store = store_ids_in_use().map_err(|e| CleanError::Failed(e.to_string()))?;
+
+    let mut orphans = find_orphans(&paths::bases_dir(), &live_bases)?;
+    orphans.extend(find_orphans(&paths::deps_dir(), &live_deps)?);
+    orphans.extend(find_orphans(&paths::store_dir(), &live_store)?);
+
+    if orphans.is_empty() {
+        crate::info!("Nothing to clean.");
+        return Ok(());
+    }
+
+    let mut reclaimed = 0u64;
+    for path in &orphans {
+        let size = dir_size(path);
+        if dry_run {
+            println!("  Would remove {} ({})", path.display(), HumanBytes(size));
+        } else {
+            if path.is_dir() {
+                remove_dir_all_force(path)?;
+            } else {
+                fs::remove_file(path)?;
+            }
+            println!("  Removed {} ({})", path.display(), HumanBytes(size));
+        }
+        reclaimed += size;
+    }
+
+    println!();
+    if dry_run {
+        crate::info!(
+            "{} would be reclaimed (dry run, nothing removed).",
+            HumanBytes(reclaimed)
+        );
+    } else {
+        crate::info!("Reclaimed {}.", HumanBytes(reclaimed));
+    }
+
+    Ok(())
+}
+
+/// Get the base IDs referenced by installed apps
+///
+/// Fails if any app's base info is unreadable, so a corrupt base.json can
+/// never cause a base that is still in use to be pruned.
+fn get_all_base_ids() -> Result<HashSet<String>, CleanError> {
+    let mut base_ids = HashSet::new();
+    let apps_dir = paths::apps_dir();
+
+    if !apps_dir.exists() {
+        return Ok(base_ids);
+    }
+
+    for entry in fs::read_dir(&apps_dir)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+        let app_name = entry.file_name().to_string_lossy().to_string();
+        let rootfs = paths::app_rootfs_dir(&app_name);
+        match read_base_info_for_rootfs(&rootfs) {
+            Ok(Some(info)) => {
+                base_ids.insert(paths::base_id(&info.base, &info.arch));
+            }
+            Ok(None) => {}
+            Err(e) => {
+                return Err(CleanError::Failed(format!(
+                    "could not read base info for {}: {}",
+                    app_name, e
+                )));
+            }
+        }
+    }
+
+    Ok(base_ids)
+}
+
+/// List entries of a shared directory whose name isn't in the live set
+fn find_orphans(dir: &Path, live: &HashSet<String>) -> Result<Vec<PathBuf>, CleanError> {
+    let mut orphans = Vec::new();
+
+    if !dir.exists() {
+        return Ok(orphans);
+    }
+
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        let name = entry.file_name().to_string_lossy().to_string();
+        if !live.contains(&name) {
+            orphans.push(entry.path());
+        }
+    }
+
+    orphans.sort();
+    Ok(orphans)
+}
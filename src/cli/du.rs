@@ -0,0 +1,138 @@
+//! Disk usage command implementation
+
+use crate::cli::get_installed_apps;
+use crate::storage::{dir_size, paths};
+use indicatif::HumanBytes;
+use serde::Serialize;
+use std::fs;
+use std::path::Path;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum DuError {
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("List error: {0}")]
+    ListError(#[from] crate::cli::ListError),
+
+    #[error("Failed to serialize report: {0}")]
+    SerializeError(#[from] serde_json::Error),
+}
+
+#[derive(Debug, Serialize)]
+struct UsageEntry {
+    name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    display_name: Option<String>,
+    bytes: u64,
+}
+
+#[derive(Debug, Serialize)]
+struct UsageReport {
+    apps: Vec<UsageEntry>,
+    bases: Vec<UsageEntry>,
+    deps: Vec<UsageEntry>,
+    store: Vec<UsageEntry>,
+    total: u64,
+}
+
+/// Show disk usage per installed app plus shared bases, deps layers and
+/// stored app files
+pub fn disk_usage(json: bool) -> Result<(), DuError> {
+    let mut apps: Vec<UsageEntry> = get_installed_apps()?
+        .into_iter()
+        .map(|app| UsageEntry {
+            bytes: dir_size(&paths::app_dir(&app.name)),
+            name: app.name,
+            display_name: Some(app.display_name),
+        })
+        .collect();
+    apps.sort_by_key(|entry| std::cmp::Reverse(entry.bytes));
+
+    let bases = shared_usage(&paths::bases_dir())?;
+    let deps = shared_usage(&paths::deps_dir())?;
+    let store = shared_usage(&paths::store_dir())?;
+
+    let total = apps
+        .iter()
+        .chain(bases.iter())
+        .chain(deps.iter())
+        .chain(store.iter())
+        .map(|entry| entry.bytes)
+        .sum();
+
+    let report = UsageReport {
+        apps,
+        bases,
+        deps,
+        store,
+        total,
+    };
+
+    if json {
+        println!("{}", serde_json::to_string_pretty(&report)?);
+        return Ok(());
+    }
+
+    println!("Apps:");
+    if report.apps.is_empty() {
+        println!("  (none installed)");
+    }
+    for entry in &report.apps {
+        let label = match &entry.display_name {
+            Some(display_name) => format!("{} ({})", display_name, entry.name),
+            None => entry.name.clone(),
+        };
+        println!(
+            "  {:<40} {:>12}",
+            label,
+            HumanBytes(entry.bytes).to_string()
+        );
+    }
+
+    print_shared_section("Shared base images:", &report.bases);
+    print_shared_section("Shared dependency layers:", &report.deps);
+    print_shared_section("Shared app files:", &report.store);
+
+    println!();
+    println!("Total: {}", HumanBytes(report.total));
+
+    Ok(())
+}
+
+fn print_shared_section(title: &str, entries: &[UsageEntry]) {
+    if entries.is_empty() {
+        return;
+    }
+    println!();
+    println!("{}", title);
+    for entry in entries {
+        println!(
+            "  {:<40} {:>12}",
+            entry.name,
+            HumanBytes(entry.bytes).to_string()
+        );
+    }
+}
+
+/// Size of each entry in a shared directory (bases, deps or store), largest first
+fn shared_usage(dir: &Path) -> Result<Vec<UsageEntry>, DuError> {
+    let mut entries = Vec::new();
+
+    if !dir.exists() {
+        return Ok(entries);
+    }
+
+    for entry in fs::read_dir(dir)? {
+        let entry = entry?;
+        entries.push(UsageEntry {
+            name: entry.file_name().to_string_lossy().to_string(),
+            display_name: None,
+            bytes: dir_size(&entry.path()),
+        });
+    }
+
+    entries.sort_by_key(|entry| std::cmp::Reverse(entry.bytes));
+    Ok(entries)
+}
@@ -0,0 +1,167 @@
+//! Edit command implementation
+
+use crate::manifest::{AppManifest, parse_manifest_str, validate_manifest};
+use crate::storage::{paths, write_atomic};
+use serde::Serialize;
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+use std::process::Command;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum EditError {
+    #[error("App not installed: {0}")]
+    NotInstalled(String),
+
+    #[error("No editor found - set $EDITOR")]
+    NoEditor,
+
+    #[error("Editor exited with {0}")]
+    EditorFailed(std::process::ExitStatus),
+
+    #[error("Manifest left unchanged: {0}")]
+    Invalid(String),
+
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+}
+
+/// Editors tried when $EDITOR is not set
+const FALLBACK_EDITORS: &[&str] = &["nano", "vi"];
+
+/// Open an installed app's manifest in $EDITOR
+///
+/// The editor works on a copy. It only replaces the manifest once it parses
+/// and validates, so a typo can't break the app's next launch.
+pub fn edit_manifest(app_name: &str) -> Result<(), EditError> {
+    let manifest_path = paths::manifest_path(app_name);
+    if !manifest_path.exists() {
+        return Err(EditError::NotInstalled(app_name.to_string()));
+    }
+
+    let original = fs::read_to_string(&manifest_path)?;
+    let draft_path = paths::manifests_dir().join(format!(".{}.edit.toml", app_name));
+    fs::write(&draft_path, &original)?;
+
+    let result = edit_until_valid(app_name, &original, &draft_path);
+    let _ = fs::remove_file(&draft_path);
+    let Some((content, manifest)) = result? else {
+        crate::info!("No changes.");
+        return Ok(());
+    };
+
+    write_atomic(&manifest_path, content)?;
+    crate::info!("Saved {}", manifest_path.display());
+
+    // An unparseable old manifest can't be compared - assume the worst
+    let changed = parse_manifest_str(&original)
+        .map(|old| reinstall_changes(&old, &manifest))
+        .unwrap_or_else(|_| vec!["manifest"]);
+    if changed.is_empty() {
+        crate::info!("Changes take effect on the next launch.");
+    } else {
+        crate::info!(
+            "Changed {} - run `voidbox reinstall {}` to apply.",
+            changed.join(", "),
+            app_name
+        );
+    }
+    Ok(())
+}
+
+/// Run the editor until the draft is valid or the user gives up
+///
+/// Returns `None` if the draft was saved unchanged.
+fn edit_until_valid(
+    app_name: &str,
+    original: &str,
+    draft_path: &Path,
+) -> Result<Option<(String, AppManifest)>, EditError> {
+    loop {
+        run_editor(draft_path)?;
+
+        let content = fs::read_to_string(draft_path)?;
+        if content == original {
+            return Ok(None);
+        }
+
+        let error = match parse_manifest_str(&content) {
+            Ok(manifest) if manifest.app.name != app_name => format!(
+                "app.name must stay \"{}\" (use `voidbox rename` to rename it)",
+                app_name
+            ),
+            Ok(manifest) => match validate_manifest(&manifest) {
+                Ok(()) => return Ok(Some((content, manifest))),
+                Err(e) => e.to_string(),
+            },
+            Err(e) => e.to_string(),
+        };
+
+        eprintln!("[voidbox] {}", error);
+        print!("Edit again? [Y/n] ");
+        std::io::stdout().flush()?;
+        let mut input = String::new();
+        std::io::stdin().read_line(&mut input)?;
+        if input.trim().eq_ignore_ascii_case("n") {
+            return Err(EditError::Invalid(error));
+        }
+    }
+}
+
+/// Open `path` in $EDITOR, falling back to nano and vi
+fn run_editor(path: &Path) -> Result<(), EditError> {
+    let configured = std::env::var("EDITOR")
+        .ok()
+        .filter(|editor| !editor.trim().is_empty());
+    let candidates: Vec<String> = match configured {
+        Some(editor) => vec![editor],
+        None => FALLBACK_EDITORS.iter().map(|e| e.to_string()).collect(),
+    };
+
+    for editor in &candidates {
+        // $EDITOR may carry flags, e.g. "code --wait"
+        let mut words = editor.split_whitespace();
+        let Some(program) = words.next() else {
+            continue;
+        };
+        match Command::new(program).args(words).arg(path).status() {
+            Ok(status) if status.success() => return Ok(()),
+            Ok(status) => return Err(EditError::EditorFailed(status)),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => continue,
+            Err(e) => return Err(e.into()),
+        }
+    }
+    Err(EditError::NoEditor)
+}
+
+/// Manifest sections that only take effect on install
+fn reinstall_changes(old: &AppManifest, new: &AppManifest) -> Vec<&'static str> {
+    fn differs<T: Serialize>(a: &T, b: &T) -> bool {
+        toml::Value::try_from(a).ok() != toml::Value::try_from(b).ok()
+    }
+
+    let mut changed = Vec::new();
+    if differs(&old.source, &new.source) {
+        changed.push("source");
+    }
+    if old.runtime.base != new.runtime.base {
+        changed.push("runtime.base");
+    }
+    if differs(&old.dependencies, &new.dependencies) {
+        changed.push("dependencies");
+    }
+    if old.binary.name != new.binary.name
+        || old.binary.path != new.binary.path
+        || old.binary.install_dir != new.binary.install_dir
+    {
+        changed.push("binary");
+    }
+    if differs(&old.desktop, &new.desktop) || old.app.display_name != new.app.display_name {
+        changed.push("desktop entry");
+    }
+    if differs(&old.hooks, &new.hooks) {
+        changed.push("hooks");
+    }
+    changed
+}
@@ -0,0 +1,135 @@
+//! Failure classes reported by the `voidbox` binary and installer GUI
+//!
+//! Each command has its own error type. These are sorted into a few classes
+//! by walking the error's source chain, so scripts can tell a network
+//! failure from a full disk by the exit code and the GUI can suggest a fix.
+
+use crate::cli::{InstallError, UpdateError};
+use crate::storage::{DownloadError, SpaceError, is_interrupted};
+use nix::errno::Errno;
+use std::error::Error;
+use std::io::ErrorKind;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum RunnerError {
+    /// A download or API request failed
+    #[error("{0}")]
+    Network(Box<dyn Error>),
+
+    /// The disk (or quota) is full
+    #[error("{0}")]
+    Disk(Box<dyn Error>),
+
+    /// The kernel or filesystem refused access
+    #[error("{0}")]
+    Permission(Box<dyn Error>),
+
+    /// Stopped by Ctrl-C or SIGTERM
+    #[error("{0}")]
+    Interrupted(Box<dyn Error>),
+
+    /// Anything else
+    #[error("{0}")]
+    Other(Box<dyn Error>),
+}
+
+impl RunnerError {
+    /// Process exit code for this failure class
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::Other(_) => 1,
+            Self::Network(_) => 2,
+            Self::Disk(_) => 3,
+            Self::Permission(_) => 4,
+            Self::Interrupted(_) => 130,
+        }
+    }
+
+    /// What the user can do about it, if there is anything specific
+    pub fn hint(&self) -> Option<&'static str> {
+        match self {
+            Self::Network(_) => {
+                Some("Check your internet connection, or retry later if a server is down.")
+            }
+            Self::Disk(_) => Some("Free up disk space (try `voidbox clean`) and retry."),
+            Self::Permission(_) => Some("Check the permissions of the files voidbox touches."),
+            Self::Interrupted(_) | Self::Other(_) => None,
+        }
+    }
+}
+
+impl From<Box<dyn Error>> for RunnerError {
+    fn from(error: Box<dyn Error>) -> Self {
+        // Whatever failed after Ctrl-C (a killed apt, a cut download) failed
+        // because of it
+        if is_interrupted() {
+            return Self::Interrupted(error);
+        }
+
+        let classify = |e: &(dyn Error + 'static)| -> Option<fn(Box<dyn Error>) -> Self> {
+            if let Some(e) = e.downcast_ref::<std::io::Error>() {
+                return io_error_class(e);
+            }
+            if let Some(errno) = e.downcast_ref::<Errno>() {
+                return errno_class(*errno);
+            }
+            let network = e.is::<ureq::Error>()
+                || matches!(e.downcast_ref(), Some(DownloadError::HttpError(_)))
+                || matches!(e.downcast_ref(), Some(InstallError::NetworkError(_)))
+                || matches!(e.downcast_ref(), Some(UpdateError::NetworkError(_)));
+            let disk = matches!(
+                e.downcast_ref(),
+                Some(InstallError::InsufficientSpace { .. })
+            ) || matches!(e.downcast_ref(), Some(SpaceError::Insufficient { .. }));
+            if network {
+                Some(Self::Network)
+            } else if disk {
+                Some(Self::Disk)
+            } else {
+                None
+            }
+        };
+
+        // The outermost error that says something specific wins
+        let class = std::iter::successors(Some(&*error), |&e| e.source()).find_map(classify);
+        class.unwrap_or(Self::Other)(error)
+    }
+}
+
+fn io_error_class(error: &std::io::Error) -> Option<fn(Box<dyn Error>) -> RunnerError> {
+    if let Some(class) = error
+        .raw_os_error()
+        .and_then(|code| errno_class(Errno::from_raw(code)))
+    {
+        return Some(class);
+    }
+    match error.kind() {
+        ErrorKind::StorageFull | ErrorKind::QuotaExceeded => Some(RunnerError::Disk),
+        ErrorKind::PermissionDenied | ErrorKind::ReadOnlyFilesystem => {
+            Some(RunnerError::Permission)
+        }
+        ErrorKind::ConnectionRefused
+        | ErrorKind::ConnectionReset
+        | ErrorKind::ConnectionAborted
+        | ErrorKind::HostUnreachable
+        | ErrorKind::NetworkUnreachable
+        | ErrorKind::NetworkDown
+        | ErrorKind::TimedOut => Some(RunnerError::Network),
+        _ => None,
+    }
+}
+
+fn errno_class(errno: Errno) -> Option<fn(Box<dyn Error>) -> RunnerError> {
+    match errno {
+        Errno::ENOSPC | Errno::EDQUOT => Some(RunnerError::Disk),
+        Errno::EPERM | Errno::EACCES | Errno::EROFS => Some(RunnerError::Permission),
+        Errno::ENETDOWN
+        | Errno::ENETUNREACH
+        | Errno::EHOSTUNREACH
+        | Errno::ECONNREFUSED
+        | Errno::ECONNRESET
+        | Errno::ETIMEDOUT => Some(RunnerError::Network),
+        _ => None,
+    }
+}
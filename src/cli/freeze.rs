@@ -0,0 +1,66 @@
+//! Freeze command implementation
+
+use crate::manifest::InstalledApp;
+use crate::storage::{lock_file, paths, write_atomic};
+use std::fs;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum FreezeError {
+    #[error("App not installed: {0}")]
+    NotInstalled(String),
+
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Freeze failed: {0}")]
+    Failed(String),
+}
+
+/// Hold an app at its current version (`frozen = true`) or release it
+///
+/// Frozen apps are skipped by `voidbox update` and get no update notices
+/// at launch. Unlike a pinned version, this survives reinstalls.
+pub fn set_app_frozen(app_name: &str, frozen: bool) -> Result<(), FreezeError> {
+    let db_path = paths::database_path();
+    if !db_path.exists() {
+        return Err(FreezeError::NotInstalled(app_name.to_string()));
+    }
+    let _lock = lock_file(&db_path)?;
+
+    let content = fs::read_to_string(&db_path)?;
+    let mut apps: Vec<InstalledApp> = serde_json::from_str(&content)
+        .map_err(|e| FreezeError::Failed(format!("Failed to parse database: {}", e)))?;
+
+    let app = apps
+        .iter_mut()
+        .find(|a| a.name == app_name)
+        .ok_or_else(|| FreezeError::NotInstalled(app_name.to_string()))?;
+    let version = app.version.clone().unwrap_or_else(|| "unknown".into());
+
+    if app.frozen == frozen {
+        crate::info!(
+            "{} is already {}.",
+            app.display_name,
+            if frozen { "frozen" } else { "not frozen" }
+        );
+        return Ok(());
+    }
+    app.frozen = frozen;
+    let display_name = app.display_name.clone();
+
+    let content = serde_json::to_string_pretty(&apps)
+        .map_err(|e| FreezeError::Failed(format!("Failed to serialize: {}", e)))?;
+    write_atomic(&db_path, content)?;
+
+    if frozen {
+        crate::info!(
+            "{} is frozen at v{} - updates will skip it.",
+            display_name,
+            version
+        );
+    } else {
+        crate::info!("{} will be updated again.", display_name);
+    }
+    Ok(())
+}
@@ -40,6 +40,10 @@ pub fn show_voidbox_info() -> Result<(), InfoError> {
 
     // Check for self-updates
     print!("Checking for updates... ");
+    if crate::storage::is_offline() {
+        println!("Skipped (offline)");
+        return Ok(());
+    }
     match check_latest_version() {
         Ok(latest) => {
             if latest == crate::VERSION {
@@ -85,6 +89,17 @@ pub fn show_app_info(app_name: &str) -> Result<(), InfoError> {
         if rootfs.exists() { "exists" } else { "missing" }
     );
     println!("Manifest:    {}", manifest_path.display());
+    let installed = crate::cli::get_installed_apps().unwrap_or_default();
+    if let Some(source) = installed
+        .into_iter()
+        .find(|app| app.name == app_name)
+        .and_then(|app| app.source)
+    {
+        println!("Source:      {}", source);
+    }
+    if manifest.runtime.persist_home {
+        println!("Home:        {}", paths::app_home_dir(app_name).display());
+    }
 
     // Show permissions
     println!();
@@ -1,19 +1,35 @@
 //! Install command implementation
 
-use crate::desktop::{create_app_wrapper, create_desktop_entry, extract_icon};
+use crate::desktop::{
+    create_app_wrapper, create_desktop_entry, extract_icon, refresh_desktop_caches,
+    register_mime_handlers, remove_app_wrapper, remove_desktop_entry,
+};
 use crate::manifest::{
-    AppManifest, ArchiveType, InstalledApp, SourceConfig, parse_manifest_file, parse_manifest_str,
-    parse_manifest_url, validate_manifest,
+    AppManifest, AptRepo, ArchiveType, InstallSource, InstalledApp, SourceConfig, host_ubuntu_arch,
+    parse_manifest_file, parse_manifest_str, parse_manifest_url, ubuntu_arch_name,
+    validate_manifest,
 };
 use crate::storage::{
-    BaseInfo, download_file, download_string, paths, write_base_info, write_base_info_for_dir,
+    BaseInfo, InstallLock, InterruptGuard, SpaceError, app_tree_id, check_free_space,
+    check_interrupted, download_file, download_file_with_progress, download_string,
+    estimate_install_size, github_token, http_get, is_interrupted, lock_file,
+    move_app_tree_to_store, paths, read_base_info_for_rootfs, remote_file_size,
+    remove_dir_all_force, remove_download, remove_unused_store_entry, sha256_file, write_atomic,
+    write_base_info, write_base_info_for_dir,
 };
 use flate2::read::GzDecoder;
-use serde::Deserialize;
+use indicatif::{HumanBytes, ProgressBar, ProgressStyle};
+use serde::{Deserialize, Serialize};
 use serde_json::Value;
 use std::fs::{self, File};
-use std::path::Path;
+use std::io::Read;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::mpsc::Sender;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+use std::time::Duration;
 use thiserror::Error;
 use walkdir::WalkDir;
 
@@ -34,8 +50,30 @@ pub enum InstallError {
     #[error("Installation failed: {0}")]
     Failed(String),
 
+    #[error("Network error: {0}")]
+    NetworkError(String),
+
     #[error("App already installed: {0}")]
     AlreadyInstalled(String),
+
+    #[error("Checksum mismatch for {file}: expected {expected}, got {actual}")]
+    ChecksumMismatch {
+        file: String,
+        expected: String,
+        actual: String,
+    },
+
+    #[error(
+        "Not enough disk space in {}: need {}, only {} available",
+        path.display(),
+        HumanBytes(*needed),
+        HumanBytes(*available)
+    )]
+    InsufficientSpace {
+        path: PathBuf,
+        needed: u64,
+        available: u64,
+    },
 }
 
 #[derive(Deserialize)]
@@ -48,22 +86,205 @@ struct GitHubRelease {
 struct GitHubAsset {
     name: String,
     browser_download_url: String,
+    /// API URL of the asset (works for private repos with a token)
+    url: String,
+}
+
+impl GitHubAsset {
+    /// URL to download the asset from
+    ///
+    /// With a GitHub token the API URL is used, since `browser_download_url`
+    /// does not accept token auth for private repos.
+    fn download_url(self) -> String {
+        if github_token().is_some() {
+            self.url
+        } else {
+            self.browser_download_url
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct GitLabRelease {
+    tag_name: String,
+    #[serde(default)]
+    assets: GitLabAssets,
+}
+
+#[derive(Deserialize, Default)]
+struct GitLabAssets {
+    #[serde(default)]
+    links: Vec<GitLabLink>,
+}
+
+#[derive(Deserialize)]
+struct GitLabLink {
+    name: String,
+    url: String,
+    #[serde(default)]
+    direct_asset_url: Option<String>,
+}
+
+/// Options for installing an app
+#[derive(Debug, Clone, Default)]
+pub struct InstallOptions {
+    /// Reinstall even if the app is already installed
+    pub force: bool,
+    /// Install this exact version instead of the latest release
+    pub version: Option<String>,
+    /// Receives install progress (None for CLI installs)
+    pub progress: Option<InstallProgress>,
+    /// Resolve sources and print what would be installed without touching disk
+    pub dry_run: bool,
+    /// Wipe and rebuild the shared dependency layer even if it is ready
+    pub rebuild_deps: bool,
+    /// Bind-mount a local source directory at run time instead of copying it
+    pub symlink_local: bool,
+    /// Install as the separate profile `<name>@<profile>`
+    pub profile: Option<String>,
+    /// Target architecture instead of the host's (amd64 or arm64)
+    pub arch: Option<String>,
+    /// Where the manifest came from (None keeps the app's recorded source)
+    pub source: Option<InstallSource>,
+}
+
+/// Install progress updates: overall fraction (0.0-1.0) and a status message
+pub type InstallProgress = Sender<(f32, String)>;
+
+fn report_progress(progress: Option<&InstallProgress>, fraction: f32, message: impl Into<String>) {
+    if let Some(sender) = progress {
+        let _ = sender.send((fraction, message.into()));
+    }
+}
+
+/// Extraction progress shown on the terminal and sent to the install progress
+/// channel as a percentage between `start` and `end`
+struct ExtractProgress<'a> {
+    bar: ProgressBar,
+    progress: Option<&'a InstallProgress>,
+    range: (f32, f32),
+    label: &'a str,
+    last_percent: Option<u64>,
+}
+
+impl<'a> ExtractProgress<'a> {
+    fn new(progress: Option<&'a InstallProgress>, range: (f32, f32), label: &'a str) -> Self {
+        let bar = ProgressBar::new(0);
+        bar.set_style(
+            ProgressStyle::default_bar()
+                .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {percent}%")
+                .unwrap()
+                .progress_chars("#>-"),
+        );
+        Self {
+            bar,
+            progress,
+            range,
+            label,
+            last_percent: None,
+        }
+    }
+
+    /// Record `done` of `total` units (files or archive bytes) extracted
+    fn update(&mut self, done: u64, total: u64) {
+        if total == 0 {
+            return;
+        }
+        self.bar.set_length(total);
+        self.bar.set_position(done);
+
+        let percent = done.min(total) * 100 / total;
+        if self.last_percent == Some(percent) {
+            return;
+        }
+        self.last_percent = Some(percent);
+        let (start, end) = self.range;
+        let fraction = start + (end - start) * percent as f32 / 100.0;
+        report_progress(
+            self.progress,
+            fraction,
+            format!("{} {}%", self.label, percent),
+        );
+    }
+
+    fn finish(self) {
+        self.bar.finish_and_clear();
+    }
+}
+
+/// Reader that reports the running total of bytes read
+struct CountingReader<'a, R> {
+    inner: R,
+    count: u64,
+    on_read: &'a mut dyn FnMut(u64),
+}
+
+impl<'a, R: Read> CountingReader<'a, R> {
+    fn new(inner: R, on_read: &'a mut dyn FnMut(u64)) -> Self {
+        Self {
+            inner,
+            count: 0,
+            on_read,
+        }
+    }
+}
+
+impl<R: Read> Read for CountingReader<'_, R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        check_interrupted()?;
+        let n = self.inner.read(buf)?;
+        self.count += n as u64;
+        (self.on_read)(self.count);
+        Ok(n)
+    }
+}
+
+/// Download a file, reporting its percentage as progress between `start` and `end`
+fn download_with_progress(
+    url: &str,
+    dest: &Path,
+    progress: Option<&InstallProgress>,
+    (start, end): (f32, f32),
+    label: &str,
+) -> Result<(), InstallError> {
+    let mut last_percent = None;
+    download_file_with_progress(url, dest, true, &mut |downloaded, total| {
+        if total == 0 {
+            return Ok(());
+        }
+        let percent = downloaded * 100 / total;
+        if last_percent == Some(percent) {
+            return Ok(());
+        }
+        last_percent = Some(percent);
+        let fraction = start + (end - start) * percent as f32 / 100.0;
+        report_progress(progress, fraction, format!("{} {}%", label, percent));
+        Ok(())
+    })?;
+    Ok(())
 }
 
 /// Install an app from a manifest source
-pub fn install_app(source: &str, force: bool) -> Result<(), InstallError> {
-    println!("[voidbox] Installing from {}...", source);
+pub fn install_app(source: &str, options: &InstallOptions) -> Result<(), InstallError> {
+    crate::info!("Installing from {}...", source);
 
     // Parse manifest based on source type
-    let manifest = if source.starts_with("http://") || source.starts_with("https://") {
-        parse_manifest_url(source)?
+    let (manifest, origin) = if source.starts_with("http://") || source.starts_with("https://") {
+        let origin = InstallSource::Url {
+            url: source.to_string(),
+        };
+        (parse_manifest_url(source)?, Some(origin))
     } else if Path::new(source).exists() {
-        parse_manifest_file(Path::new(source))?
+        // Updates reinstall from the saved copy, which says nothing about origin
+        let path = fs::canonicalize(source)?;
+        let saved = fs::canonicalize(paths::manifests_dir()).is_ok_and(|dir| path.starts_with(dir));
+        let origin = (!saved).then_some(InstallSource::File { path });
+        (parse_manifest_file(Path::new(source))?, origin)
     } else {
         // Try to find in local manifests directory
         let manifest_path = paths::manifest_path(source);
         if manifest_path.exists() {
-            parse_manifest_file(&manifest_path)?
+            (parse_manifest_file(&manifest_path)?, None)
         } else {
             // TODO: Try registry lookup
             return Err(InstallError::Failed(format!(
@@ -73,13 +294,74 @@ pub fn install_app(source: &str, force: bool) -> Result<(), InstallError> {
         }
     };
 
-    install_app_from_manifest(&manifest, force)
+    let options = InstallOptions {
+        source: origin.or_else(|| options.source.clone()),
+        ..options.clone()
+    };
+    install_app_from_manifest(&manifest, &options)
 }
 
 /// Install an app from an already-parsed manifest
-pub fn install_app_from_manifest(manifest: &AppManifest, force: bool) -> Result<(), InstallError> {
+pub fn install_app_from_manifest(
+    manifest: &AppManifest,
+    options: &InstallOptions,
+) -> Result<(), InstallError> {
+    let profiled;
+    let manifest = match options.profile.as_deref() {
+        Some(profile) => {
+            profiled = manifest.with_profile(profile);
+            &profiled
+        }
+        None => manifest,
+    };
     validate_manifest(manifest)?;
 
+    let arch = install_arch(manifest, options)?;
+    let retargeted;
+    let manifest = match host_ubuntu_arch().filter(|host| *host != arch) {
+        Some(host) => {
+            crate::info!(
+                "Note: installing for {} on a {} host. Setting it up and running \
+                 it needs qemu-user-static registered with binfmt_misc.",
+                arch,
+                host
+            );
+            retargeted = retarget_asset_arch(manifest, host, &arch);
+            &retargeted
+        }
+        None => manifest,
+    };
+    if !manifest.runtime.supports_arch(&arch) {
+        return Err(InstallError::Failed(format!(
+            "{} does not support this architecture ({}); supported: {}",
+            manifest.app.display_name,
+            arch,
+            manifest.runtime.arch.join(", ")
+        )));
+    }
+
+    if options.dry_run {
+        return print_install_plan(manifest, options, &arch);
+    }
+
+    let _lock = InstallLock::acquire()?;
+    let _interrupts = InterruptGuard::install();
+    // Cancelled before the install got this far
+    check_interrupted()?;
+
+    let linked_source = if options.symlink_local {
+        Some(local_link_source(manifest)?)
+    } else {
+        None
+    };
+
+    let force = options.force;
+    let progress = options.progress.as_ref();
+    let pinned_version = options
+        .version
+        .as_deref()
+        .map(|v| v.trim_start_matches('v'));
+
     let app_name = &manifest.app.name;
     let app_dir = paths::app_dir(app_name);
 
@@ -90,6 +372,11 @@ pub fn install_app_from_manifest(manifest: &AppManifest, force: bool) -> Result<
 
     // Create directories
     paths::ensure_dirs()?;
+    let mut partial = PartialInstall {
+        app_name,
+        fresh: !app_dir.exists(),
+        finished: false,
+    };
     fs::create_dir_all(&app_dir)?;
 
     // Save manifest locally
@@ -104,19 +391,40 @@ pub fn install_app_from_manifest(manifest: &AppManifest, force: bool) -> Result<
     let base_info_path = paths::app_base_info_path(app_name);
     let shared_packages = &manifest.dependencies.shared;
     let app_packages = &manifest.dependencies.packages;
-
-    let (install_root, base_version) = if rootfs.join("etc/os-release").exists()
-        && !base_info_path.exists()
-    {
-        println!("[voidbox] Existing rootfs detected - using legacy mode.");
-        fs::create_dir_all(&rootfs)?;
-        (rootfs.clone(), None)
+    let repos = &manifest.dependencies.repos;
+    // The files are extracted again, so a reinstall replaces its store entry
+    let previous_store_id = read_base_info_for_rootfs(&rootfs)
+        .ok()
+        .flatten()
+        .and_then(|info| info.store_id);
+
+    let (install_root, base_version) =
+        if rootfs.join("etc/os-release").exists() && !base_info_path.exists() {
+            crate::info!("Existing rootfs detected - using legacy mode.");
+            (rootfs.clone(), None)
+        } else {
+            let base_dir = paths::base_dir(&manifest.runtime.base, &arch);
+            report_progress(progress, 0.0, "Preparing base image...");
+            let base_version = setup_base_image(
+                &base_dir,
+                manifest.runtime.ubuntu_version(),
+                &arch,
+                progress,
+            )?;
+            (layer_dir.clone(), Some(base_version))
+        };
+    fs::create_dir_all(&install_root)?;
+
+    // Download the app while apt installs the dependencies
+    let prefetch = if linked_source.is_some() {
+        None
     } else {
-        let arch = detect_ubuntu_arch()?;
-        let base_dir = paths::base_dir(&manifest.runtime.base, &arch);
-        let base_version = setup_base_image(&base_dir, &arch)?;
+        AppPrefetch::start(&install_root, manifest, pinned_version)?
+    };
 
-        let deps_id = ensure_deps_layer(&manifest, &arch, &base_version)?;
+    if let Some(base_version) = &base_version {
+        report_progress(progress, 0.25, "Preparing shared dependencies...");
+        let deps_id = ensure_deps_layer(&manifest, &arch, base_version, options.rebuild_deps)?;
 
         write_base_info(
             app_name,
@@ -125,65 +433,186 @@ pub fn install_app_from_manifest(manifest: &AppManifest, force: bool) -> Result<
                 arch: arch.clone(),
                 version: base_version.clone(),
                 deps_id: deps_id.clone(),
+                // The app's files stay in the store until new ones replace them
+                store_id: previous_store_id.clone(),
             },
         )?;
 
         fs::create_dir_all(&rootfs)?;
-        fs::create_dir_all(&layer_dir)?;
         fs::create_dir_all(&work_dir)?;
-        (layer_dir.clone(), Some(base_version))
-    };
+    }
 
     // Install dependencies
+    if base_version.is_none() || !app_packages.is_empty() {
+        report_progress(progress, 0.4, "Installing dependencies...");
+    }
     if base_version.is_none() {
         let mut legacy_packages = shared_packages.clone();
         legacy_packages.extend(app_packages.iter().cloned());
-        install_dependencies(&rootfs, &install_root, &legacy_packages)?;
+        install_dependencies(&rootfs, &install_root, &legacy_packages, repos)?;
     } else if !app_packages.is_empty() {
-        install_dependencies(&rootfs, &install_root, app_packages)?;
+        install_dependencies(&rootfs, &install_root, app_packages, repos)?;
+    }
+
+    if let Some(script) = manifest.hooks.pre_install.as_deref() {
+        run_install_hook(&rootfs, &install_root, "pre_install", script)?;
     }
 
     // Download and install the app (returns actual version downloaded)
-    let actual_version = install_app_binary(&install_root, &manifest)?;
+    let actual_version = match linked_source.as_deref() {
+        Some(source) => {
+            link_local_source(&install_root, manifest, source)?;
+            None
+        }
+        None => install_app_binary(&rootfs, &install_root, &manifest, prefetch, progress)?,
+    };
+
+    if let Some(script) = manifest.hooks.post_install.as_deref() {
+        run_install_hook(&rootfs, &install_root, "post_install", script)?;
+    }
+
+    report_progress(progress, 0.97, "Creating desktop entry...");
 
     // Extract icon
     let icon_filename = manifest.desktop.icon.as_deref();
     if let Err(e) = extract_icon(app_name, icon_filename) {
-        println!("[voidbox] Warning: Could not extract icon: {}", e);
+        crate::warn!("Warning: Could not extract icon: {}", e);
     }
 
     // Create desktop entry
     if let Err(e) = create_desktop_entry(&manifest) {
-        println!("[voidbox] Warning: Could not create desktop entry: {}", e);
+        crate::warn!("Warning: Could not create desktop entry: {}", e);
+    } else if let Err(e) = register_mime_handlers(manifest) {
+        crate::warn!("Warning: Could not register MIME handlers: {}", e);
     }
 
     // Create wrapper script
     if let Err(e) = create_app_wrapper(app_name) {
-        println!("[voidbox] Warning: Could not create wrapper script: {}", e);
+        crate::warn!("Warning: Could not create wrapper script: {}", e);
+    }
+
+    // A local source is bind-mounted at run time, so there is nothing to share
+    if base_version.is_some() {
+        store_app_files(app_name, manifest, linked_source.is_none())?;
+    }
+    if let Some(previous) = previous_store_id {
+        remove_unused_store_entry(&previous)?;
     }
 
+    // An install interrupted in a step that does not poll must not be recorded
+    check_interrupted()?;
+    partial.finished = true;
+
     // Save installed app info with actual version
     save_installed_app(
         &manifest,
         actual_version.as_deref(),
         base_version.as_deref(),
+        pinned_version,
+        linked_source.as_deref(),
+        options.source.as_ref(),
     )?;
 
-    println!(
-        "[voidbox] Successfully installed {}!",
-        manifest.app.display_name
-    );
-    println!("[voidbox] Run with: voidbox run {}", app_name);
+    // Make the new entry show up in launcher menus without a re-login
+    refresh_desktop_caches();
+
+    report_progress(progress, 1.0, "Done!");
 
+    crate::info!("Successfully installed {}!", manifest.app.display_name);
+    crate::info!("Run with: voidbox run {}", app_name);
+
+    Ok(())
+}
+
+/// Move the app's extracted files to the shared store
+///
+/// Until this runs the base info keeps a reinstall's previous entry, so an
+/// install that fails earlier leaves the app as it was. The base info is
+/// pointed at the new entry first: if the move never happens the missing
+/// entry is skipped at mount time and the layer's copy is used. Without
+/// `share`, or without files to share, the app has no entry.
+fn store_app_files(
+    app_name: &str,
+    manifest: &AppManifest,
+    share: bool,
+) -> Result<(), InstallError> {
+    let layer_dir = paths::app_layer_dir(app_name);
+    let install_dir = app_install_dir(manifest);
+    let store_id = if share {
+        app_tree_id(&layer_dir, install_dir)?
+    } else {
+        None
+    };
+    let Some(info) = read_base_info_for_rootfs(&paths::app_rootfs_dir(app_name))? else {
+        return Ok(());
+    };
+
+    write_base_info(
+        app_name,
+        &BaseInfo {
+            store_id: store_id.clone(),
+            ..info
+        },
+    )?;
+    if let Some(store_id) = store_id {
+        fs::create_dir_all(paths::store_dir())?;
+        move_app_tree_to_store(&layer_dir, install_dir, &store_id)?;
+    }
     Ok(())
 }
 
+/// Cleans up after an install stopped by Ctrl-C or SIGTERM
+///
+/// A fresh install is removed completely. A reinstall over an existing app
+/// only loses its temporary files, since the app dir also holds user data.
+struct PartialInstall<'a> {
+    app_name: &'a str,
+    fresh: bool,
+    finished: bool,
+}
+
+impl Drop for PartialInstall<'_> {
+    fn drop(&mut self) {
+        if self.finished || !is_interrupted() {
+            return;
+        }
+
+        if self.fresh {
+            let _ = remove_desktop_entry(self.app_name);
+            let _ = remove_app_wrapper(self.app_name);
+            fs::remove_file(paths::manifest_path(self.app_name)).ok();
+            if let Err(e) = remove_dir_all_force(&paths::app_dir(self.app_name)) {
+                crate::warn!("Warning: Could not remove partial install: {}", e);
+            }
+            crate::notice!(
+                "Install interrupted - removed the partial install of {}.",
+                self.app_name
+            );
+            return;
+        }
+
+        // Downloads and scripts staged at the root of the app layer
+        let layer_dir = paths::app_layer_dir(self.app_name);
+        for entry in fs::read_dir(&layer_dir).into_iter().flatten().flatten() {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            if name.contains("_download") || name.starts_with("voidbox-") {
+                fs::remove_file(entry.path()).ok();
+            }
+        }
+        crate::notice!(
+            "Install interrupted - {} may be incomplete. \
+             Run `voidbox install --force` again to finish it.",
+            self.app_name
+        );
+    }
+}
+
 /// Install an app from an embedded bundle (manifest + local archive)
 pub fn install_app_from_bundle(
     manifest_content: &str,
     archive_path: &Path,
     archive_ext: &str,
-    force: bool,
+    options: &InstallOptions,
 ) -> Result<(), InstallError> {
     let manifest = parse_manifest_str(manifest_content)?;
     let mut install_manifest = manifest.clone();
@@ -191,10 +620,7 @@ pub fn install_app_from_bundle(
         match resolve_bundle_version(&manifest) {
             Ok(Some(version)) => install_manifest.app.version = Some(version),
             Ok(None) => {}
-            Err(e) => println!(
-                "[voidbox] Warning: Could not determine app version for bundle: {}",
-                e
-            ),
+            Err(e) => crate::warn!("Warning: Could not determine app version for bundle: {}", e),
         }
     }
     install_manifest.source = SourceConfig::Local {
@@ -202,7 +628,7 @@ pub fn install_app_from_bundle(
         archive_type: Some(archive_ext.to_string()),
     };
 
-    install_app_from_manifest(&install_manifest, force)?;
+    install_app_from_manifest(&install_manifest, options)?;
 
     // Restore original manifest content (preserves comments/source)
     let manifest_path = paths::manifest_path(&manifest.app.name);
@@ -211,36 +637,124 @@ pub fn install_app_from_bundle(
     Ok(())
 }
 
+/// Record of a completed base image extraction (`.voidbox/base.meta`)
+#[derive(Serialize, Deserialize)]
+struct BaseMeta {
+    /// SHA-256 of the tarball it was extracted from (None for older bases)
+    sha256: Option<String>,
+    /// Number of entries under the base dir right after extraction
+    file_count: usize,
+}
+
+fn base_meta_path(base_dir: &Path) -> PathBuf {
+    base_dir.join(".voidbox/base.meta")
+}
+
+/// Count every file, directory and symlink in a base, minus our own metadata
+fn count_entries(base_dir: &Path) -> usize {
+    WalkDir::new(base_dir)
+        .min_depth(1)
+        .into_iter()
+        .filter_entry(|entry| entry.depth() != 1 || entry.file_name() != ".voidbox")
+        .flatten()
+        .count()
+}
+
+/// Check whether an existing base dir is a complete extraction
+///
+/// Bases from before `base.meta` existed are trusted once (and get a meta
+/// file) unless their tarball is still lying around, which means the
+/// extraction that should have deleted it never finished.
+fn base_image_is_complete(base_dir: &Path, archive_path: &Path) -> Result<bool, InstallError> {
+    if !base_dir.join("etc/os-release").exists() {
+        return Ok(false);
+    }
+
+    let meta_path = base_meta_path(base_dir);
+    let meta = fs::read_to_string(&meta_path)
+        .ok()
+        .and_then(|content| serde_json::from_str::<BaseMeta>(&content).ok());
+    match meta {
+        Some(meta) => Ok(count_entries(base_dir) == meta.file_count),
+        None if archive_path.exists() => Ok(false),
+        None => {
+            write_base_meta(base_dir, None)?;
+            Ok(true)
+        }
+    }
+}
+
+fn write_base_meta(base_dir: &Path, sha256: Option<String>) -> Result<(), InstallError> {
+    let meta = BaseMeta {
+        sha256,
+        file_count: count_entries(base_dir),
+    };
+    let content = serde_json::to_string_pretty(&meta)
+        .map_err(|e| InstallError::Failed(format!("Failed to serialize base meta: {}", e)))?;
+    let meta_path = base_meta_path(base_dir);
+    if let Some(parent) = meta_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    write_atomic(&meta_path, content)?;
+    Ok(())
+}
+
 /// Setup shared base image (Ubuntu)
-fn setup_base_image(base_dir: &Path, arch: &str) -> Result<String, InstallError> {
+pub(crate) fn setup_base_image(
+    base_dir: &Path,
+    ubuntu_version: Option<&str>,
+    arch: &str,
+    progress: Option<&InstallProgress>,
+) -> Result<String, InstallError> {
+    let _lock = InstallLock::acquire()?;
+
+    // Keep the tarball beside the base dir so an interrupted download survives
+    // the cleanup below and can be resumed on the next attempt
+    let archive_path = PathBuf::from(format!("{}.tar.gz", base_dir.display()));
+
     if base_dir.exists() {
         // Check if base is already setup
-        if base_dir.join("etc/os-release").exists() {
-            println!("[voidbox] Base image already exists, skipping...");
+        if base_image_is_complete(base_dir, &archive_path)? {
+            crate::info!("Base image already exists, skipping...");
             let existing_version = read_base_version(base_dir).unwrap_or_else(|| "unknown".into());
             return Ok(existing_version);
         }
-        fs::remove_dir_all(base_dir)?;
+        if base_dir.join("etc/os-release").exists() {
+            crate::info!("Base image is incomplete - extracting it again...");
+        }
+        remove_dir_all_force(base_dir)?;
     }
 
     fs::create_dir_all(base_dir)?;
 
-    println!("[voidbox] Fetching Ubuntu base image...");
+    crate::info!("Fetching Ubuntu base image...");
+
+    let (version, url) = fetch_ubuntu_base(ubuntu_version, arch)?;
+    crate::info!("Downloading Ubuntu {} base...", version);
 
-    // Fetch latest Ubuntu base
-    let (version, url) = fetch_latest_ubuntu_base(arch)?;
-    println!("[voidbox] Downloading Ubuntu {} base...", version);
+    ensure_space_for_download(&url, base_dir)?;
 
-    let archive_path = base_dir.join("ubuntu_base.tar.gz");
-    download_file(&url, &archive_path, true)?;
+    download_with_progress(
+        &url,
+        &archive_path,
+        progress,
+        (0.0, 0.2),
+        "Downloading base image...",
+    )?;
+
+    let sha256 = verify_ubuntu_base(&url, &archive_path)?;
 
-    println!("[voidbox] Extracting base image...");
+    crate::info!("Extracting base image...");
+    report_progress(progress, 0.2, "Extracting base image...");
     let tar_gz = File::open(&archive_path)?;
-    let decoder = GzDecoder::new(tar_gz);
+    let total = tar_gz.metadata()?.len();
+    let mut reporter = ExtractProgress::new(progress, (0.2, 0.25), "Extracting base image...");
+    let mut on_read = |read| reporter.update(read, total);
+    let decoder = GzDecoder::new(CountingReader::new(tar_gz, &mut on_read));
     let mut archive = tar::Archive::new(decoder);
     archive.set_ignore_zeros(true);
     archive.unpack(base_dir)?;
-    fs::remove_file(archive_path)?;
+    reporter.finish();
 
     // Setup network
     if Path::new("/etc/resolv.conf").exists() {
@@ -250,15 +764,102 @@ fn setup_base_image(base_dir: &Path, arch: &str) -> Result<String, InstallError>
         fs::write(base_dir.join("etc/resolv.conf"), content)?;
     }
 
+    // Written last: a base without a matching meta file is re-extracted
+    write_base_meta(base_dir, Some(sha256))?;
+    fs::remove_file(archive_path)?;
+
     Ok(version)
 }
 
-/// Fetch latest Ubuntu base image URL
-fn fetch_latest_ubuntu_base(arch: &str) -> Result<(String, String), InstallError> {
-    let mut resp = ureq::get(crate::UBUNTU_RELEASES_URL)
-        .header("User-Agent", crate::APP_NAME)
-        .call()
-        .map_err(|e| InstallError::Failed(format!("Failed to fetch Ubuntu releases: {}", e)))?;
+/// Print what an install would download and set up, without writing anything
+fn print_install_plan(
+    manifest: &AppManifest,
+    options: &InstallOptions,
+    arch: &str,
+) -> Result<(), InstallError> {
+    let app_name = &manifest.app.name;
+    let describe_size = |url: &str| {
+        remote_file_size(url)
+            .map(|size| HumanBytes(size).to_string())
+            .unwrap_or_else(|| "unknown size".to_string())
+    };
+
+    crate::info!("Dry run - nothing will be downloaded or written.");
+    println!();
+    println!("App:          {} ({})", manifest.app.display_name, app_name);
+    if paths::app_dir(app_name).exists() {
+        if options.force {
+            println!("              already installed - would be reinstalled");
+        } else {
+            println!("              already installed - would fail without --force");
+        }
+    }
+
+    let base_dir = paths::base_dir(&manifest.runtime.base, arch);
+    if base_dir.join("etc/os-release").exists() {
+        println!(
+            "Base image:   {} ({}) - already downloaded",
+            manifest.runtime.base, arch
+        );
+    } else {
+        let (version, url) = fetch_ubuntu_base(manifest.runtime.ubuntu_version(), arch)?;
+        println!("Base image:   Ubuntu {} ({})", version, arch);
+        println!("              {} ({})", url, describe_size(&url));
+    }
+
+    let shared = &manifest.dependencies.shared;
+    if !shared.is_empty() {
+        let deps_id = paths::deps_id(&manifest.runtime.base, arch, shared);
+        let state = if paths::deps_ready_path(&deps_id).exists() {
+            "already built"
+        } else {
+            "would be built"
+        };
+        println!("Shared deps:  {} packages ({})", shared.len(), state);
+        println!("              {}", shared.join(" "));
+    }
+    if !manifest.dependencies.packages.is_empty() {
+        println!("App packages: {}", manifest.dependencies.packages.join(" "));
+    }
+    for repo in &manifest.dependencies.repos {
+        println!("Apt repo:     {} {}", repo.uri, repo.suite);
+    }
+
+    let pinned_version = options
+        .version
+        .as_deref()
+        .map(|v| v.trim_start_matches('v'));
+    match resolve_release(manifest, pinned_version)? {
+        Some(release) => {
+            println!("Version:      {}", release.version);
+            println!(
+                "Download:     {} ({})",
+                release.url,
+                describe_size(&release.url)
+            );
+        }
+        None => {
+            if let SourceConfig::Local { path, .. } = &manifest.source {
+                println!("Source:       {}", path.display());
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Fetch the Ubuntu base image URL for `requested` (latest if None)
+///
+/// A pinned "24.04" takes the newest 24.04.x point release. If that release
+/// is gone, the nearest one available is used with a warning: the newest
+/// older release, otherwise the oldest newer one.
+fn fetch_ubuntu_base(
+    requested: Option<&str>,
+    arch: &str,
+) -> Result<(String, String), InstallError> {
+    let mut resp = http_get(crate::UBUNTU_RELEASES_URL).call().map_err(|e| {
+        InstallError::NetworkError(format!("Failed to fetch Ubuntu releases: {}", e))
+    })?;
 
     let body = resp
         .body_mut()
@@ -287,42 +888,190 @@ fn fetch_latest_ubuntu_base(arch: &str) -> Result<(String, String), InstallError
         return Err(InstallError::Failed("No Ubuntu versions found".into()));
     }
 
-    // Sort and get latest
-    versions.sort_by(|a, b| {
-        let parse_version =
-            |s: &str| -> Vec<u32> { s.split('.').filter_map(|p| p.parse().ok()).collect() };
-        parse_version(a).cmp(&parse_version(b))
-    });
+    let parse_version =
+        |s: &str| -> Vec<u32> { s.split('.').filter_map(|p| p.parse().ok()).collect() };
+    versions.sort_by_key(|v| parse_version(v));
+
+    // Newest first, or for a pinned release: its point releases, then older
+    // releases, then newer ones
+    let candidates: Vec<&String> = match requested {
+        None => versions.iter().rev().collect(),
+        Some(requested) => {
+            let wanted = parse_version(requested);
+            let (matching, others): (Vec<&String>, Vec<&String>) = versions
+                .iter()
+                .rev()
+                .partition(|v| parse_version(v).starts_with(&wanted));
+            let (older, newer): (Vec<&String>, Vec<&String>) =
+                others.into_iter().partition(|v| parse_version(v) < wanted);
+            matching
+                .into_iter()
+                .chain(older)
+                .chain(newer.into_iter().rev())
+                .collect()
+        }
+    };
 
-    // Try versions from newest to oldest
-    for version in versions.iter().rev() {
+    for version in candidates {
         let release_url = format!("{}{}/release/", crate::UBUNTU_RELEASES_URL, version);
-
-        if let Ok(mut resp) = ureq::get(&release_url)
-            .header("User-Agent", crate::APP_NAME)
+        let Some(body) = http_get(&release_url)
             .call()
+            .ok()
+            .and_then(|mut resp| resp.body_mut().read_to_string().ok())
+        else {
+            continue;
+        };
+
+        let Some(url) = ubuntu_base_url(&release_url, &body, version, arch) else {
+            continue;
+        };
+
+        if let Some(requested) =
+            requested.filter(|r| !parse_version(version).starts_with(&parse_version(r)))
         {
-            if let Ok(body) = resp.body_mut().read_to_string() {
-                let pattern = format!("ubuntu-base-{}-base-{}.tar.gz", version, arch);
-                if body.contains(&pattern) {
-                    let download_url = format!("{}{}", release_url, pattern);
-                    return Ok((version.clone(), download_url));
-                }
-
-                // Try base version for point releases
-                let base_version: String = version.split('.').take(2).collect::<Vec<_>>().join(".");
-                let alt_pattern = format!("ubuntu-base-{}-base-{}.tar.gz", base_version, arch);
-                if body.contains(&alt_pattern) {
-                    let download_url = format!("{}{}", release_url, alt_pattern);
-                    return Ok((version.clone(), download_url));
-                }
-            }
+            crate::warn!(
+                "Warning: Ubuntu {} base is not available, using {} instead.",
+                requested,
+                version
+            );
         }
+        return Ok((version.clone(), url));
     }
 
     Err(InstallError::Failed("No Ubuntu base image found".into()))
 }
 
+/// URL of the base tarball for `arch` listed on a release page, if any
+fn ubuntu_base_url(release_url: &str, listing: &str, version: &str, arch: &str) -> Option<String> {
+    // Point releases may ship a tarball named after the base version
+    let base_version: String = version.split('.').take(2).collect::<Vec<_>>().join(".");
+    [
+        ubuntu_base_filename(version, arch),
+        ubuntu_base_filename(&base_version, arch),
+    ]
+    .into_iter()
+    .find(|pattern| listing.contains(pattern))
+    .map(|pattern| format!("{}{}", release_url, pattern))
+}
+
+/// Check a downloaded Ubuntu base tarball against its release's SHA256SUMS
+///
+/// A mismatching tarball is deleted so the next attempt downloads it afresh
+/// instead of resuming from bad data. Returns the verified hash.
+fn verify_ubuntu_base(url: &str, archive_path: &Path) -> Result<String, InstallError> {
+    let Some((release_url, filename)) = url.rsplit_once('/') else {
+        return Err(InstallError::Failed(format!(
+            "Invalid base image URL: {}",
+            url
+        )));
+    };
+
+    crate::info!("Verifying base image checksum...");
+    let sums_url = format!("{}/SHA256SUMS", release_url);
+    let sums = download_string(&sums_url)?;
+    verify_sums_signature(&sums_url, &sums, archive_path)?;
+
+    // Lines look like "<hash> *<file>" (binary mode) or "<hash>  <file>"
+    let expected = sums
+        .lines()
+        .find_map(|line| {
+            let (hash, name) = line.split_once(' ')?;
+            (name.trim_start_matches([' ', '*']) == filename).then(|| hash.to_lowercase())
+        })
+        .ok_or_else(|| {
+            InstallError::Failed(format!("{} is not listed in {}", filename, sums_url))
+        })?;
+
+    let actual = sha256_file(archive_path)?;
+    if actual != expected {
+        let _ = fs::remove_file(archive_path);
+        return Err(InstallError::ChecksumMismatch {
+            file: filename.to_string(),
+            expected,
+            actual,
+        });
+    }
+
+    Ok(actual)
+}
+
+/// Keyring that signs Ubuntu release SHA256SUMS files
+const UBUNTU_KEYRING: &str = "/usr/share/keyrings/ubuntu-archive-keyring.gpg";
+
+/// Verify the signature on SHA256SUMS when `gpgv` and the Ubuntu keyring exist
+///
+/// Scratch files are written next to `archive_path` and removed afterwards.
+fn verify_sums_signature(
+    sums_url: &str,
+    sums: &str,
+    archive_path: &Path,
+) -> Result<(), InstallError> {
+    let gpgv = which::which("gpgv")
+        .ok()
+        .filter(|_| Path::new(UBUNTU_KEYRING).exists());
+    let Some(gpgv) = gpgv else {
+        crate::info!(
+            "Note: gpgv or the Ubuntu keyring is missing - SHA256SUMS signature not checked."
+        );
+        return Ok(());
+    };
+
+    let sums_path = PathBuf::from(format!("{}.SHA256SUMS", archive_path.display()));
+    let sig_path = PathBuf::from(format!("{}.SHA256SUMS.gpg", archive_path.display()));
+    fs::write(&sums_path, sums)?;
+
+    let result = download_file(&format!("{}.gpg", sums_url), &sig_path, false)
+        .map_err(InstallError::from)
+        .and_then(|()| {
+            let status = Command::new(gpgv)
+                .arg("--keyring")
+                .arg(UBUNTU_KEYRING)
+                .arg(&sig_path)
+                .arg(&sums_path)
+                .stdout(Stdio::null())
+                .stderr(Stdio::null())
+                .status()?;
+            if status.success() {
+                Ok(())
+            } else {
+                Err(InstallError::Failed(
+                    "SHA256SUMS signature verification failed".into(),
+                ))
+            }
+        });
+
+    let _ = fs::remove_file(&sums_path);
+    let _ = fs::remove_file(&sig_path);
+    result
+}
+
+/// Ubuntu base tarball name for a release and arch (amd64 or arm64)
+fn ubuntu_base_filename(version: &str, arch: &str) -> String {
+    format!("ubuntu-base-{}-base-{}.tar.gz", version, arch)
+}
+
+/// Abort before downloading if the filesystem can't hold the archive plus its extraction
+fn ensure_space_for_download(url: &str, dir: &Path) -> Result<(), InstallError> {
+    let Some(size) = remote_file_size(url) else {
+        return Ok(());
+    };
+
+    match check_free_space(dir, estimate_install_size(size)) {
+        Ok(()) => Ok(()),
+        Err(SpaceError::Insufficient { needed, available }) => {
+            Err(InstallError::InsufficientSpace {
+                path: dir.to_path_buf(),
+                needed,
+                available,
+            })
+        }
+        Err(e) => {
+            crate::warn!("Warning: {}", e);
+            Ok(())
+        }
+    }
+}
+
 fn read_base_version(base_dir: &Path) -> Option<String> {
     let os_release = base_dir.join("etc/os-release");
     let content = fs::read_to_string(os_release).ok()?;
@@ -335,34 +1084,76 @@ fn read_base_version(base_dir: &Path) -> Option<String> {
 }
 
 fn detect_ubuntu_arch() -> Result<String, InstallError> {
-    match std::env::consts::ARCH {
-        "x86_64" => Ok("amd64".to_string()),
-        "aarch64" => Ok("arm64".to_string()),
-        other => Err(InstallError::Failed(format!(
+    host_ubuntu_arch().map(str::to_string).ok_or_else(|| {
+        InstallError::Failed(format!(
             "Unsupported architecture: {}",
-            other
-        ))),
+            std::env::consts::ARCH
+        ))
+    })
+}
+
+/// Architecture to install for: `--arch`, else the arch an existing install
+/// of the app was set up for (so updates keep it), else the host's
+fn install_arch(manifest: &AppManifest, options: &InstallOptions) -> Result<String, InstallError> {
+    if let Some(arch) = options.arch.as_deref() {
+        return ubuntu_arch_name(arch).map(str::to_string).ok_or_else(|| {
+            InstallError::Failed(format!(
+                "Unsupported architecture: {} (use amd64 or arm64)",
+                arch
+            ))
+        });
+    }
+
+    let rootfs = paths::app_rootfs_dir(&manifest.app.name);
+    let installed_arch = read_base_info_for_rootfs(&rootfs)
+        .ok()
+        .flatten()
+        .and_then(|info| ubuntu_arch_name(&info.arch));
+    match installed_arch {
+        Some(arch) => Ok(arch.to_string()),
+        None => detect_ubuntu_arch(),
+    }
+}
+
+/// Point release asset matching at `arch` when it targeted the host arch
+fn retarget_asset_arch(manifest: &AppManifest, host: &str, arch: &str) -> AppManifest {
+    let mut manifest = manifest.clone();
+    match &mut manifest.source {
+        SourceConfig::Github { asset_arch, .. } | SourceConfig::Gitlab { asset_arch, .. }
+            if asset_arch == host =>
+        {
+            *asset_arch = arch.to_string();
+        }
+        _ => {}
     }
+    manifest
 }
 
 /// Install dependencies in the container
+///
+/// `repos` are added to apt (with their signing keys) before the package
+/// lists are fetched.
 fn install_dependencies(
     rootfs: &Path,
     layer_dir: &Path,
     packages: &[String],
+    repos: &[AptRepo],
 ) -> Result<(), InstallError> {
     if packages.is_empty() {
         return Ok(());
     }
 
-    println!("[voidbox] Installing dependencies...");
+    crate::info!("Installing dependencies...");
+    let (repos_script, key_files) = prepare_apt_repos(layer_dir, repos)?;
 
     // Get Ubuntu codename
     let _codename = get_ubuntu_codename(rootfs);
     let packages = packages.join(" ");
+    crate::debug!("apt packages: {}", packages);
 
     let setup_script = format!(
         r#"#!/bin/bash
+{trace}
 export DEBIAN_FRONTEND=noninteractive
 export PATH=/usr/local/sbin:/usr/local/bin:/usr/sbin:/usr/bin:/sbin:/bin
 
@@ -388,6 +1179,7 @@ if [ -f /etc/apt/sources.list.d/ubuntu.sources ]; then
     rm -f /etc/apt/apt.conf.d/99temp-insecure
 fi
 
+{repos}
 apt-get update -qq
 
 if [ ! -f /etc/machine-id ]; then
@@ -419,7 +1211,9 @@ rm -rf /var/lib/apt/lists/*
 
 echo "Setup complete!"
 "#,
-        packages = packages
+        packages = packages,
+        repos = repos_script,
+        trace = trace_script_line()
     );
 
     let setup_path = layer_dir.join("setup.sh");
@@ -432,30 +1226,25 @@ echo "Setup complete!"
     }
 
     // Run setup script using voidbox itself
-    // Note: We use the installed voidbox path, not current_exe(), because
-    // this code may be called from app-specific binaries like void_brave
-    let voidbox_exe = crate::storage::paths::install_path();
-    let exe_to_use = if voidbox_exe.exists() {
-        voidbox_exe
-    } else {
-        std::env::current_exe()?
-    };
-    let status = Command::new(&exe_to_use)
+    let status = Command::new(internal_run_exe()?)
         .args(["internal-run", rootfs.to_str().unwrap(), "/setup.sh"])
-        .stdout(Stdio::inherit())
+        .stdout(crate::logging::child_stdout())
         .stderr(Stdio::inherit())
         .status();
 
     fs::remove_file(&setup_path).ok();
+    for key_file in &key_files {
+        fs::remove_file(key_file).ok();
+    }
 
     match status {
         Ok(s) if !s.success() => {
-            println!(
-                "[voidbox] Note: Some packages couldn't be fully configured (expected in container)"
+            crate::info!(
+                "Note: Some packages couldn't be fully configured (expected in container)"
             );
         }
         Err(e) => {
-            println!("[voidbox] Warning: Setup script failed: {}", e);
+            crate::warn!("Warning: Setup script failed: {}", e);
         }
         _ => {}
     }
@@ -463,31 +1252,316 @@ echo "Setup complete!"
     Ok(())
 }
 
-fn ensure_deps_layer(
-    manifest: &AppManifest,
-    arch: &str,
-    base_version: &str,
-) -> Result<Option<String>, InstallError> {
-    if manifest.dependencies.shared.is_empty() {
-        return Ok(None);
-    }
-
-    let deps_id = paths::deps_id(
-        &manifest.runtime.base,
-        arch,
-        &manifest.dependencies.shared,
+/// Stage the signing keys of `repos` in the layer and build the setup script
+/// lines that install them along with a deb822 sources file per repo
+///
+/// Returns the script and the staged key files to delete afterwards.
+fn prepare_apt_repos(
+    layer_dir: &Path,
+    repos: &[AptRepo],
+) -> Result<(String, Vec<PathBuf>), InstallError> {
+    let mut script = String::new();
+    let mut key_files = Vec::new();
+
+    for (index, repo) in repos.iter().enumerate() {
+        let key_url = match (&repo.key_url, &repo.key_fingerprint) {
+            (Some(url), _) => url.clone(),
+            (None, Some(fingerprint)) => format!(
+                "https://keyserver.ubuntu.com/pks/lookup?op=get&options=mr&search=0x{}",
+                fingerprint.replace(' ', "")
+            ),
+            // Rejected by manifest validation
+            (None, None) => continue,
+        };
+
+        crate::info!("Fetching signing key for {}...", repo.uri);
+        let name = format!("voidbox-repo-{}", index);
+        let staged = layer_dir.join(format!("{}.key", name));
+        download_file(&key_url, &staged, false)?;
+        key_files.push(staged.clone());
+        if let Some(fingerprint) = &repo.key_fingerprint {
+            check_key_fingerprint(&staged, fingerprint)?;
+        }
+
+        let armored = fs::read(&staged)?
+            .trim_ascii_start()
+            .starts_with(b"-----BEGIN PGP");
+        let keyring = format!(
+            "/etc/apt/keyrings/{}.{}",
+            name,
+            if armored { "asc" } else { "gpg" }
+        );
+
+        let mut sources = format!("Types: deb\nURIs: {}\nSuites: {}\n", repo.uri, repo.suite);
+        if !repo.components.is_empty() {
+            sources.push_str(&format!("Components: {}\n", repo.components.join(" ")));
+        }
+        sources.push_str(&format!("Signed-By: {}\n", keyring));
+
+        script.push_str(&format!(
+            "install -D -m 644 /{name}.key {keyring}\n\
+             cat > /etc/apt/sources.list.d/{name}.sources <<'VOIDBOX_EOF'\n{sources}VOIDBOX_EOF\n"
+        ));
+    }
+
+    Ok((script, key_files))
+}
+
+/// Make sure a downloaded key file holds only the key with `fingerprint`
+///
+/// The keyserver is not trusted to return the key that was asked for, and
+/// every key in the file would be trusted for the repo.
+fn check_key_fingerprint(key_file: &Path, fingerprint: &str) -> Result<(), InstallError> {
+    let expected: String = fingerprint
+        .chars()
+        .filter(|c| *c != ' ')
+        .collect::<String>()
+        .to_uppercase();
+
+    let output = Command::new("gpg")
+        .args(["--batch", "--show-keys", "--with-colons"])
+        .arg(key_file)
+        .output()
+        .map_err(|e| InstallError::Failed(format!("gpg is needed to check the repo key: {}", e)))?;
+    let listing = String::from_utf8_lossy(&output.stdout);
+
+    // The first fpr record after each pub record is the primary key's
+    let mut primary_keys = Vec::new();
+    let mut after_pub = false;
+    for fields in listing
+        .lines()
+        .map(|line| line.split(':').collect::<Vec<_>>())
+    {
+        match fields[0] {
+            "pub" => after_pub = true,
+            "fpr" if after_pub => {
+                primary_keys.push(fields.get(9).copied().unwrap_or_default().to_uppercase());
+                after_pub = false;
+            }
+            _ => {}
+        }
+    }
+
+    if !output.status.success() || primary_keys.is_empty() {
+        return Err(InstallError::Failed(format!(
+            "Could not read the key for {}",
+            fingerprint
+        )));
+    }
+    if let Some(other) = primary_keys.iter().find(|key| **key != expected) {
+        return Err(InstallError::Failed(format!(
+            "Repo key mismatch: expected {}, got {}",
+            expected, other
+        )));
+    }
+    Ok(())
+}
+
+/// `set -x` for setup scripts at trace level, so `-vv` shows each apt step
+fn trace_script_line() -> &'static str {
+    if crate::logging::enabled(crate::logging::Level::Trace) {
+        "set -x"
+    } else {
+        ""
+    }
+}
+
+/// Executable used for `internal-run`
+///
+/// We use the installed voidbox path, not current_exe(), because this code
+/// may be called from app-specific binaries like void_brave
+fn internal_run_exe() -> std::io::Result<PathBuf> {
+    let voidbox_exe = paths::install_path();
+    if voidbox_exe.exists() {
+        Ok(voidbox_exe)
+    } else {
+        std::env::current_exe()
+    }
+}
+
+/// Run a manifest hook script inside the container
+/// A failing hook is reported as a warning, not an install error
+fn run_install_hook(
+    rootfs: &Path,
+    install_root: &Path,
+    name: &str,
+    script: &str,
+) -> Result<(), InstallError> {
+    crate::info!("Running {} hook...", name);
+
+    let hook_script = format!(
+        "#!/bin/bash\n\
+         export DEBIAN_FRONTEND=noninteractive\n\
+         export PATH=/usr/local/sbin:/usr/local/bin:/usr/sbin:/usr/bin:/sbin:/bin\n\
+         \n\
+         {}\n",
+        script
     );
-    let deps_dir = paths::deps_dir().join(&deps_id);
-    let deps_rootfs = paths::deps_rootfs_dir(&deps_id);
-    let deps_layer = paths::deps_layer_dir(&deps_id);
-    let deps_work = paths::deps_work_dir(&deps_id);
-    let deps_ready = paths::deps_ready_path(&deps_id);
 
-    if deps_ready.exists() {
+    let hook_path = install_root.join("voidbox-hook.sh");
+    fs::write(&hook_path, hook_script)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&hook_path, fs::Permissions::from_mode(0o755))?;
+    }
+
+    let status = Command::new(internal_run_exe()?)
+        .args(["internal-run", rootfs.to_str().unwrap(), "/voidbox-hook.sh"])
+        .stdout(crate::logging::child_stdout())
+        .stderr(Stdio::inherit())
+        .status();
+
+    fs::remove_file(&hook_path).ok();
+
+    match status {
+        Ok(s) if !s.success() => {
+            let code = s.code().map_or_else(|| "unknown".into(), |c| c.to_string());
+            crate::warn!("Warning: {} hook exited with status {}", name, code);
+        }
+        Err(e) => {
+            crate::warn!("Warning: {} hook failed: {}", name, e);
+        }
+        _ => {}
+    }
+
+    Ok(())
+}
+
+fn ensure_deps_layer(
+    manifest: &AppManifest,
+    arch: &str,
+    base_version: &str,
+    rebuild: bool,
+) -> Result<Option<String>, InstallError> {
+    if manifest.dependencies.shared.is_empty() {
+        return Ok(None);
+    }
+    let _lock = InstallLock::acquire()?;
+
+    let deps_id = paths::deps_id(&manifest.runtime.base, arch, &manifest.dependencies.shared);
+    let deps_ready = paths::deps_ready_path(&deps_id);
+    if rebuild {
+        crate::info!("Rebuilding shared dependency layer {}...", deps_id);
+        // Drop the marker first so an interrupted rebuild gets repaired at launch
+        if deps_ready.exists() {
+            fs::remove_file(&deps_ready)?;
+        }
+        remove_dir_all_force(&paths::deps_rootfs_dir(&deps_id))?;
+        remove_dir_all_force(&paths::deps_layer_dir(&deps_id))?;
+        remove_dir_all_force(&paths::deps_work_dir(&deps_id))?;
+    } else if deps_ready.exists() {
         return Ok(Some(deps_id));
+    } else {
+        crate::info!("Building shared dependency layer...");
     }
 
-    println!("[voidbox] Building shared dependency layer...");
+    build_deps_layer(
+        &deps_id,
+        &manifest.runtime.base,
+        arch,
+        base_version,
+        &manifest.dependencies.shared,
+    )?;
+
+    Ok(Some(deps_id))
+}
+
+/// Rebuild an installed app's dependencies from its current manifest
+///
+/// The deps id is recomputed, so an edited `dependencies.shared` list gets a
+/// fresh layer (and the app is switched to it). `dependencies.packages` are
+/// reinstalled into the app layer. The app binary is left alone.
+pub(crate) fn rebuild_app_deps(app_name: &str, manifest: &AppManifest) -> Result<(), InstallError> {
+    let _lock = InstallLock::acquire()?;
+    let rootfs = paths::app_rootfs_dir(app_name);
+    let Some(info) = read_base_info_for_rootfs(&rootfs)? else {
+        return Err(InstallError::Failed(format!(
+            "{} uses a legacy rootfs without shared layers; reinstall it with --force",
+            app_name
+        )));
+    };
+
+    let deps_id = ensure_deps_layer(manifest, &info.arch, &info.version, true)?;
+    if deps_id != info.deps_id {
+        write_base_info(app_name, &BaseInfo { deps_id, ..info })?;
+    }
+
+    let app_packages = &manifest.dependencies.packages;
+    if !app_packages.is_empty() {
+        install_dependencies(
+            &rootfs,
+            &paths::app_layer_dir(app_name),
+            app_packages,
+            &manifest.dependencies.repos,
+        )?;
+    }
+
+    crate::info!("Dependencies rebuilt for {}.", manifest.app.display_name);
+    Ok(())
+}
+
+/// Rebuild an app's shared dependency layer if it was never finished
+///
+/// An interrupted install leaves the layer without its ready marker; the
+/// app would then start on a bare base image without its packages.
+/// Returns true if a repair was performed.
+pub(crate) fn repair_deps_layer(
+    app_name: &str,
+    manifest: &AppManifest,
+) -> Result<bool, InstallError> {
+    let rootfs = paths::app_rootfs_dir(app_name);
+    let Some(info) = read_base_info_for_rootfs(&rootfs)? else {
+        return Ok(false);
+    };
+    let Some(deps_id) = info.deps_id.as_deref() else {
+        return Ok(false);
+    };
+    if paths::deps_ready_path(deps_id).exists() {
+        return Ok(false);
+    }
+
+    // Another launch may be building it right now - wait, then check again
+    let _lock = InstallLock::acquire()?;
+    if paths::deps_ready_path(deps_id).exists() {
+        return Ok(false);
+    }
+
+    crate::info!(
+        "Shared dependency layer for {} was never completed - rebuilding it now...",
+        manifest.app.display_name
+    );
+
+    // Start from a clean layer rather than on top of a half-finished apt run
+    remove_dir_all_force(&paths::deps_layer_dir(deps_id))?;
+    remove_dir_all_force(&paths::deps_work_dir(deps_id))?;
+
+    build_deps_layer(
+        deps_id,
+        &info.base,
+        &info.arch,
+        &info.version,
+        &manifest.dependencies.shared,
+    )?;
+
+    crate::info!("Dependency layer repaired.");
+    Ok(true)
+}
+
+/// Install packages into a shared dependency layer and mark it ready
+fn build_deps_layer(
+    deps_id: &str,
+    base: &str,
+    arch: &str,
+    base_version: &str,
+    packages: &[String],
+) -> Result<(), InstallError> {
+    let deps_dir = paths::deps_dir().join(deps_id);
+    let deps_rootfs = paths::deps_rootfs_dir(deps_id);
+    let deps_layer = paths::deps_layer_dir(deps_id);
+    let deps_work = paths::deps_work_dir(deps_id);
+    let deps_ready = paths::deps_ready_path(deps_id);
 
     fs::create_dir_all(&deps_rootfs)?;
     fs::create_dir_all(&deps_layer)?;
@@ -499,18 +1573,19 @@ fn ensure_deps_layer(
     write_base_info_for_dir(
         &deps_dir,
         &BaseInfo {
-            base: manifest.runtime.base.clone(),
+            base: base.to_string(),
             arch: arch.to_string(),
             version: base_version.to_string(),
             deps_id: None,
+            store_id: None,
         },
     )?;
 
-    install_dependencies(&deps_rootfs, &deps_layer, &manifest.dependencies.shared)?;
+    install_dependencies(&deps_rootfs, &deps_layer, packages, &[])?;
 
     fs::write(&deps_ready, b"ok")?;
 
-    Ok(Some(deps_id))
+    Ok(())
 }
 
 fn get_ubuntu_codename(rootfs: &Path) -> String {
@@ -528,13 +1603,195 @@ fn get_ubuntu_codename(rootfs: &Path) -> String {
     "noble".to_string()
 }
 
-/// Download and install the app binary
+/// Install the app binary from its local source or prefetched download
 /// Returns the actual version downloaded (if available)
 fn install_app_binary(
+    rootfs: &Path,
     install_root: &Path,
     manifest: &AppManifest,
+    prefetch: Option<AppPrefetch>,
+    progress: Option<&InstallProgress>,
 ) -> Result<Option<String>, InstallError> {
-    let (version, download_url, override_ext) = match &manifest.source {
+    if let SourceConfig::Local { path, archive_type } = &manifest.source {
+        report_progress(progress, 0.85, "Extracting...");
+        install_local_source(
+            rootfs,
+            install_root,
+            manifest,
+            path,
+            archive_type.as_deref(),
+            progress,
+        )?;
+        return Ok(None);
+    }
+
+    let Some(prefetch) = prefetch else {
+        return Ok(None);
+    };
+    let download = prefetch.wait(manifest, progress)?;
+    finish_app_download(rootfs, install_root, manifest, download, progress)
+}
+
+/// Archive of a remote app release, downloaded into the install root
+struct AppDownload {
+    version: String,
+    path: PathBuf,
+    /// Archive extension, with the leading dot
+    extension: String,
+}
+
+/// App download running in the background while dependencies install
+///
+/// The release is resolved up front, so a missing asset fails the install
+/// before the long apt steps. Progress goes through a mutex instead of the
+/// terminal: apt writes there meanwhile, so the bar is only drawn by `wait`.
+/// Dropped without `wait` (a failed install), it stops the download and
+/// deletes what was fetched.
+struct AppPrefetch {
+    state: Arc<Mutex<(u64, u64)>>,
+    cancelled: Arc<AtomicBool>,
+    handle: Option<JoinHandle<Result<AppDownload, InstallError>>>,
+    path: PathBuf,
+}
+
+impl AppPrefetch {
+    /// Resolve the release and start downloading it (None for local sources)
+    fn start(
+        install_root: &Path,
+        manifest: &AppManifest,
+        pinned_version: Option<&str>,
+    ) -> Result<Option<Self>, InstallError> {
+        if matches!(manifest.source, SourceConfig::Local { .. }) {
+            if pinned_version.is_some() {
+                crate::warn!("Warning: --version is ignored for local sources");
+            }
+            return Ok(None);
+        }
+        let Some(release) = resolve_release(manifest, pinned_version)? else {
+            return Ok(None);
+        };
+
+        crate::info!(
+            "Downloading {} v{}...",
+            manifest.app.display_name,
+            release.version
+        );
+        let extension = release.extension();
+        let path = install_root.join(format!(
+            "{}_download{}",
+            app_install_dir(manifest),
+            extension
+        ));
+        ensure_space_for_download(&release.url, install_root)?;
+
+        let state = Arc::new(Mutex::new((0, 0)));
+        let cancelled = Arc::new(AtomicBool::new(false));
+        let shared = Arc::clone(&state);
+        let stop = Arc::clone(&cancelled);
+        let dest = path.clone();
+        let handle = thread::spawn(move || {
+            download_file_with_progress(&release.url, &dest, false, &mut |downloaded, total| {
+                if stop.load(Ordering::SeqCst) {
+                    return Err(std::io::Error::other("download cancelled"));
+                }
+                if let Ok(mut state) = shared.lock() {
+                    *state = (downloaded, total);
+                }
+                Ok(())
+            })?;
+            Ok(AppDownload {
+                version: release.version,
+                path: dest,
+                extension,
+            })
+        });
+        Ok(Some(Self {
+            state,
+            cancelled,
+            handle: Some(handle),
+            path,
+        }))
+    }
+
+    /// Wait for the download, showing its progress until it is done
+    fn wait(
+        mut self,
+        manifest: &AppManifest,
+        progress: Option<&InstallProgress>,
+    ) -> Result<AppDownload, InstallError> {
+        let Some(handle) = self.handle.take() else {
+            return Err(InstallError::Failed(
+                "app download already collected".into(),
+            ));
+        };
+        let label = format!("Downloading {}...", manifest.app.display_name);
+        let mut bar = None;
+        let mut last_percent = None;
+        while !handle.is_finished() {
+            let (downloaded, total) = *self.state.lock().unwrap_or_else(|e| e.into_inner());
+            let bar = bar.get_or_insert_with(download_bar);
+            bar.set_length(total);
+            bar.set_position(downloaded);
+
+            // Nothing to report before the response headers arrive
+            let percent = (downloaded.min(total) * 100).checked_div(total);
+            if let Some(percent) = percent.filter(|p| last_percent != Some(*p)) {
+                last_percent = Some(percent);
+                let fraction = 0.5 + 0.35 * percent as f32 / 100.0;
+                report_progress(progress, fraction, format!("{} {}%", label, percent));
+            }
+            thread::sleep(Duration::from_millis(100));
+        }
+        if let Some(bar) = bar {
+            bar.finish_and_clear();
+        }
+
+        handle
+            .join()
+            .map_err(|_| InstallError::Failed("app download thread panicked".into()))?
+    }
+}
+
+impl Drop for AppPrefetch {
+    fn drop(&mut self) {
+        let Some(handle) = self.handle.take() else {
+            return;
+        };
+        self.cancelled.store(true, Ordering::SeqCst);
+        let _ = handle.join();
+        remove_download(&self.path);
+    }
+}
+
+/// A remote app release resolved from the manifest source
+pub(crate) struct ResolvedRelease {
+    pub(crate) version: String,
+    pub(crate) url: String,
+    /// Archive type from the manifest, overriding the URL's extension
+    archive_type: Option<String>,
+    /// File name of the release asset; API download URLs have no extension
+    asset_name: Option<String>,
+}
+
+impl ResolvedRelease {
+    /// Extension to save the download under, which picks how it is installed
+    fn extension(&self) -> String {
+        match (&self.archive_type, &self.asset_name) {
+            (Some(ext), _) if ext.starts_with('.') => ext.clone(),
+            (Some(ext), _) => format!(".{}", ext),
+            (None, Some(name)) => get_extension_from_url(name),
+            (None, None) => get_extension_from_url(&self.url),
+        }
+    }
+}
+
+/// Resolve the version and download URL of a remote source
+/// Returns None for local sources
+pub(crate) fn resolve_release(
+    manifest: &AppManifest,
+    pinned_version: Option<&str>,
+) -> Result<Option<ResolvedRelease>, InstallError> {
+    let (version, url, archive_type) = match &manifest.source {
         SourceConfig::Github {
             owner,
             repo,
@@ -542,130 +1799,239 @@ fn install_app_binary(
             asset_arch,
             asset_extension,
             ..
-        } => (
-            fetch_github_release(
+        } => {
+            return fetch_github_release(
                 owner,
                 repo,
                 asset_os,
                 asset_arch,
                 asset_extension.as_deref(),
-            )?
-            .0,
-            fetch_github_release(
-                owner,
-                repo,
+                pinned_version,
+            )
+            .map(Some);
+        }
+        SourceConfig::Gitlab {
+            project_id,
+            asset_os,
+            asset_arch,
+            asset_extension,
+        } => {
+            return fetch_gitlab_release(
+                project_id,
                 asset_os,
                 asset_arch,
                 asset_extension.as_deref(),
-            )?
-            .1,
-            None,
-        ),
+                pinned_version,
+            )
+            .map(Some);
+        }
         SourceConfig::Direct {
             url,
             archive_type,
             version_url,
+            version_json_path,
         } => {
+            if let Some(pinned) = pinned_version {
+                if !url.contains("{version}") {
+                    return Err(InstallError::Failed(
+                        "Cannot pin a version: direct source URL has no {version} placeholder"
+                            .into(),
+                    ));
+                }
+                return Ok(Some(ResolvedRelease {
+                    version: pinned.to_string(),
+                    url: url.replace("{version}", pinned),
+                    archive_type: archive_type.clone(),
+                    asset_name: None,
+                }));
+            }
+
             let mut version = "latest".to_string();
             if let Some(version_url) = version_url.as_deref() {
-                match fetch_direct_version(version_url) {
+                match fetch_direct_version(version_url, version_json_path.as_deref()) {
                     Ok(Some(found)) => version = found,
-                    Ok(None) => println!(
-                        "[voidbox] Warning: Could not parse version from {}",
-                        version_url
-                    ),
-                    Err(e) => println!(
-                        "[voidbox] Warning: Could not fetch version from {}: {}",
-                        version_url, e
+                    Ok(None) => {
+                        crate::warn!("Warning: Could not parse version from {}", version_url)
+                    }
+                    Err(e) => crate::warn!(
+                        "Warning: Could not fetch version from {}: {}",
+                        version_url,
+                        e
                     ),
                 }
             }
             (version, url.clone(), archive_type.clone())
         }
-        SourceConfig::Local { path, archive_type } => {
-            // Install from local path
-            let install_dir = manifest
-                .binary
-                .install_dir
-                .as_deref()
-                .unwrap_or(&manifest.app.name);
-            let target_dir = install_root.join(format!("opt/{}", install_dir));
-            fs::create_dir_all(&target_dir)?;
-
-            if path.is_dir() {
-                copy_dir_all(path, &target_dir)?;
-                create_binary_symlink(install_root, manifest)?;
-                return Ok(None);
-            }
-
-            let path_str = path.to_string_lossy();
-            let extension = if let Some(ext) = archive_type {
-                if ext.starts_with('.') {
-                    ext.clone()
-                } else {
-                    format!(".{}", ext)
-                }
-            } else {
-                get_extension_from_url(&path_str)
-            };
-
-            if let Some(archive_type) =
-                ArchiveType::from_extension(&extension.trim_start_matches('.'))
-            {
-                extract_archive(archive_type, path, &target_dir)?;
-                create_binary_symlink(install_root, manifest)?;
-                return Ok(None);
-            }
-
-            fs::copy(path, target_dir.join(path.file_name().unwrap()))?;
-            create_binary_symlink(install_root, manifest)?;
-            return Ok(None);
-        }
+        SourceConfig::Local { .. } => return Ok(None),
     };
 
-    let actual_version = if version != "latest" {
-        Some(version.clone())
-    } else {
-        None
-    };
-
-    println!(
-        "[voidbox] Downloading {} v{}...",
-        manifest.app.display_name, version
-    );
+    Ok(Some(ResolvedRelease {
+        version,
+        url,
+        archive_type,
+        asset_name: None,
+    }))
+}
 
+/// Install the app from a local file, archive, package or directory
+fn install_local_source(
+    rootfs: &Path,
+    install_root: &Path,
+    manifest: &AppManifest,
+    path: &Path,
+    archive_type: Option<&str>,
+    progress: Option<&InstallProgress>,
+) -> Result<(), InstallError> {
     let install_dir = manifest
         .binary
         .install_dir
         .as_deref()
         .unwrap_or(&manifest.app.name);
+    let target_dir = install_root.join(format!("opt/{}", install_dir));
+    fs::create_dir_all(&target_dir)?;
+
+    if path.is_dir() {
+        copy_dir_all(path, &target_dir)?;
+        create_binary_symlink(install_root, manifest)?;
+        return Ok(());
+    }
 
-    let extension = if let Some(ext) = override_ext {
+    let path_str = path.to_string_lossy();
+    let extension = if let Some(ext) = archive_type {
         if ext.starts_with('.') {
-            ext
+            ext.to_string()
         } else {
             format!(".{}", ext)
         }
     } else {
-        get_extension_from_url(&download_url)
+        get_extension_from_url(&path_str)
     };
 
-    let archive_path = install_root.join(format!("{}_download{}", install_dir, extension));
+    let archive_type = ArchiveType::from_extension(&extension.trim_start_matches('.'));
+    if archive_type == Some(ArchiveType::Deb) {
+        return install_deb_package(rootfs, install_root, manifest, path);
+    }
+    if archive_type == Some(ArchiveType::AppImage) {
+        return install_appimage(rootfs, install_root, manifest, path);
+    }
+    if let Some(archive_type) = archive_type {
+        let mut reporter = ExtractProgress::new(progress, (0.85, 0.95), "Extracting...");
+        extract_archive(archive_type, path, &target_dir, &mut |done, total| {
+            reporter.update(done, total)
+        })?;
+        reporter.finish();
+        create_binary_symlink(install_root, manifest)?;
+        return Ok(());
+    }
 
-    download_file(&download_url, &archive_path, true)?;
+    fs::copy(path, target_dir.join(path.file_name().unwrap()))?;
+    create_binary_symlink(install_root, manifest)?;
+    Ok(())
+}
 
-    println!("[voidbox] Extracting...");
-    let target_dir = install_root.join(format!("opt/{}", install_dir));
+/// Absolute path of the local source directory for `--symlink-local`
+fn local_link_source(manifest: &AppManifest) -> Result<PathBuf, InstallError> {
+    let SourceConfig::Local { path, .. } = &manifest.source else {
+        return Err(InstallError::Failed(
+            "--symlink-local needs a local directory source".into(),
+        ));
+    };
+    if !path.is_dir() {
+        return Err(InstallError::Failed(format!(
+            "--symlink-local needs a directory, not {}",
+            path.display()
+        )));
+    }
+    Ok(fs::canonicalize(path)?)
+}
+
+/// Link an app whose files stay in a local directory
+///
+/// Only the install dir and the binary symlink are created; the directory
+/// itself is bind-mounted over the install dir at run time, so rebuilding
+/// it on the host takes effect on the next launch.
+fn link_local_source(
+    install_root: &Path,
+    manifest: &AppManifest,
+    source: &Path,
+) -> Result<(), InstallError> {
+    let target_dir = install_root.join(format!("opt/{}", app_install_dir(manifest)));
     fs::create_dir_all(&target_dir)?;
+    link_binary(install_root, manifest, source)?;
+    crate::info!("Linked {} (not copied).", source.display());
+    Ok(())
+}
+
+/// Terminal bar for the rest of a background download
+fn download_bar() -> ProgressBar {
+    let template = "{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] \
+                    {bytes}/{total_bytes} {bytes_per_sec} ({eta})";
+    let bar = ProgressBar::new(0);
+    bar.set_style(
+        ProgressStyle::default_bar()
+            .template(template)
+            .unwrap()
+            .progress_chars("#>-"),
+    );
+    bar
+}
+
+/// Extract and link a downloaded app release
+fn finish_app_download(
+    rootfs: &Path,
+    install_root: &Path,
+    manifest: &AppManifest,
+    download: AppDownload,
+    progress: Option<&InstallProgress>,
+) -> Result<Option<String>, InstallError> {
+    let AppDownload {
+        version,
+        path: archive_path,
+        extension,
+    } = download;
+    let actual_version = if version != "latest" {
+        Some(version)
+    } else {
+        None
+    };
+    let install_dir = app_install_dir(manifest);
 
-    // Extract based on archive type
     let archive_type =
         ArchiveType::from_extension(&extension.trim_start_matches('.')).unwrap_or(ArchiveType::Zip);
-    extract_archive(archive_type, &archive_path, &target_dir)?;
+    if archive_type == ArchiveType::Deb {
+        report_progress(progress, 0.85, "Installing package...");
+        let result = install_deb_package(rootfs, install_root, manifest, &archive_path);
+        fs::remove_file(&archive_path).ok();
+        result?;
+        return Ok(actual_version);
+    }
+    if archive_type == ArchiveType::AppImage {
+        report_progress(progress, 0.85, "Extracting AppImage...");
+        let result = install_appimage(rootfs, install_root, manifest, &archive_path);
+        fs::remove_file(&archive_path).ok();
+        result?;
+        return Ok(actual_version);
+    }
+
+    crate::info!("Extracting...");
+    report_progress(progress, 0.85, "Extracting...");
+    let target_dir = install_root.join(format!("opt/{}", install_dir));
+    fs::create_dir_all(&target_dir)?;
+
+    // Extract based on archive type
+    let mut reporter = ExtractProgress::new(progress, (0.85, 0.95), "Extracting...");
+    extract_archive(
+        archive_type,
+        &archive_path,
+        &target_dir,
+        &mut |done, total| reporter.update(done, total),
+    )?;
+    reporter.finish();
 
     fs::remove_file(archive_path)?;
 
     // Create symlink to binary
+    report_progress(progress, 0.95, "Linking binary...");
     create_binary_symlink(install_root, manifest)?;
 
     Ok(actual_version)
@@ -677,21 +2043,18 @@ fn fetch_github_release(
     asset_os: &str,
     asset_arch: &str,
     asset_extension: Option<&str>,
-) -> Result<(String, String), InstallError> {
-    let api_url = format!(
-        "https://api.github.com/repos/{}/{}/releases/latest",
-        owner, repo
-    );
-
-    let mut resp = ureq::get(&api_url)
-        .header("User-Agent", crate::APP_NAME)
-        .call()
-        .map_err(|e| InstallError::Failed(format!("GitHub API error: {}", e)))?;
+    version: Option<&str>,
+) -> Result<ResolvedRelease, InstallError> {
+    let releases_url = format!("https://api.github.com/repos/{}/{}/releases", owner, repo);
+    let candidates = match version {
+        Some(v) => vec![
+            format!("{}/tags/v{}", releases_url, v),
+            format!("{}/tags/{}", releases_url, v),
+        ],
+        None => vec![format!("{}/latest", releases_url)],
+    };
 
-    let body = resp
-        .body_mut()
-        .read_to_string()
-        .map_err(|e| InstallError::Failed(format!("Failed to read response: {}", e)))?;
+    let body = fetch_release_body(&candidates, "GitHub")?;
 
     let release: GitHubRelease = serde_json::from_str(&body)
         .map_err(|e| InstallError::Failed(format!("Failed to parse GitHub response: {}", e)))?;
@@ -702,12 +2065,17 @@ fn fetch_github_release(
     for asset in release.assets {
         let name_lower = asset.name.to_lowercase();
         if name_lower.contains(asset_os) && name_lower.contains(asset_arch) {
-            if let Some(ext) = asset_extension {
-                if asset.name.ends_with(ext) {
-                    return Ok((version, asset.browser_download_url));
+            match asset_extension {
+                Some(ext) if !asset.name.ends_with(ext) => continue,
+                _ => {
+                    let asset_name = Some(asset.name.clone());
+                    return Ok(ResolvedRelease {
+                        version,
+                        url: asset.download_url(),
+                        archive_type: None,
+                        asset_name,
+                    });
                 }
-            } else {
-                return Ok((version, asset.browser_download_url));
             }
         }
     }
@@ -718,9 +2086,144 @@ fn fetch_github_release(
     )))
 }
 
-fn fetch_direct_version(version_url: &str) -> Result<Option<String>, InstallError> {
+/// Fetch the first release API response that exists
+/// Candidates are tried in order; a 404 moves on to the next one
+fn fetch_release_body(candidates: &[String], api_name: &str) -> Result<String, InstallError> {
+    for api_url in candidates {
+        match http_get(api_url).call() {
+            Ok(mut resp) => {
+                return resp
+                    .body_mut()
+                    .read_to_string()
+                    .map_err(|e| InstallError::Failed(format!("Failed to read response: {}", e)));
+            }
+            Err(ureq::Error::StatusCode(404)) => continue,
+            Err(e) => {
+                return Err(InstallError::NetworkError(format!(
+                    "{} API error: {}",
+                    api_name, e
+                )));
+            }
+        }
+    }
+
+    Err(InstallError::Failed(format!(
+        "{} release not found: {}",
+        api_name,
+        candidates.join(", ")
+    )))
+}
+
+/// GitLab releases API URL for a project ID or "group/project" path
+pub(crate) fn gitlab_releases_url(project_id: &str) -> String {
+    format!(
+        "https://gitlab.com/api/v4/projects/{}/releases",
+        project_id.replace('/', "%2F")
+    )
+}
+
+fn fetch_gitlab_release(
+    project_id: &str,
+    asset_os: &str,
+    asset_arch: &str,
+    asset_extension: Option<&str>,
+    version: Option<&str>,
+) -> Result<ResolvedRelease, InstallError> {
+    let releases_url = gitlab_releases_url(project_id);
+
+    let release = if let Some(v) = version {
+        let candidates = [
+            format!("{}/v{}", releases_url, v.replace('/', "%2F")),
+            format!("{}/{}", releases_url, v.replace('/', "%2F")),
+        ];
+        let body = fetch_release_body(&candidates, "GitLab")?;
+        serde_json::from_str::<GitLabRelease>(&body)
+            .map_err(|e| InstallError::Failed(format!("Failed to parse GitLab response: {}", e)))?
+    } else {
+        let body = fetch_release_body(&[releases_url], "GitLab")?;
+        latest_gitlab_release(&body, project_id)?
+    };
+
+    gitlab_release_asset(release, project_id, asset_os, asset_arch, asset_extension)
+}
+
+/// Newest release in a GitLab releases list (returned newest first)
+fn latest_gitlab_release(body: &str, project_id: &str) -> Result<GitLabRelease, InstallError> {
+    let releases: Vec<GitLabRelease> = serde_json::from_str(body)
+        .map_err(|e| InstallError::Failed(format!("Failed to parse GitLab response: {}", e)))?;
+
+    releases.into_iter().next().ok_or_else(|| {
+        InstallError::Failed(format!(
+            "No releases found for GitLab project {}",
+            project_id
+        ))
+    })
+}
+
+/// Version, download URL and name of the release link matching the platform
+fn gitlab_release_asset(
+    release: GitLabRelease,
+    project_id: &str,
+    asset_os: &str,
+    asset_arch: &str,
+    asset_extension: Option<&str>,
+) -> Result<ResolvedRelease, InstallError> {
+    let version = release.tag_name.trim_start_matches('v').to_string();
+
+    // Find matching release link
+    for link in release.assets.links {
+        let name_lower = link.name.to_lowercase();
+        if name_lower.contains(asset_os) && name_lower.contains(asset_arch) {
+            let url = link.direct_asset_url.unwrap_or(link.url);
+            match asset_extension {
+                Some(ext) if !link.name.ends_with(ext) => continue,
+                _ => {
+                    return Ok(ResolvedRelease {
+                        version,
+                        url,
+                        archive_type: None,
+                        asset_name: Some(link.name),
+                    });
+                }
+            }
+        }
+    }
+
+    Err(InstallError::Failed(format!(
+        "No matching asset found for {} {} in GitLab project {}",
+        asset_os, asset_arch, project_id
+    )))
+}
+
+fn fetch_direct_version(
+    version_url: &str,
+    json_path: Option<&str>,
+) -> Result<Option<String>, InstallError> {
     let content = download_string(version_url)?;
-    Ok(parse_version_response(&content))
+    Ok(json_path
+        .and_then(|path| version_at_json_path(&content, path))
+        .or_else(|| parse_version_response(&content)))
+}
+
+/// Look up a version string at a dotted JSON path (e.g. "data.tag")
+///
+/// Numeric segments index into arrays, so "releases.0.version" works too.
+pub(crate) fn version_at_json_path(content: &str, path: &str) -> Option<String> {
+    let root: Value = serde_json::from_str(content.trim()).ok()?;
+    let value = path
+        .split('.')
+        .filter(|segment| !segment.is_empty())
+        .try_fold(&root, |value, segment| match value {
+            Value::Array(items) => items.get(segment.parse::<usize>().ok()?),
+            _ => value.get(segment),
+        })?;
+
+    let version = match value {
+        Value::String(s) => s.clone(),
+        Value::Number(n) => n.to_string(),
+        _ => return None,
+    };
+    Some(version.trim().trim_start_matches('v').to_string())
 }
 
 fn resolve_bundle_version(manifest: &AppManifest) -> Result<Option<String>, InstallError> {
@@ -739,12 +2242,32 @@ fn resolve_bundle_version(manifest: &AppManifest) -> Result<Option<String>, Inst
                 asset_os,
                 asset_arch,
                 asset_extension.as_deref(),
+                None,
             )?
-            .0,
+            .version,
         )),
-        SourceConfig::Direct { version_url, .. } => {
+        SourceConfig::Gitlab {
+            project_id,
+            asset_os,
+            asset_arch,
+            asset_extension,
+        } => Ok(Some(
+            fetch_gitlab_release(
+                project_id,
+                asset_os,
+                asset_arch,
+                asset_extension.as_deref(),
+                None,
+            )?
+            .version,
+        )),
+        SourceConfig::Direct {
+            version_url,
+            version_json_path,
+            ..
+        } => {
             if let Some(version_url) = version_url.as_deref() {
-                fetch_direct_version(version_url)
+                fetch_direct_version(version_url, version_json_path.as_deref())
             } else {
                 Ok(None)
             }
@@ -800,15 +2323,22 @@ fn get_extension_from_url(url: &str) -> String {
         ".tar.zst".to_string()
     } else if path.ends_with(".zip") {
         ".zip".to_string()
+    } else if path.ends_with(".deb") {
+        ".deb".to_string()
+    } else if path.to_lowercase().ends_with(".appimage") {
+        ".AppImage".to_string()
     } else {
         ".zip".to_string() // Default
     }
 }
 
+/// Extract an archive, reporting `(done, total)` to `on_progress`: entries
+/// for zips, compressed bytes read for tarballs
 fn extract_archive(
     archive_type: ArchiveType,
     archive_path: &Path,
     target_dir: &Path,
+    on_progress: &mut dyn FnMut(u64, u64),
 ) -> Result<(), InstallError> {
     match archive_type {
         ArchiveType::Zip => {
@@ -816,7 +2346,10 @@ fn extract_archive(
             let mut archive = zip::ZipArchive::new(file)
                 .map_err(|e| InstallError::Failed(format!("Failed to open zip: {}", e)))?;
 
+            let total = archive.len() as u64;
             for i in 0..archive.len() {
+                check_interrupted()?;
+                on_progress(i as u64, total);
                 let mut file = archive.by_index(i).map_err(|e| {
                     InstallError::Failed(format!("Failed to read zip entry: {}", e))
                 })?;
@@ -846,11 +2379,14 @@ fn extract_archive(
                     }
                 }
             }
+            on_progress(total, total);
             Ok(())
         }
         ArchiveType::TarGz => {
             let file = File::open(archive_path)?;
-            let decoder = GzDecoder::new(file);
+            let total = file.metadata()?.len();
+            let mut on_read = |read| on_progress(read, total);
+            let decoder = GzDecoder::new(CountingReader::new(file, &mut on_read));
             let mut archive = tar::Archive::new(decoder);
             archive.unpack(target_dir)?;
             Ok(())
@@ -862,23 +2398,243 @@ fn extract_archive(
     }
 }
 
-fn create_binary_symlink(install_root: &Path, manifest: &AppManifest) -> Result<(), InstallError> {
-    let install_dir = manifest
+/// Install a `.deb` into the app layer with dpkg inside the container
+///
+/// Missing dependencies are pulled in with `apt-get -f install`. The app
+/// binary is then looked up where packages put it rather than in /opt/<app>.
+fn install_deb_package(
+    rootfs: &Path,
+    install_root: &Path,
+    manifest: &AppManifest,
+    deb_path: &Path,
+) -> Result<(), InstallError> {
+    crate::info!("Installing package {}...", deb_path.display());
+
+    // The package must be visible inside the container
+    let staged = install_root.join("voidbox-package.deb");
+    fs::copy(deb_path, &staged)?;
+
+    let script = format!(
+        "#!/bin/bash\n\
+         {trace}\n\
+         export DEBIAN_FRONTEND=noninteractive\n\
+         export PATH=/usr/local/sbin:/usr/local/bin:/usr/sbin:/usr/bin:/sbin:/bin\n\
+         \n\
+         apt-get update -qq\n\
+         if ! dpkg -i /voidbox-package.deb; then\n\
+         \x20   apt-get -f install -y --no-install-recommends || exit 1\n\
+         fi\n\
+         dpkg -s \"$(dpkg-deb -f /voidbox-package.deb Package)\" >/dev/null || exit 1\n\
+         apt-get clean\n\
+         rm -rf /var/lib/apt/lists/*\n",
+        trace = trace_script_line()
+    );
+    let status = run_container_script(rootfs, install_root, "voidbox-package.sh", &script);
+    fs::remove_file(&staged).ok();
+
+    let status = status?;
+    if !status.success() {
+        return Err(InstallError::Failed(format!(
+            "dpkg could not install {} (exit status {})",
+            deb_path.display(),
+            exit_code_string(status)
+        )));
+    }
+
+    link_package_binary(install_root, manifest)
+}
+
+/// Unpack an AppImage into /opt/<install_dir> and link its `AppRun`
+///
+/// The AppImage is extracted by its own runtime inside the container, so no
+/// FUSE is needed at run time and nothing from it executes on the host.
+fn install_appimage(
+    rootfs: &Path,
+    install_root: &Path,
+    manifest: &AppManifest,
+    appimage_path: &Path,
+) -> Result<(), InstallError> {
+    crate::info!("Extracting AppImage {}...", appimage_path.display());
+
+    let install_dir = app_install_dir(manifest);
+    let target_dir = install_root.join(format!("opt/{}", install_dir));
+    let extracted_dir = target_dir.join("squashfs-root");
+    if extracted_dir.exists() {
+        fs::remove_dir_all(&extracted_dir)?;
+    }
+    fs::create_dir_all(&target_dir)?;
+
+    let staged = target_dir.join("voidbox-app.AppImage");
+    fs::copy(appimage_path, &staged)?;
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&staged, fs::Permissions::from_mode(0o755))?;
+    }
+
+    let script = format!(
+        "#!/bin/bash\n\
+         {trace}\n\
+         cd /opt/{dir} || exit 1\n\
+         ./voidbox-app.AppImage --appimage-extract >/dev/null\n",
+        trace = trace_script_line(),
+        dir = install_dir
+    );
+    let status = run_container_script(rootfs, install_root, "voidbox-appimage.sh", &script);
+    fs::remove_file(&staged).ok();
+
+    let status = status?;
+    if !status.success() {
+        return Err(InstallError::Failed(format!(
+            "Could not extract {} (exit status {})",
+            appimage_path.display(),
+            exit_code_string(status)
+        )));
+    }
+    if fs::symlink_metadata(extracted_dir.join("AppRun")).is_err() {
+        return Err(InstallError::Failed(format!(
+            "{} has no AppRun",
+            appimage_path.display()
+        )));
+    }
+
+    let link_path = install_root.join("usr/bin").join(&manifest.binary.name);
+    fs::create_dir_all(install_root.join("usr/bin"))?;
+    if fs::symlink_metadata(&link_path).is_ok() {
+        fs::remove_file(&link_path)?;
+    }
+    let container_path = format!("/opt/{}/squashfs-root/AppRun", install_dir);
+    std::os::unix::fs::symlink(container_path, link_path)?;
+    Ok(())
+}
+
+/// Run `script` inside the container, staged at /<file_name> in the layer
+fn run_container_script(
+    rootfs: &Path,
+    install_root: &Path,
+    file_name: &str,
+    script: &str,
+) -> Result<std::process::ExitStatus, InstallError> {
+    let script_path = install_root.join(file_name);
+    fs::write(&script_path, script)?;
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::PermissionsExt;
+        fs::set_permissions(&script_path, fs::Permissions::from_mode(0o755))?;
+    }
+
+    let status = Command::new(internal_run_exe()?)
+        .arg("internal-run")
+        .arg(rootfs)
+        .arg(format!("/{}", file_name))
+        .stdout(crate::logging::child_stdout())
+        .stderr(Stdio::inherit())
+        .status();
+
+    fs::remove_file(&script_path).ok();
+    Ok(status?)
+}
+
+/// Exit code of a finished process, or "unknown" if it was killed
+fn exit_code_string(status: std::process::ExitStatus) -> String {
+    status
+        .code()
+        .map_or_else(|| "unknown".into(), |c| c.to_string())
+}
+
+/// Make the binary a package installed available as /usr/bin/<name>
+///
+/// Packages usually ship that link themselves; otherwise `binary.path`
+/// (absolute in the container) or a file named like the binary under /opt,
+/// /usr/share or /usr/lib is linked.
+fn link_package_binary(install_root: &Path, manifest: &AppManifest) -> Result<(), InstallError> {
+    let binary_name = &manifest.binary.name;
+    let link_path = install_root.join("usr/bin").join(binary_name);
+    if fs::symlink_metadata(&link_path).is_ok() {
+        return Ok(());
+    }
+
+    let explicit = manifest
+        .binary
+        .path
+        .as_deref()
+        .map(|path| install_root.join(path.trim_start_matches('/')))
+        .filter(|path| fs::symlink_metadata(path).is_ok());
+    let found = explicit.or_else(|| {
+        ["opt", "usr/share", "usr/lib"]
+            .iter()
+            .flat_map(|dir| WalkDir::new(install_root.join(dir)).max_depth(4))
+            .filter_map(|entry| entry.ok())
+            .find(|entry| {
+                entry.file_name().to_string_lossy() == binary_name.as_str()
+                    && !entry.file_type().is_dir()
+            })
+            .map(|entry| entry.into_path())
+    });
+
+    let binary_path = found.ok_or_else(|| {
+        InstallError::Failed(format!(
+            "Binary '{}' not found in the package (set binary.path)",
+            binary_name
+        ))
+    })?;
+    let relative_path = binary_path
+        .strip_prefix(install_root)
+        .map_err(|e| InstallError::Failed(format!("Path error: {}", e)))?;
+
+    fs::create_dir_all(install_root.join("usr/bin"))?;
+    std::os::unix::fs::symlink(Path::new("/").join(relative_path), link_path)?;
+    Ok(())
+}
+
+/// Directory under /opt the app's files live in
+pub(crate) fn app_install_dir(manifest: &AppManifest) -> &str {
+    manifest
         .binary
         .install_dir
         .as_deref()
-        .unwrap_or(&manifest.app.name);
-    let target_dir = install_root.join(format!("opt/{}", install_dir));
+        .unwrap_or(&manifest.app.name)
+}
 
+/// Directory levels searched for the binary unless the manifest says otherwise
+const DEFAULT_BINARY_SEARCH_DEPTH: usize = 5;
+
+fn create_binary_symlink(install_root: &Path, manifest: &AppManifest) -> Result<(), InstallError> {
+    let target_dir = install_root.join(format!("opt/{}", app_install_dir(manifest)));
+    link_binary(install_root, manifest, &target_dir)
+}
+
+/// Find the app binary in `search_dir` and link it as /usr/bin/<name>
+///
+/// `search_dir` holds what ends up at /opt/<install_dir> in the container:
+/// the install dir itself, or the local source dir bound over it.
+fn link_binary(
+    install_root: &Path,
+    manifest: &AppManifest,
+    search_dir: &Path,
+) -> Result<(), InstallError> {
     // Find the binary
     let binary_name = &manifest.binary.name;
+    let depth = manifest
+        .binary
+        .search_depth
+        .unwrap_or(DEFAULT_BINARY_SEARCH_DEPTH);
     let mut binary_path = None;
 
-    // Priority 1: Check manifest path (suffix match for flexibility)
+    // Priority 1: Manifest path relative to the app directory (any depth)
     if let Some(explicit_path) = &manifest.binary.path {
-        for entry in WalkDir::new(&target_dir).max_depth(3) {
+        let exact = search_dir.join(explicit_path.trim_start_matches('/'));
+        if fs::symlink_metadata(&exact).is_ok() {
+            binary_path = Some(exact);
+        }
+    }
+
+    // Priority 2: Manifest path (suffix match for flexibility)
+    if let (None, Some(explicit_path)) = (&binary_path, &manifest.binary.path) {
+        for entry in WalkDir::new(search_dir).max_depth(depth) {
             if let Ok(entry) = entry {
-                if entry.path().ends_with(explicit_path) && entry.path().is_file() {
+                if entry.path().ends_with(explicit_path) && !entry.file_type().is_dir() {
                     binary_path = Some(entry.path().to_path_buf());
                     break;
                 }
@@ -886,12 +2642,12 @@ fn create_binary_symlink(install_root: &Path, manifest: &AppManifest) -> Result<
         }
     }
 
-    // Priority 2: Name match (existing logic)
+    // Priority 3: Name match (existing logic)
     if binary_path.is_none() {
-        for entry in WalkDir::new(&target_dir).max_depth(3) {
+        for entry in WalkDir::new(search_dir).max_depth(depth) {
             if let Ok(entry) = entry {
                 if entry.file_name().to_string_lossy() == binary_name.as_str()
-                    && entry.path().is_file()
+                    && !entry.file_type().is_dir()
                 {
                     binary_path = Some(entry.path().to_path_buf());
                     break;
@@ -901,14 +2657,21 @@ fn create_binary_symlink(install_root: &Path, manifest: &AppManifest) -> Result<
     }
 
     let binary_path = binary_path.ok_or_else(|| {
-        InstallError::Failed(format!("Binary '{}' not found in archive", binary_name))
+        InstallError::Failed(format!(
+            "Binary '{}' not found in archive (searched {} levels deep; \
+             set binary.path or binary.search_depth)",
+            binary_name, depth
+        ))
     })?;
+    ensure_executable(install_root, &binary_path)?;
 
     // Create /usr/bin symlink
     let relative_path = binary_path
-        .strip_prefix(install_root)
+        .strip_prefix(search_dir)
         .map_err(|e| InstallError::Failed(format!("Path error: {}", e)))?;
-    let container_path = Path::new("/").join(relative_path);
+    let container_path = Path::new("/opt")
+        .join(app_install_dir(manifest))
+        .join(relative_path);
 
     fs::create_dir_all(install_root.join("usr/bin"))?;
     let link_path = install_root.join(format!("usr/bin/{}", binary_name));
@@ -922,6 +2685,53 @@ fn create_binary_symlink(install_root: &Path, manifest: &AppManifest) -> Result<
     Ok(())
 }
 
+/// Check that `binary` is a regular file once symlinks are followed, and
+/// make it executable if the archive lost its mode bits
+///
+/// Absolute symlink targets are resolved inside `install_root`, the way the
+/// container will see them.
+fn ensure_executable(install_root: &Path, binary: &Path) -> Result<(), InstallError> {
+    use std::os::unix::fs::PermissionsExt;
+
+    let dangling = |target: &Path| {
+        InstallError::Failed(format!(
+            "Binary {} points to {}, which does not exist",
+            binary.display(),
+            target.display()
+        ))
+    };
+
+    let mut target = binary.to_path_buf();
+    // Same limit as the kernel's
+    for _ in 0..40 {
+        let metadata = fs::symlink_metadata(&target).map_err(|_| dangling(&target))?;
+        if !metadata.file_type().is_symlink() {
+            if !metadata.is_file() {
+                return Err(InstallError::Failed(format!(
+                    "Binary {} is not a regular file",
+                    target.display()
+                )));
+            }
+            let mode = metadata.permissions().mode();
+            if mode & 0o111 == 0 {
+                crate::info!("Making {} executable", target.display());
+                fs::set_permissions(&target, fs::Permissions::from_mode(mode | 0o755))?;
+            }
+            return Ok(());
+        }
+
+        let link = fs::read_link(&target)?;
+        target = match link.strip_prefix("/") {
+            Ok(inside) => install_root.join(inside),
+            Err(_) => target.parent().unwrap_or(install_root).join(link),
+        };
+    }
+    Err(InstallError::Failed(format!(
+        "Binary {} is a symlink loop",
+        binary.display()
+    )))
+}
+
 fn copy_dir_all(src: &Path, dst: &Path) -> Result<(), InstallError> {
     fs::create_dir_all(dst)?;
     for entry in fs::read_dir(src)? {
@@ -940,8 +2750,12 @@ fn save_installed_app(
     manifest: &AppManifest,
     actual_version: Option<&str>,
     base_version: Option<&str>,
+    pinned_version: Option<&str>,
+    linked_source: Option<&Path>,
+    source: Option<&InstallSource>,
 ) -> Result<(), InstallError> {
     let db_path = paths::database_path();
+    let _lock = lock_file(&db_path)?;
 
     let mut apps: Vec<InstalledApp> = if db_path.exists() {
         let content = fs::read_to_string(&db_path)?;
@@ -950,7 +2764,12 @@ fn save_installed_app(
         Vec::new()
     };
 
-    // Remove existing entry if any
+    // Remove existing entry if any (a reinstall stays frozen and keeps its source)
+    let previous = apps.iter().find(|a| a.name == manifest.app.name);
+    let frozen = previous.is_some_and(|a| a.frozen);
+    let source = source
+        .cloned()
+        .or_else(|| previous.and_then(|a| a.source.clone()));
     apps.retain(|a| a.name != manifest.app.name);
 
     // Use actual downloaded version if available, otherwise manifest version
@@ -966,11 +2785,85 @@ fn save_installed_app(
         base_version: base_version.map(|v| v.to_string()),
         installed_date: Some(chrono::Local::now().format("%Y-%m-%d %H:%M:%S").to_string()),
         manifest_path: Some(paths::manifest_path(&manifest.app.name)),
+        pinned_version: pinned_version.map(|v| v.to_string()),
+        linked_source: linked_source.map(Path::to_path_buf),
+        frozen,
+        source,
     });
 
     let content = serde_json::to_string_pretty(&apps)
         .map_err(|e| InstallError::Failed(format!("Failed to serialize: {}", e)))?;
-    fs::write(&db_path, content)?;
+    write_atomic(&db_path, content)?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ubuntu_base_url_for_arm64() {
+        let release_url = format!("{}24.04.3/release/", crate::UBUNTU_RELEASES_URL);
+        let listing = r#"<a href="ubuntu-base-24.04.3-base-amd64.tar.gz">ubuntu-base-24.04.3-base-amd64.tar.gz</a>
+<a href="ubuntu-base-24.04.3-base-arm64.tar.gz">ubuntu-base-24.04.3-base-arm64.tar.gz</a>
+<a href="ubuntu-base-24.04.3-base-riscv64.tar.gz">ubuntu-base-24.04.3-base-riscv64.tar.gz</a>"#;
+
+        assert_eq!(
+            ubuntu_base_url(&release_url, listing, "24.04.3", "arm64").unwrap(),
+            format!("{}ubuntu-base-24.04.3-base-arm64.tar.gz", release_url)
+        );
+        assert_eq!(
+            ubuntu_base_url(&release_url, listing, "24.04.3", "amd64").unwrap(),
+            format!("{}ubuntu-base-24.04.3-base-amd64.tar.gz", release_url)
+        );
+        assert!(ubuntu_base_url(&release_url, listing, "24.04.3", "s390x").is_none());
+    }
+
+    const GITLAB_RELEASES: &str = include_str!("../../tests/fixtures/gitlab_releases.json");
+
+    #[test]
+    fn repo_key_must_match_its_fingerprint() {
+        if which::which("gpg").is_err() {
+            return;
+        }
+        let key = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/repo-key.asc");
+
+        check_key_fingerprint(&key, "E33B 107B CA6F 8952 5117  D1ED 36E8 056A C39D DF06").unwrap();
+        assert!(check_key_fingerprint(&key, "0000000000000000000000000000000000000000").is_err());
+    }
+
+    #[test]
+    fn gitlab_fixture_picks_newest_release_asset() {
+        let release = latest_gitlab_release(GITLAB_RELEASES, "example/app").unwrap();
+        let asset =
+            gitlab_release_asset(release, "example/app", "linux", "x86_64", Some(".tar.gz"))
+                .unwrap();
+
+        assert_eq!(asset.version, "1.4.2");
+        assert_eq!(
+            asset.url,
+            "https://gitlab.com/example/app/-/releases/v1.4.2/downloads/app-linux-x86_64.tar.gz"
+        );
+    }
+
+    #[test]
+    fn gitlab_fixture_falls_back_to_link_url() {
+        let release = latest_gitlab_release(GITLAB_RELEASES, "example/app").unwrap();
+        let asset = gitlab_release_asset(release, "example/app", "linux", "aarch64", None).unwrap();
+
+        assert_eq!(
+            asset.url,
+            "https://gitlab.com/example/app/-/package_files/2003/download"
+        );
+        // The download URL has no extension, so the link name decides it
+        assert_eq!(asset.extension(), ".tar.gz");
+    }
+
+    #[test]
+    fn gitlab_fixture_without_matching_asset_fails() {
+        let release = latest_gitlab_release(GITLAB_RELEASES, "example/app").unwrap();
+        assert!(gitlab_release_asset(release, "example/app", "windows", "x86_64", None).is_err());
+        assert!(latest_gitlab_release("[]", "example/app").is_err());
+    }
+}
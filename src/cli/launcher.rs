@@ -8,10 +8,12 @@
 
 use crate::cli;
 use crate::gui;
-use crate::manifest::parse_manifest;
-use crate::storage::paths;
+use crate::manifest::{AppManifest, InstallSource, parse_manifest};
+use crate::storage::{InstallLock, paths};
 use std::fs;
 use std::os::unix::fs::symlink;
+use std::sync::mpsc::channel;
+use std::thread;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -19,6 +21,9 @@ pub enum LauncherError {
     #[error("Unknown app: {0}")]
     UnknownApp(String),
 
+    #[error("{0} is not installed - run `voidbox install {1}` first")]
+    NotInstalled(String, String),
+
     #[error("Manifest error: {0}")]
     ManifestError(#[from] crate::manifest::ManifestError),
 
@@ -32,22 +37,31 @@ pub enum LauncherError {
     IoError(#[from] std::io::Error),
 }
 
-/// List of all embedded apps - used to create symlinks
-pub const EMBEDDED_APPS: &[&str] = &["brave", "discord", "vscode"];
-
-/// Embedded manifests for known apps
-/// Add new apps here with their manifest content
-fn get_embedded_manifest(app_name: &str) -> Option<&'static str> {
-    match app_name {
-        "brave" => Some(include_str!("../../examples/manifests/brave.toml")),
-        "discord" => Some(include_str!("../../examples/manifests/discord.toml")),
-        "vscode" => Some(include_str!("../../examples/manifests/vscode.toml")),
-        _ => None,
-    }
+/// Environment variable that stops launchers from installing missing apps
+pub const NO_AUTO_INSTALL_ENV: &str = "VOIDBOX_NO_AUTO_INSTALL";
+
+/// Check whether launcher auto-install is off (`VOIDBOX_NO_AUTO_INSTALL=1`)
+fn auto_install_disabled() -> bool {
+    std::env::var(NO_AUTO_INSTALL_ENV)
+        .map(|v| matches!(v.trim(), "1" | "true" | "yes"))
+        .unwrap_or(false)
+}
+
+// Generated by build.rs from examples/manifests/*.toml - drop a new
+// manifest there to add an app
+include!(concat!(env!("OUT_DIR"), "/embedded_apps.rs"));
+
+/// Embedded manifest for a known app
+pub(crate) fn get_embedded_manifest(app_name: &str) -> Option<&'static str> {
+    EMBEDDED_MANIFESTS
+        .iter()
+        .find(|(name, _)| *name == app_name)
+        .map(|(_, content)| *content)
 }
 
 /// Install voidbox runtime and create app launcher symlinks
 fn ensure_runtime_installed(app_name: &str, gui_mode: bool) -> Result<(), LauncherError> {
+    let _lock = InstallLock::acquire()?;
     let voidbox_path = paths::install_path();
     let current_exe = std::env::current_exe()?;
 
@@ -68,10 +82,7 @@ fn ensure_runtime_installed(app_name: &str, gui_mode: bool) -> Result<(), Launch
     // Install voidbox if not present
     if !voidbox_installed {
         if !gui_mode {
-            println!(
-                "[voidbox] Installing voidbox to {}...",
-                voidbox_path.display()
-            );
+            crate::info!("Installing voidbox to {}...", voidbox_path.display());
         }
         fs::copy(&current_exe, &voidbox_path)?;
 
@@ -82,14 +93,18 @@ fn ensure_runtime_installed(app_name: &str, gui_mode: bool) -> Result<(), Launch
         }
     }
 
-    // Create symlink for this app if not present
-    if !symlink_exists {
+    // Create launcher symlinks for this and every other embedded app
+    for name in EMBEDDED_APPS {
+        let link = paths::bin_dir().join(format!("void_{}", name));
+        if link.exists() {
+            continue;
+        }
         if !gui_mode {
-            println!("[voidbox] Creating {} symlink...", symlink_path.display());
+            crate::info!("Creating {} symlink...", link.display());
         }
         // Remove broken symlink if it exists
-        let _ = fs::remove_file(&symlink_path);
-        symlink(&voidbox_path, &symlink_path)?;
+        let _ = fs::remove_file(&link);
+        symlink(&voidbox_path, &link)?;
     }
 
     Ok(())
@@ -127,17 +142,32 @@ pub fn should_run_as_launcher() -> Option<String> {
     if args.len() > 1 {
         let subcommands = [
             "internal-init",
+            "internal-update-check",
+            "internal-selftest",
             "install",
             "remove",
+            "reinstall",
             "run",
+            "stop",
+            "ps",
             "update",
             "list",
+            "logs",
             "info",
+            "validate",
+            "rename",
+            "edit",
+            "freeze",
+            "unfreeze",
             "shell",
             "bundle",
+            "clean",
+            "du",
             "search",
             "settings",
             "self-update",
+            "selftest",
+            "serve",
             "--help",
             "-h",
             "--version",
@@ -151,8 +181,36 @@ pub fn should_run_as_launcher() -> Option<String> {
     extract_app_name(&args[0])
 }
 
+/// Install an app while showing a determinate zenity progress dialog
+fn install_with_progress_dialog(manifest: &AppManifest) -> Result<(), LauncherError> {
+    let (sender, recv) = channel::<(f32, String)>();
+    let title = format!("Installing {}", manifest.app.display_name);
+    let mut dialog = gui::ProgressDialog::new_determinate(&title, "Preparing...");
+
+    let updater = thread::spawn(move || {
+        for (fraction, message) in recv {
+            dialog.set_progress((fraction * 100.0) as u32);
+            dialog.set_text(&message);
+        }
+        dialog.close();
+    });
+
+    let options = cli::InstallOptions {
+        progress: Some(sender),
+        source: Some(InstallSource::embedded(&manifest.app.name)),
+        ..Default::default()
+    };
+    let result = cli::install_app_from_manifest(manifest, &options);
+
+    // Dropping the options closes the channel and ends the updater thread
+    drop(options);
+    updater.join().ok();
+
+    result.map_err(LauncherError::from)
+}
+
 /// Run in app launcher mode
-pub fn run_launcher(app_name: &str) -> Result<(), LauncherError> {
+pub fn run_launcher(app_name: &str) -> Result<cli::RunOutcome, LauncherError> {
     // Get embedded manifest or error
     let manifest_content = get_embedded_manifest(app_name)
         .ok_or_else(|| LauncherError::UnknownApp(app_name.to_string()))?;
@@ -170,9 +228,24 @@ pub fn run_launcher(app_name: &str) -> Result<(), LauncherError> {
     // Ensure data directories exist
     paths::ensure_dirs()?;
 
-    // Check if app is installed
+    // Check if app is installed. A missing app is checked again under the
+    // install lock, so a second launch waits for the first one's install
+    // instead of starting its own.
     let manifest_path = paths::manifest_path(app_name);
-    let app_installed = manifest_path.exists() && paths::app_layer_dir(app_name).exists();
+    let is_installed = || manifest_path.exists() && paths::app_layer_dir(app_name).exists();
+    let install_lock = if is_installed() {
+        None
+    } else {
+        Some(InstallLock::acquire()?)
+    };
+    let app_installed = install_lock.is_none() || is_installed();
+
+    if !app_installed && auto_install_disabled() {
+        return Err(LauncherError::NotInstalled(
+            display_name.clone(),
+            app_name.to_string(),
+        ));
+    }
 
     if !app_installed {
         // App not installed - install it
@@ -184,20 +257,41 @@ pub fn run_launcher(app_name: &str) -> Result<(), LauncherError> {
                 display_name: display_name.clone(),
                 manifest_content: manifest_content.to_string(),
             }) {
-                eprintln!("GUI Error: {}", e);
-                std::process::exit(1);
+                // No usable display for the egui installer - fall back to
+                // zenity/kdialog if available
+                if !gui::has_gui_support() {
+                    eprintln!("GUI Error: {}", e);
+                    std::process::exit(1);
+                }
+                if !gui::ask_yes_no(
+                    "Voidbox",
+                    &format!("{} is not installed yet. Install it now?", display_name),
+                ) {
+                    return Ok(cli::RunOutcome::default());
+                }
+                std::fs::write(&manifest_path, manifest_content)?;
+                if let Err(e) = install_with_progress_dialog(&manifest) {
+                    gui::show_error("Voidbox", &format!("Installation failed: {}", e));
+                    return Err(e);
+                }
             }
         } else {
-            println!("[voidbox] Installing {}...", display_name);
+            crate::info!("Installing {}...", display_name);
             std::fs::write(&manifest_path, manifest_content)?;
-            cli::install_app_from_manifest(&manifest, false)?;
-            println!("[voidbox] {} installed.", display_name);
+            let options = cli::InstallOptions {
+                source: Some(InstallSource::embedded(&manifest.app.name)),
+                ..Default::default()
+            };
+            cli::install_app_from_manifest(&manifest, &options)?;
+            crate::info!("{} installed.", display_name);
         }
     }
+    // The container must not inherit the lock
+    drop(install_lock);
 
     // Run the app
     if !gui_mode {
-        println!("[voidbox] Starting {}...", display_name);
+        crate::info!("Starting {}...", display_name);
     }
 
     // Get command line args to pass through (skip argv[0])
@@ -205,7 +299,5 @@ pub fn run_launcher(app_name: &str) -> Result<(), LauncherError> {
 
     // Run the app directly using our own run logic
     // This avoids the need to spawn a separate process
-    cli::run_app(app_name, &args, None, false)?;
-
-    Ok(())
+    Ok(cli::run_app(app_name, &args, &cli::RunOptions::default())?)
 }
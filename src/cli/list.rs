@@ -1,6 +1,7 @@
 //! List command implementation
 
-use crate::manifest::InstalledApp;
+use crate::cli::launcher::{EMBEDDED_APPS, get_embedded_manifest};
+use crate::manifest::{InstalledApp, parse_manifest};
 use crate::storage::paths;
 use std::fs;
 use thiserror::Error;
@@ -12,23 +13,22 @@ pub enum ListError {
 
     #[error("Failed to read database: {0}")]
     DatabaseError(String),
+
+    #[error("Manifest error: {0}")]
+    ManifestError(#[from] crate::manifest::ManifestError),
 }
 
 /// List all installed apps
-pub fn list_apps() -> Result<(), ListError> {
-    let db_path = paths::database_path();
+pub fn list_apps(json: bool) -> Result<(), ListError> {
+    let apps = get_installed_apps()?;
 
-    if !db_path.exists() {
-        println!("No apps installed.");
-        println!();
-        println!("Install an app with: voidbox install <manifest.toml>");
+    if json {
+        let output = serde_json::to_string_pretty(&apps)
+            .map_err(|e| ListError::DatabaseError(e.to_string()))?;
+        println!("{}", output);
         return Ok(());
     }
 
-    let content = fs::read_to_string(&db_path)?;
-    let apps: Vec<InstalledApp> =
-        serde_json::from_str(&content).map_err(|e| ListError::DatabaseError(e.to_string()))?;
-
     if apps.is_empty() {
         println!("No apps installed.");
         println!();
@@ -56,6 +56,58 @@ pub fn list_apps() -> Result<(), ListError> {
     Ok(())
 }
 
+/// List the apps bundled with voidbox (available as `void_<name>` launchers)
+pub fn list_available_apps(json: bool) -> Result<(), ListError> {
+    let mut apps = Vec::new();
+    for name in EMBEDDED_APPS {
+        let Some(content) = get_embedded_manifest(name) else {
+            continue;
+        };
+        let manifest = parse_manifest(content)?;
+        let installed = paths::manifest_path(name).exists() && paths::app_layer_dir(name).exists();
+        apps.push((manifest, installed));
+    }
+
+    if json {
+        let entries: Vec<_> = apps
+            .iter()
+            .map(|(manifest, installed)| {
+                serde_json::json!({
+                    "name": manifest.app.name,
+                    "display_name": manifest.app.display_name,
+                    "description": manifest.app.description,
+                    "installed": installed,
+                })
+            })
+            .collect();
+        let output = serde_json::to_string_pretty(&entries)
+            .map_err(|e| ListError::DatabaseError(e.to_string()))?;
+        println!("{}", output);
+        return Ok(());
+    }
+
+    println!("Available apps:");
+    println!();
+
+    for (manifest, installed) in &apps {
+        let status = if *installed {
+            "installed"
+        } else {
+            "not installed"
+        };
+        println!("  {} ({})", manifest.app.display_name, manifest.app.name);
+        if !manifest.app.description.is_empty() {
+            println!("    {}", manifest.app.description);
+        }
+        println!("    Status:   {}", status);
+        println!();
+    }
+
+    println!("Launch (and install on first run) with: void_<app-name>");
+
+    Ok(())
+}
+
 /// Get a list of installed app names
 pub fn get_installed_apps() -> Result<Vec<InstalledApp>, ListError> {
     let db_path = paths::database_path();
@@ -0,0 +1,44 @@
+//! Logs command implementation
+
+use crate::runtime::rotated_log_path;
+use crate::storage::paths;
+use std::fs;
+use std::io::Write;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum LogsError {
+    #[error("App not installed: {0}")]
+    NotInstalled(String),
+
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+}
+
+/// Print the output captured from an app's latest (or previous) run
+pub fn show_logs(app_name: &str, previous: bool) -> Result<(), LogsError> {
+    if !paths::app_dir(app_name).exists() {
+        return Err(LogsError::NotInstalled(app_name.to_string()));
+    }
+
+    let log_path = if previous {
+        paths::app_logs_dir(app_name).join("previous.log")
+    } else {
+        paths::app_log_path(app_name)
+    };
+
+    if !log_path.exists() {
+        crate::info!("No log for {} yet.", app_name);
+        crate::info!("Logs are saved for menu launches and `voidbox run --log`.");
+        return Ok(());
+    }
+
+    // Output from before the log was rotated comes first
+    let mut stdout = std::io::stdout();
+    let rotated = rotated_log_path(&log_path);
+    if rotated.exists() {
+        stdout.write_all(&fs::read(&rotated)?)?;
+    }
+    stdout.write_all(&fs::read(&log_path)?)?;
+    Ok(())
+}
@@ -1,21 +1,47 @@
 //! CLI command handlers
 
+mod clean;
+mod du;
+mod edit;
+mod error;
+mod freeze;
 mod info;
 mod install;
 mod launcher;
 mod list;
+mod logs;
+mod ps;
 mod bundle;
+mod reinstall;
 mod remove;
+mod rename;
 mod run;
+mod selftest;
+mod serve;
 mod shell;
+mod stop;
 mod update;
+mod validate;
 
+pub use clean::*;
+pub use du::*;
+pub use edit::*;
+pub use error::*;
+pub use freeze::*;
 pub use info::*;
 pub use install::*;
 pub use launcher::*;
 pub use list::*;
+pub use logs::*;
+pub use ps::*;
 pub use bundle::*;
+pub use reinstall::*;
 pub use remove::*;
+pub use rename::*;
 pub use run::*;
+pub use selftest::*;
+pub use serve::*;
 pub use shell::*;
+pub use stop::*;
 pub use update::*;
+pub use validate::*;
@@ -0,0 +1,73 @@
+//! Ps command implementation
+
+use crate::cli::stop::{is_container_init, read_container_pid};
+use crate::storage::paths;
+use chrono::{DateTime, Local};
+use std::fs;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum PsError {
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+}
+
+/// List apps with a running container
+///
+/// Each launch records its container init in the app's `run.pid`; files
+/// whose process is gone (e.g. after a crash) are removed along the way.
+pub fn list_running() -> Result<(), PsError> {
+    let apps_dir = paths::apps_dir();
+    let mut running = Vec::new();
+
+    if apps_dir.exists() {
+        for entry in fs::read_dir(&apps_dir)? {
+            let entry = entry?;
+            let name = entry.file_name().to_string_lossy().to_string();
+            let pid_path = paths::app_pid_path(&name);
+            if !pid_path.exists() {
+                continue;
+            }
+
+            match read_container_pid(&pid_path).filter(|pid| is_container_init(*pid)) {
+                Some(pid) => {
+                    // The file is written when the container starts
+                    let started = fs::metadata(&pid_path)?
+                        .modified()
+                        .map(|time| {
+                            DateTime::<Local>::from(time)
+                                .format("%Y-%m-%d %H:%M:%S")
+                                .to_string()
+                        })
+                        .unwrap_or_else(|_| "unknown".to_string());
+                    running.push((name, pid, started));
+                }
+                None => {
+                    crate::debug!("Removing stale {}", pid_path.display());
+                    fs::remove_file(&pid_path).ok();
+                }
+            }
+        }
+    }
+
+    if running.is_empty() {
+        println!("No apps running.");
+        return Ok(());
+    }
+
+    running.sort();
+    let width = running
+        .iter()
+        .map(|(name, _, _)| name.len())
+        .max()
+        .unwrap_or(0)
+        .max("APP".len());
+    println!("{:<width$}  {:>8}  STARTED", "APP", "PID");
+    for (name, pid, started) in &running {
+        println!("{:<width$}  {:>8}  {}", name, pid, started);
+    }
+    println!();
+    println!("Stop an app with: voidbox stop <app-name>");
+
+    Ok(())
+}
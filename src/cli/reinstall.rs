@@ -0,0 +1,75 @@
+//! Reinstall command implementation
+
+use crate::cli::install::{InstallOptions, install_app_from_manifest};
+use crate::cli::update::get_installed_record;
+use crate::manifest::parse_manifest_file;
+use crate::storage::{
+    InstallLock, paths, read_base_info_for_rootfs, remove_dir_all_force, remove_unused_store_entry,
+};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ReinstallError {
+    #[error("App not installed: {0}")]
+    NotInstalled(String),
+
+    #[error("Manifest error: {0}")]
+    ManifestError(#[from] crate::manifest::ManifestError),
+
+    #[error("Install error: {0}")]
+    InstallError(#[from] crate::cli::InstallError),
+
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+}
+
+/// Reinstall an app from its stored manifest
+///
+/// Wipes the app's filesystem layers and installs again, keeping a pinned
+/// version pinned and the architecture it was installed for. App data (home, logs) is kept unless `purge` is set.
+pub fn reinstall_app(app_name: &str, purge: bool) -> Result<(), ReinstallError> {
+    let manifest_path = paths::manifest_path(app_name);
+    if !manifest_path.exists() {
+        return Err(ReinstallError::NotInstalled(app_name.to_string()));
+    }
+
+    // Another install must not see the app half deleted
+    let _lock = InstallLock::acquire()?;
+
+    // Read everything we need before the app directory goes away
+    let manifest = parse_manifest_file(&manifest_path)?;
+    let pinned_version = get_installed_record(app_name).and_then(|a| a.pinned_version);
+    let base_info = read_base_info_for_rootfs(&paths::app_rootfs_dir(app_name))
+        .ok()
+        .flatten();
+
+    crate::info!("Reinstalling {}...", manifest.app.display_name);
+
+    let app_dir = paths::app_dir(app_name);
+    if purge {
+        crate::info!("Removing app data (this may take a moment)...");
+        remove_dir_all_force(&app_dir)?;
+    } else {
+        remove_dir_all_force(&paths::app_rootfs_dir(app_name))?;
+        remove_dir_all_force(&paths::app_layer_dir(app_name))?;
+        remove_dir_all_force(&paths::app_work_dir(app_name))?;
+        crate::info!("Note: App data kept in {}", app_dir.display());
+    }
+
+    install_app_from_manifest(
+        &manifest,
+        &InstallOptions {
+            force: true,
+            version: pinned_version,
+            arch: base_info.as_ref().map(|info| info.arch.clone()),
+            ..Default::default()
+        },
+    )?;
+
+    // A purge also removed the record of the app's old store entry
+    if let Some(store_id) = base_info.and_then(|info| info.store_id).filter(|_| purge) {
+        remove_unused_store_entry(&store_id)?;
+    }
+
+    Ok(())
+}
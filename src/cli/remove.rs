@@ -1,9 +1,15 @@
 //! Remove command implementation
 
-use crate::desktop::{remove_app_wrapper, remove_desktop_entry, remove_icon};
+use crate::desktop::{
+    refresh_desktop_caches, remove_app_wrapper, remove_desktop_entry, remove_icon,
+    unregister_mime_handlers,
+};
 use crate::manifest::InstalledApp;
 use crate::settings::remove_overrides;
-use crate::storage::{paths, read_base_info_for_rootfs, remove_dir_all_force};
+use crate::storage::{
+    BaseInfo, lock_file, paths, read_base_info_for_rootfs, remove_dir_all_force,
+    remove_unused_store_entry, write_atomic,
+};
 use std::fs;
 use thiserror::Error;
 
@@ -23,29 +29,37 @@ pub enum RemoveError {
 pub fn remove_app(app_name: &str, purge: bool) -> Result<(), RemoveError> {
     let app_dir = paths::app_dir(app_name);
     let manifest_path = paths::manifest_path(app_name);
-    let deps_id = app_deps_id(app_name);
+    let base_info = app_base_info(app_name);
 
     if !app_dir.exists() && !manifest_path.exists() {
         return Err(RemoveError::NotInstalled(app_name.to_string()));
     }
 
-    println!("[voidbox] Removing {}...", app_name);
+    crate::info!("Removing {}...", app_name);
+
+    // Drop default-handler associations before the desktop entry goes away
+    if let Err(e) = unregister_mime_handlers(app_name) {
+        crate::warn!("Warning: Could not unregister MIME handlers: {}", e);
+    }
 
     // Remove desktop entry
     if let Err(e) = remove_desktop_entry(app_name) {
-        println!("[voidbox] Warning: Could not remove desktop entry: {}", e);
+        crate::warn!("Warning: Could not remove desktop entry: {}", e);
     }
 
     // Remove wrapper script
     if let Err(e) = remove_app_wrapper(app_name) {
-        println!("[voidbox] Warning: Could not remove wrapper script: {}", e);
+        crate::warn!("Warning: Could not remove wrapper script: {}", e);
     }
 
     // Remove icon
     if let Err(e) = remove_icon(app_name) {
-        println!("[voidbox] Warning: Could not remove icon: {}", e);
+        crate::warn!("Warning: Could not remove icon: {}", e);
     }
 
+    // Drop the app from launcher menus right away
+    refresh_desktop_caches();
+
     // Remove manifest
     if manifest_path.exists() {
         fs::remove_file(&manifest_path)?;
@@ -53,50 +67,49 @@ pub fn remove_app(app_name: &str, purge: bool) -> Result<(), RemoveError> {
 
     // Remove settings
     if let Err(e) = remove_overrides(app_name) {
-        println!("[voidbox] Warning: Could not remove settings: {}", e);
+        crate::warn!("Warning: Could not remove settings: {}", e);
     }
 
     if purge {
         // Remove entire app directory (including data)
         if app_dir.exists() {
-            println!("[voidbox] Removing app data (this may take a moment)...");
+            crate::info!("Removing app data (this may take a moment)...");
             remove_dir_all_force(&app_dir)?;
         }
     } else {
         // Just remove rootfs but keep any app data
         let rootfs = paths::app_rootfs_dir(app_name);
         if rootfs.exists() {
-            println!("[voidbox] Removing rootfs...");
+            crate::info!("Removing rootfs...");
             remove_dir_all_force(&rootfs)?;
         }
-        println!("[voidbox] Note: App data kept in {}", app_dir.display());
-        println!("[voidbox] Use --purge to remove everything.");
+        crate::info!("Note: App data kept in {}", app_dir.display());
+        crate::info!("Use --purge to remove everything.");
     }
 
     // Update installed apps database
     remove_from_database(app_name)?;
 
     if purge {
-        if let Some(deps_id) = deps_id.as_deref() {
+        if let Some(deps_id) = base_info.as_ref().and_then(|info| info.deps_id.as_deref()) {
             remove_unused_deps_layer(deps_id, app_name)?;
         }
+        if let Some(store_id) = base_info.as_ref().and_then(|info| info.store_id.as_deref()) {
+            remove_unused_store_entry(store_id)?;
+        }
     }
 
-    println!("[voidbox] {} removed successfully!", app_name);
+    crate::info!("{} removed successfully!", app_name);
 
     Ok(())
 }
 
-fn app_deps_id(app_name: &str) -> Option<String> {
+fn app_base_info(app_name: &str) -> Option<BaseInfo> {
     let rootfs = paths::app_rootfs_dir(app_name);
     match read_base_info_for_rootfs(&rootfs) {
-        Ok(Some(info)) => info.deps_id,
-        Ok(None) => None,
+        Ok(info) => info,
         Err(e) => {
-            println!(
-                "[voidbox] Warning: Could not read base info for {}: {}",
-                app_name, e
-            );
+            crate::warn!("Warning: Could not read base info for {}: {}", app_name, e);
             None
         }
     }
@@ -128,10 +141,7 @@ fn remove_unused_deps_layer(deps_id: &str, removed_app: &str) -> Result<(), Remo
             }
             Ok(None) => {}
             Err(e) => {
-                println!(
-                    "[voidbox] Warning: Could not read base info for {}: {}",
-                    app_name, e
-                );
+                crate::warn!("Warning: Could not read base info for {}: {}", app_name, e);
                 can_prune = false;
             }
         }
@@ -143,7 +153,7 @@ fn remove_unused_deps_layer(deps_id: &str, removed_app: &str) -> Result<(), Remo
 
     let deps_dir = paths::deps_dir().join(deps_id);
     if deps_dir.exists() {
-        println!("[voidbox] Removing unused shared dependencies...");
+        crate::info!("Removing unused shared dependencies...");
         remove_dir_all_force(&deps_dir)?;
     }
 
@@ -156,6 +166,7 @@ fn remove_from_database(app_name: &str) -> Result<(), RemoveError> {
     if !db_path.exists() {
         return Ok(());
     }
+    let _lock = lock_file(&db_path)?;
 
     let content = fs::read_to_string(&db_path)?;
     let mut apps: Vec<InstalledApp> = serde_json::from_str(&content)
@@ -165,7 +176,7 @@ fn remove_from_database(app_name: &str) -> Result<(), RemoveError> {
 
     let content = serde_json::to_string_pretty(&apps)
         .map_err(|e| RemoveError::Failed(format!("Failed to serialize: {}", e)))?;
-    fs::write(&db_path, content)?;
+    write_atomic(&db_path, content)?;
 
     Ok(())
 }
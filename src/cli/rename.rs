@@ -0,0 +1,183 @@
+//! Rename command implementation
+
+use crate::desktop::{
+    create_app_wrapper, create_desktop_entry, refresh_desktop_caches, register_mime_handlers,
+    remove_app_wrapper, remove_desktop_entry, unregister_mime_handlers,
+};
+use crate::manifest::{AppManifest, InstalledApp, parse_manifest_str, validate_manifest};
+use crate::storage::{InstallLock, lock_file, paths, write_atomic};
+use std::fs;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum RenameError {
+    #[error("App not installed: {0}")]
+    NotInstalled(String),
+
+    #[error("App already exists: {0}")]
+    AlreadyExists(String),
+
+    #[error("Manifest error: {0}")]
+    ManifestError(#[from] crate::manifest::ManifestError),
+
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Rename failed: {0}")]
+    Failed(String),
+}
+
+/// Rename an installed app and/or change its display name
+///
+/// A new internal name moves the app's data, manifest, settings and icons
+/// and replaces its launcher, wrapper and desktop entry. The stored manifest
+/// is edited in place so its comments are kept.
+pub fn rename_app(
+    app_name: &str,
+    new_name: &str,
+    display_name: Option<&str>,
+) -> Result<(), RenameError> {
+    let manifest_path = paths::manifest_path(app_name);
+    if !manifest_path.exists() {
+        return Err(RenameError::NotInstalled(app_name.to_string()));
+    }
+    let renaming = new_name != app_name;
+    if !renaming && display_name.is_none() {
+        return Err(RenameError::Failed(
+            "nothing to change (give a new name or --display-name)".into(),
+        ));
+    }
+    if renaming && (paths::app_dir(new_name).exists() || paths::manifest_path(new_name).exists()) {
+        return Err(RenameError::AlreadyExists(new_name.to_string()));
+    }
+
+    let _lock = InstallLock::acquire()?;
+
+    let mut content = fs::read_to_string(&manifest_path)?;
+    if renaming {
+        content = set_app_field(&content, "name", new_name)?;
+    }
+    if let Some(display_name) = display_name {
+        content = set_app_field(&content, "display_name", display_name)?;
+    }
+    let manifest = parse_manifest_str(&content)?;
+    validate_manifest(&manifest)?;
+
+    if renaming {
+        crate::info!("Renaming {} to {}...", app_name, new_name);
+        remove_launchers(app_name);
+        move_app_files(app_name, new_name)?;
+        fs::remove_file(&manifest_path)?;
+    }
+    write_atomic(&paths::manifest_path(new_name), &content)?;
+    rename_in_database(app_name, &manifest)?;
+
+    if let Err(e) = create_desktop_entry(&manifest) {
+        crate::warn!("Warning: Could not create desktop entry: {}", e);
+    } else if let Err(e) = register_mime_handlers(&manifest) {
+        crate::warn!("Warning: Could not register MIME handlers: {}", e);
+    }
+    if let Err(e) = create_app_wrapper(new_name) {
+        crate::warn!("Warning: Could not create wrapper script: {}", e);
+    }
+    refresh_desktop_caches();
+
+    crate::info!(
+        "{} is now {} ({}).",
+        app_name,
+        manifest.app.display_name,
+        manifest.app.name
+    );
+    Ok(())
+}
+
+/// Replace `key = ...` in the manifest's `[app]` table
+fn set_app_field(content: &str, key: &str, value: &str) -> Result<String, RenameError> {
+    let mut in_app = false;
+    let mut found = false;
+    let mut lines = Vec::new();
+
+    for line in content.lines() {
+        let trimmed = line.trim();
+        if trimmed.starts_with('[') {
+            in_app = trimmed == "[app]";
+        }
+        let line_key = trimmed.split('=').next().unwrap_or_default().trim();
+        if in_app && !found && trimmed.contains('=') && line_key == key {
+            lines.push(format!("{} = {}", key, toml::Value::String(value.into())));
+            found = true;
+        } else {
+            lines.push(line.to_string());
+        }
+    }
+
+    if !found {
+        return Err(RenameError::Failed(format!(
+            "app.{} not found in the stored manifest",
+            key
+        )));
+    }
+    Ok(lines.join("\n") + "\n")
+}
+
+/// Remove everything that launches the app under its old name
+fn remove_launchers(app_name: &str) {
+    if let Err(e) = unregister_mime_handlers(app_name) {
+        crate::warn!("Warning: Could not unregister MIME handlers: {}", e);
+    }
+    if let Err(e) = remove_desktop_entry(app_name) {
+        crate::warn!("Warning: Could not remove desktop entry: {}", e);
+    }
+    if let Err(e) = remove_app_wrapper(app_name) {
+        crate::warn!("Warning: Could not remove wrapper script: {}", e);
+    }
+}
+
+/// Move the app directory, settings and icons to the new name
+fn move_app_files(app_name: &str, new_name: &str) -> Result<(), RenameError> {
+    let moves = [
+        (paths::app_dir(app_name), paths::app_dir(new_name)),
+        (
+            paths::app_settings_path(app_name),
+            paths::app_settings_path(new_name),
+        ),
+        (
+            paths::app_icon_path(app_name),
+            paths::app_icon_path(new_name),
+        ),
+        (
+            paths::app_icon_svg_path(app_name),
+            paths::app_icon_svg_path(new_name),
+        ),
+    ];
+    for (from, to) in moves {
+        if from.exists() {
+            fs::rename(&from, &to)?;
+        }
+    }
+    Ok(())
+}
+
+/// Point the app's database entry at the new name and display name
+fn rename_in_database(app_name: &str, manifest: &AppManifest) -> Result<(), RenameError> {
+    let db_path = paths::database_path();
+    if !db_path.exists() {
+        return Ok(());
+    }
+    let _lock = lock_file(&db_path)?;
+
+    let content = fs::read_to_string(&db_path)?;
+    let mut apps: Vec<InstalledApp> = serde_json::from_str(&content)
+        .map_err(|e| RenameError::Failed(format!("Failed to parse database: {}", e)))?;
+
+    for app in apps.iter_mut().filter(|a| a.name == app_name) {
+        app.name = manifest.app.name.clone();
+        app.display_name = manifest.app.display_name.clone();
+        app.manifest_path = Some(paths::manifest_path(&manifest.app.name));
+    }
+
+    let content = serde_json::to_string_pretty(&apps)
+        .map_err(|e| RenameError::Failed(format!("Failed to serialize: {}", e)))?;
+    write_atomic(&db_path, content)?;
+    Ok(())
+}
@@ -1,15 +1,30 @@
 //! Run command implementation
 
-use crate::manifest::{AppManifest, PermissionConfig, parse_manifest_file};
+use crate::cli::install::{app_install_dir, repair_deps_layer};
+use crate::cli::stop::stop_app;
+use crate::cli::update::get_installed_record;
+use crate::manifest::{
+    AppManifest, BridgeConfig, MountConfig, PermissionConfig, parse_manifest_file,
+};
 use crate::runtime::{
-    setup_container_namespaces, setup_user_namespace, spawn_container_init, start_host_bridge,
+    ContainerConfig, DbusProxy, DbusProxyError, DisplayBackend, HeadlessDisplay, expand_home,
+    forward_to_instance, session_bus_socket, setup_container_namespaces, setup_user_namespace,
+    spawn_container_init, start_host_bridge, wayland_socket_path,
+};
+use crate::settings::{
+    load_bridge_config, load_global_config, load_overrides, merge_permissions, save_overrides,
 };
-use crate::settings::{load_overrides, merge_permissions};
 use crate::storage::paths;
+use nix::sys::signal::{Signal, kill};
 use nix::sys::wait::{WaitStatus, waitpid};
-use nix::unistd::{ForkResult, fork};
-use std::path::Path;
+use nix::unistd::{ForkResult, Pid, fork};
 use std::fs;
+use std::io::IsTerminal;
+use std::net::IpAddr;
+use std::path::{Component, Path, PathBuf};
+use std::process::ExitStatus;
+use std::thread;
+use std::time::Duration;
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -37,15 +52,136 @@ pub enum RunError {
 
     #[error("Bridge error: {0}")]
     BridgeError(#[from] crate::runtime::BridgeError),
+
+    #[error("Install error: {0}")]
+    InstallError(#[from] crate::cli::InstallError),
+
+    #[error("Headless display: {0}")]
+    XvfbError(#[from] crate::runtime::XvfbError),
+
+    #[error("D-Bus proxy: {0}")]
+    DbusProxyError(#[from] crate::runtime::DbusProxyError),
+}
+
+/// Options for a single app launch
+#[derive(Debug, Clone, Default)]
+pub struct RunOptions {
+    /// URL to open (for browsers)
+    pub url: Option<String>,
+    /// Extra arguments appended to the manifest's `binary.args`
+    pub extra_args: Vec<String>,
+    /// Run `args` as a command (program and arguments) instead of passing
+    /// them to the app binary
+    pub exec: bool,
+    /// Enable developer mode (mount host tools)
+    pub dev_mode: bool,
+    /// Run in native mode (host userspace and bridge) whatever the permissions say
+    pub native: bool,
+    /// Environment variables that extend or override the manifest's `[environment]`
+    pub env: Vec<(String, String)>,
+    /// Save the app's output to its log file (always on for GUI launches)
+    pub log: bool,
+    /// Force a display backend instead of the manifest's choice
+    pub display: Option<DisplayBackend>,
+    /// Nameservers that replace the manifest's `runtime.dns`
+    pub dns: Vec<IpAddr>,
+    /// Start with an empty home that is discarded on exit
+    pub isolate_home: bool,
+    /// Stop the app once it has run this long
+    pub time_limit: Option<Duration>,
+    /// Run on a private Xvfb display instead of the session's
+    pub headless: bool,
+    /// One-off bind mounts added after the manifest's `[[mounts]]`
+    pub binds: Vec<MountConfig>,
+    /// Print what would be executed instead of launching the app
+    pub print_command: bool,
+    /// Filter the session bus whatever the permissions say
+    pub dbus_proxy: bool,
 }
 
-/// Run an installed app
+/// Exit code of a launch stopped by `--time-limit` (as with timeout(1))
+pub const TIMEOUT_EXIT_CODE: i32 = 124;
+
+/// How a launch ended
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct RunOutcome {
+    /// Exit status of the app (128 + signal number if it was killed)
+    pub exit_code: i32,
+    /// The app was stopped for exceeding its time limit
+    pub timed_out: bool,
+}
+
+impl RunOutcome {
+    fn from_status(status: ExitStatus) -> Self {
+        use std::os::unix::process::ExitStatusExt;
+        let exit_code = status
+            .code()
+            .or_else(|| status.signal().map(|sig| 128 + sig))
+            .unwrap_or(1);
+        Self {
+            exit_code,
+            ..Default::default()
+        }
+    }
+
+    /// Check whether the app exited successfully
+    pub fn success(&self) -> bool {
+        self.exit_code == 0
+    }
+}
+
+/// Parse a `KEY=VALUE` environment assignment
+pub fn parse_env_assignment(s: &str) -> Result<(String, String), String> {
+    let (key, value) = s
+        .split_once('=')
+        .ok_or_else(|| format!("invalid KEY=VALUE: no '=' found in '{}'", s))?;
+    if key.is_empty() {
+        return Err(format!("invalid KEY=VALUE: empty key in '{}'", s));
+    }
+    Ok((key.to_string(), value.to_string()))
+}
+
+/// Parse a `SRC:DST[:ro]` bind mount (read-write unless `:ro` is given)
+///
+/// `~` and `$HOME` in either path expand to the user's home. The source must
+/// exist and the target is a path in the container without `..`.
+pub fn parse_bind_mount(s: &str) -> Result<MountConfig, String> {
+    let (spec, readonly) = match s.strip_suffix(":ro") {
+        Some(spec) => (spec, true),
+        None => (s.strip_suffix(":rw").unwrap_or(s), false),
+    };
+    let (source, target) = spec
+        .split_once(':')
+        .ok_or_else(|| format!("invalid SRC:DST[:ro]: no ':' found in '{}'", s))?;
+
+    let source = expand_home(source);
+    if !Path::new(&source).exists() {
+        return Err(format!("source does not exist: {}", source));
+    }
+    let target = expand_home(target);
+    let relative = Path::new(target.trim_start_matches('/'));
+    if relative.as_os_str().is_empty() {
+        return Err(format!("cannot bind over the container root: '{}'", s));
+    }
+    if relative.components().any(|c| c == Component::ParentDir) {
+        return Err(format!("target must not contain '..': {}", target));
+    }
+
+    Ok(MountConfig {
+        source,
+        target,
+        readonly,
+    })
+}
+
+/// Run an installed app and wait for it to exit
+///
+/// A launch handed to an already running single-instance app reports success.
 pub fn run_app(
     app_name: &str,
     args: &[String],
-    url: Option<&str>,
-    dev_mode: bool,
-) -> Result<(), RunError> {
+    options: &RunOptions,
+) -> Result<RunOutcome, RunError> {
     // Check if app is installed
     let manifest_path = paths::manifest_path(app_name);
     if !manifest_path.exists() {
@@ -64,29 +200,352 @@ pub fn run_app(
     // Load manifest
     let manifest = parse_manifest_file(&manifest_path)?;
 
-    // Get permissions (manifest defaults + user overrides)
-    let mut permissions = manifest.permissions.clone();
-    if let Some(overrides) = load_overrides(app_name)? {
-        permissions = merge_permissions(&manifest.permissions, Some(&overrides));
+    // Finish a shared dependency layer left incomplete by an interrupted install
+    if !options.print_command {
+        repair_deps_layer(app_name, &manifest)?;
     }
 
+    // Get permissions (manifest defaults + user overrides, asked for on the
+    // first launch if the user opted in)
+    let overrides = match load_overrides(app_name)? {
+        Some(overrides) => Some(overrides),
+        None if !options.print_command && load_global_config()?.prompt_permissions => {
+            prompt_permissions(app_name, &manifest)?
+        }
+        None => None,
+    };
+    let mut permissions = merge_permissions(&manifest.permissions, overrides.as_ref());
+
     // Override dev_mode if specified on command line
-    if dev_mode {
+    if options.dev_mode {
         permissions.dev_mode = true;
     }
+    if options.native {
+        permissions.native_mode = true;
+    }
+    if options.dbus_proxy {
+        permissions.dbus_filter = true;
+    }
+    // Shown once for a denied microphone, and again only after a change
+    let mic_note = paths::app_mic_note_path(app_name);
+    if permissions.audio && !permissions.microphone {
+        if !mic_note.exists() {
+            crate::warn!(
+                "Note: {} can still record through the sound server; deny audio as well to block it.",
+                manifest.app.display_name
+            );
+            let _ = std::fs::write(&mic_note, "");
+        }
+    } else {
+        let _ = std::fs::remove_file(&mic_note);
+    }
+
+    // --x11-only/--wayland-only beat the manifest's wayland toggle; Xvfb
+    // only speaks X11
+    let display = if options.headless {
+        DisplayBackend::X11
+    } else {
+        options.display.unwrap_or(if manifest.runtime.wayland {
+            DisplayBackend::Auto
+        } else {
+            DisplayBackend::X11
+        })
+    };
+    if display == DisplayBackend::Wayland && wayland_socket_path().is_none() {
+        crate::warn!("Warning: No Wayland session found; the app may not open a window.");
+    }
+
+    // Manifest environment, overridden by --env values
+    let mut config = ContainerConfig {
+        env: manifest.environment.clone(),
+        drop_privileges: manifest.runtime.run_as_user,
+        wait_all: manifest.runtime.wait_all,
+        display,
+        seccomp: Some(manifest.security.seccomp),
+        mounts: manifest.mounts.clone(),
+        pid_file: Some(paths::app_pid_path(app_name)),
+        host_libraries: manifest.runtime.extra_packages_from_host.clone(),
+        hostname: manifest.runtime.hostname.clone(),
+        dns: if options.dns.is_empty() {
+            manifest.runtime.dns_servers()
+        } else {
+            options.dns.clone()
+        },
+        ..Default::default()
+    };
+    config.env.extend(options.env.iter().cloned());
+    config.mounts.extend(options.binds.iter().cloned());
+    if options.isolate_home {
+        config.isolate_home = true;
+    } else if manifest.runtime.persist_home {
+        config.persist_home = Some(prepare_app_home(app_name)?);
+    }
+    if let Some(source) = get_installed_record(app_name).and_then(|r| r.linked_source) {
+        let target = Path::new("/opt").join(app_install_dir(&manifest));
+        config.linked_source = Some((source, target));
+    }
+
+    if !config.host_libraries.is_empty() && !permissions.native_mode {
+        crate::warn!(
+            "WARNING: {} uses host libraries ({}). It depends on the host's \
+             versions of them and may break when the host updates.",
+            manifest.app.display_name,
+            config.host_libraries.join(", ")
+        );
+    }
+
+    // Menu launches have no terminal, so their output is only kept in the log
+    let from_menu = crate::gui::launched_from_menu();
+    if options.log || from_menu {
+        config.log_file = Some(paths::app_log_path(app_name));
+    }
 
     // Build command and args
-    let (cmd, cmd_args) = build_command(&manifest, args, url, &rootfs)?;
+    let (cmd, cmd_args) = build_command(&manifest, args, options, &rootfs, display)?;
+    if options.print_command {
+        print_command(&cmd, &cmd_args, &permissions);
+        return Ok(RunOutcome::default());
+    }
+
+    // Single-instance apps hand the launch to the running container if there
+    // is one (a custom command always gets its own container)
+    let instance_socket = paths::instance_socket_path(app_name)
+        .filter(|_| manifest.runtime.single_instance && !options.exec);
+    if let Some(socket) = instance_socket {
+        if forward_to_instance(&socket, &cmd_args)? {
+            crate::info!(
+                "{} is already running - opened in the existing instance.",
+                manifest.app.display_name
+            );
+            return Ok(RunOutcome::default());
+        }
+        config.instance_socket = Some(socket);
+    }
+
+    // Look for app updates in the background; launch doesn't wait for it
+    let update_check = crate::cli::spawn_update_check(app_name, from_menu);
+
+    // Started on the host before the namespaces are entered, and stopped
+    // when this returns
+    let headless = if options.headless {
+        let xvfb = HeadlessDisplay::start()?;
+        crate::info!("Running headless on display {}.", xvfb.display());
+        config.x11_display = Some(xvfb.display().to_string());
+        Some(xvfb)
+    } else {
+        None
+    };
+    let dbus_proxy = if permissions.dbus_filter {
+        start_dbus_proxy(&manifest.app.display_name)?
+    } else {
+        None
+    };
+    config.dbus_proxy = dbus_proxy
+        .as_ref()
+        .map(|proxy| proxy.socket().to_path_buf());
+
+    // Started before a native-mode fork so it stays with the waiting parent
+    let watchdog = options
+        .time_limit
+        .map(|limit| Watchdog::start(app_name, limit))
+        .transpose()?;
 
     // If native_mode, we need to fork BEFORE namespace setup
     // Parent stays on host to run the bridge, child enters namespaces
-    if permissions.native_mode {
-        run_with_host_bridge(&rootfs, &cmd, &cmd_args, &permissions)?;
+    let outcome = if permissions.native_mode {
+        let bridge_config = load_bridge_config()?.unwrap_or_else(|| manifest.bridge.clone());
+        run_with_host_bridge(
+            &rootfs,
+            &cmd,
+            &cmd_args,
+            &permissions,
+            &config,
+            &bridge_config,
+        )?
     } else {
-        run_in_container(&rootfs, &cmd, &cmd_args, &permissions)?;
+        run_in_container(&rootfs, &cmd, &cmd_args, &permissions, &config)?
+    };
+
+    drop(dbus_proxy);
+    drop(headless);
+    if let Some(check) = update_check {
+        check.finish();
     }
+    if watchdog.is_some_and(Watchdog::finish) {
+        return Ok(RunOutcome {
+            exit_code: TIMEOUT_EXIT_CODE,
+            timed_out: true,
+        });
+    }
+    Ok(outcome)
+}
 
-    Ok(())
+/// Start the filtered session bus for `dbus_filter`
+///
+/// Without xdg-dbus-proxy the app gets the raw bus, as it would without the
+/// filter. Without a session bus there is nothing to filter.
+fn start_dbus_proxy(display_name: &str) -> Result<Option<DbusProxy>, RunError> {
+    match DbusProxy::start() {
+        Ok(proxy) => {
+            if session_bus_socket().is_none() {
+                crate::warn!(
+                    "Warning: The session bus has no socket file to hide; {} may still reach it directly.",
+                    display_name
+                );
+            }
+            Ok(Some(proxy))
+        }
+        Err(DbusProxyError::NotFound) => {
+            crate::warn!(
+                "Warning: xdg-dbus-proxy not found - {} gets the unfiltered session bus \
+                 (e.g. sudo apt install xdg-dbus-proxy).",
+                display_name
+            );
+            Ok(None)
+        }
+        Err(DbusProxyError::NoSessionBus) => {
+            crate::debug!("No session bus to filter");
+            Ok(None)
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Stops the container through its pidfile once `--time-limit` runs out
+///
+/// The timer is a forked process, not a thread: the launch goes on to
+/// unshare its user namespace, which fails in a multi-threaded process.
+struct Watchdog {
+    pid: Option<Pid>,
+}
+
+impl Watchdog {
+    fn start(app_name: &str, limit: Duration) -> Result<Self, RunError> {
+        match unsafe { fork() } {
+            Ok(ForkResult::Parent { child }) => Ok(Self { pid: Some(child) }),
+            Ok(ForkResult::Child) => {
+                // Don't outlive a launch that is killed
+                unsafe {
+                    libc::prctl(libc::PR_SET_PDEATHSIG, libc::SIGKILL, 0, 0, 0);
+                }
+                thread::sleep(limit);
+                crate::notice!(
+                    "{} reached its time limit of {}s.",
+                    app_name,
+                    limit.as_secs()
+                );
+                if let Err(e) = stop_app(app_name) {
+                    crate::warn!("Warning: Could not stop {}: {}", app_name, e);
+                }
+                // The forked child must not return into the caller's code
+                std::process::exit(0);
+            }
+            Err(e) => Err(RunError::Failed(format!("Fork failed: {}", e))),
+        }
+    }
+
+    /// Check whether the app was stopped for running too long, once it exited
+    fn finish(mut self) -> bool {
+        self.stop()
+    }
+
+    /// Kill the timer if it is still waiting; true if it had stopped the app
+    fn stop(&mut self) -> bool {
+        let Some(pid) = self.pid.take() else {
+            return false;
+        };
+        let _ = kill(pid, Signal::SIGKILL);
+        matches!(waitpid(pid, None), Ok(WaitStatus::Exited(_, 0)))
+    }
+}
+
+impl Drop for Watchdog {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Ask which of the manifest's sensitive permissions to grant
+///
+/// A summary is shown first; declining it asks about each permission in
+/// turn. The answers are saved as the app's overrides, so this only happens
+/// once. Returns None (manifest defaults, nothing saved) when nobody can be
+/// asked, e.g. a launch from a script, or there is nothing to ask.
+fn prompt_permissions(
+    app_name: &str,
+    manifest: &AppManifest,
+) -> Result<Option<PermissionConfig>, RunError> {
+    // Without a dialog tool the questions are read from stdin
+    if !crate::gui::has_gui_support() && !std::io::stdin().is_terminal() {
+        return Ok(None);
+    }
+
+    let display_name = &manifest.app.display_name;
+    let title = format!("{} permissions", display_name);
+    let mut permissions = manifest.permissions.clone();
+    let requested: Vec<String> = sensitive_permissions(&mut permissions)
+        .into_iter()
+        .filter(|(_, granted)| **granted)
+        .map(|(description, _)| format!("- {}", description))
+        .collect();
+    if requested.is_empty() {
+        return Ok(None);
+    }
+
+    let allow_all = crate::gui::ask_yes_no(
+        &title,
+        &format!(
+            "{} asks to:\n\n{}\n\nAllow all of these?",
+            display_name,
+            requested.join("\n")
+        ),
+    );
+    if !allow_all {
+        for (description, granted) in sensitive_permissions(&mut permissions) {
+            if *granted {
+                *granted = crate::gui::ask_yes_no(
+                    &title,
+                    &format!("Allow {} to {}?", display_name, description),
+                );
+            }
+        }
+    }
+
+    save_overrides(app_name, &permissions)?;
+    crate::info!(
+        "Saved {}'s permissions to {} (delete it to be asked again).",
+        display_name,
+        paths::app_settings_path(app_name).display()
+    );
+    Ok(Some(permissions))
+}
+
+/// Permissions worth asking about, with what they let the app do
+fn sensitive_permissions(permissions: &mut PermissionConfig) -> [(&'static str, &mut bool); 9] {
+    [
+        ("access the network", &mut permissions.network),
+        ("play audio", &mut permissions.audio),
+        ("use the microphone", &mut permissions.microphone),
+        ("use the camera", &mut permissions.camera),
+        ("use the GPU", &mut permissions.gpu),
+        ("read and write your home directory", &mut permissions.home),
+        (
+            "read and write your Downloads folder",
+            &mut permissions.downloads,
+        ),
+        ("access removable media", &mut permissions.removable_media),
+        (
+            "run in native mode (host /usr, /lib and /etc, commands on the host)",
+            &mut permissions.native_mode,
+        ),
+    ]
+}
+
+/// Create the app's persistent home directory if needed
+pub(crate) fn prepare_app_home(app_name: &str) -> std::io::Result<PathBuf> {
+    let home = paths::app_home_dir(app_name);
+    fs::create_dir_all(&home)?;
+    Ok(home)
 }
 
 /// Run app without host bridge (standard container mode)
@@ -95,20 +554,17 @@ fn run_in_container(
     cmd: &str,
     args: &[String],
     permissions: &PermissionConfig,
-) -> Result<(), RunError> {
+    config: &ContainerConfig,
+) -> Result<RunOutcome, RunError> {
     // Setup namespaces
     setup_user_namespace(permissions.native_mode)?;
     setup_container_namespaces()?;
 
     // Spawn container init process with permissions
     let self_exe = std::env::current_exe()?;
-    let status = spawn_container_init(&self_exe, rootfs, cmd, args, permissions)?;
-
-    if !status.success() {
-        std::process::exit(status.code().unwrap_or(1));
-    }
+    let status = spawn_container_init(&self_exe, rootfs, cmd, args, permissions, config)?;
 
-    Ok(())
+    Ok(RunOutcome::from_status(status))
 }
 
 /// Run app with host bridge for native mode
@@ -118,9 +574,11 @@ fn run_with_host_bridge(
     cmd: &str,
     args: &[String],
     permissions: &PermissionConfig,
-) -> Result<(), RunError> {
+    config: &ContainerConfig,
+    bridge_config: &BridgeConfig,
+) -> Result<RunOutcome, RunError> {
     // Start the host bridge BEFORE forking so it's available
-    let bridge_handle = start_host_bridge()?;
+    let bridge_handle = start_host_bridge(bridge_config)?;
     let bridge_port = bridge_handle.port();
 
     // Fork: parent stays on host for bridge, child enters namespaces
@@ -132,21 +590,27 @@ fn run_with_host_bridge(
             loop {
                 match waitpid(child, None) {
                     Ok(WaitStatus::Exited(_, code)) => {
-                        std::process::exit(code);
+                        return Ok(RunOutcome {
+                            exit_code: code,
+                            ..Default::default()
+                        });
                     }
                     Ok(WaitStatus::Signaled(_, sig, _)) => {
                         // Child killed by signal
-                        std::process::exit(128 + sig as i32);
+                        return Ok(RunOutcome {
+                            exit_code: 128 + sig as i32,
+                            ..Default::default()
+                        });
                     }
                     Ok(_) => continue, // Other status, keep waiting
                     Err(nix::errno::Errno::ECHILD) => break, // No more children
                     Err(e) => {
-                        eprintln!("[voidbox] Wait error: {}", e);
+                        crate::warn!("Wait error: {}", e);
                         break;
                     }
                 }
             }
-            Ok(())
+            Ok(RunOutcome::default())
         }
         Ok(ForkResult::Child) => {
             // Child: setup namespaces and run container
@@ -160,21 +624,36 @@ fn run_with_host_bridge(
             setup_container_namespaces()?;
 
             let self_exe = std::env::current_exe()?;
-            let status = spawn_container_init(&self_exe, rootfs, cmd, args, permissions)?;
+            let status = spawn_container_init(&self_exe, rootfs, cmd, args, permissions, config)?;
 
-            std::process::exit(status.code().unwrap_or(1));
+            // The forked child must not return into the caller's code
+            std::process::exit(RunOutcome::from_status(status).exit_code);
         }
         Err(e) => Err(RunError::Failed(format!("Fork failed: {}", e))),
     }
 }
 
-/// Build the command and arguments to run
+/// Command line for a launch
+///
+/// By default the order is `binary.args`, `--arg` values, `args`, then
+/// `binary.url_args` and the URL when one is opened. With `exec`, `args`
+/// replace the app command entirely. The URL always goes last.
 fn build_command(
     manifest: &AppManifest,
     args: &[String],
-    url: Option<&str>,
+    options: &RunOptions,
     rootfs: &Path,
+    display: DisplayBackend,
 ) -> Result<(String, Vec<String>), RunError> {
+    if options.exec {
+        let (cmd, rest) = args
+            .split_first()
+            .ok_or_else(|| RunError::Failed("--exec needs a command after --".into()))?;
+        let mut cmd_args = rest.to_vec();
+        cmd_args.extend(options.url.iter().cloned());
+        return Ok((cmd.clone(), cmd_args));
+    }
+
     // Default app command
     let binary_name = &manifest.binary.name;
 
@@ -184,16 +663,80 @@ fn build_command(
         .unwrap_or_else(|| format!("/usr/bin/{}", binary_name));
 
     let mut cmd_args: Vec<String> = manifest.binary.args.clone();
+    let url_args: &[String] = match options.url {
+        Some(_) => &manifest.binary.url_args,
+        None => &[],
+    };
+
+    // Chromium picks its display backend from flags, not the environment.
+    // Explicit --ozone-platform args in the manifest or command line win.
+    let has_ozone_arg = cmd_args
+        .iter()
+        .chain(&options.extra_args)
+        .chain(args)
+        .chain(url_args)
+        .any(|a| a.starts_with("--ozone-platform"));
+    if manifest.runtime.chromium && !has_ozone_arg {
+        cmd_args.push(ozone_platform_arg(display).to_string());
+    }
+
+    cmd_args.extend(options.extra_args.iter().cloned());
     cmd_args.extend(args.iter().cloned());
 
-    // Add URL if specified (for browsers)
-    if let Some(u) = url {
-        cmd_args.push(u.to_string());
+    // Add URL if specified (for browsers), after its own args
+    if let Some(u) = &options.url {
+        cmd_args.extend(url_args.iter().cloned());
+        cmd_args.push(u.clone());
     }
 
     Ok((cmd, cmd_args))
 }
 
+/// Show the resolved launch for `run --print-command`
+fn print_command(cmd: &str, args: &[String], permissions: &PermissionConfig) {
+    let flags = [
+        ("network", permissions.network),
+        ("audio", permissions.audio),
+        ("microphone", permissions.microphone),
+        ("camera", permissions.camera),
+        ("gpu", permissions.gpu),
+        ("home", permissions.home),
+        ("downloads", permissions.downloads),
+        ("removable_media", permissions.removable_media),
+        ("fonts", permissions.fonts),
+        ("themes", permissions.themes),
+        ("dev_mode", permissions.dev_mode),
+        ("native_mode", permissions.native_mode),
+        ("dbus_filter", permissions.dbus_filter),
+    ];
+    let list = |granted: bool| {
+        let names: Vec<&str> = flags
+            .iter()
+            .filter(|(_, value)| *value == granted)
+            .map(|(name, _)| *name)
+            .collect();
+        if names.is_empty() {
+            "-".to_string()
+        } else {
+            names.join(", ")
+        }
+    };
+
+    println!("Binary:  {}", cmd);
+    println!("Args:    {:?}", args);
+    println!("Granted: {}", list(true));
+    println!("Denied:  {}", list(false));
+}
+
+/// Chromium/Electron flag selecting the display backend
+fn ozone_platform_arg(display: DisplayBackend) -> &'static str {
+    match display {
+        DisplayBackend::Auto => "--ozone-platform-hint=auto",
+        DisplayBackend::X11 => "--ozone-platform=x11",
+        DisplayBackend::Wayland => "--ozone-platform=wayland",
+    }
+}
+
 fn resolve_binary_symlink(rootfs: &Path, binary_name: &str) -> Option<String> {
     let symlink_path = rootfs.join("usr/bin").join(binary_name);
     if std::fs::symlink_metadata(&symlink_path).is_ok() {
@@ -219,10 +762,34 @@ pub fn internal_init(
     cmd: &str,
     args: &[String],
     permissions: &PermissionConfig,
+    config: &ContainerConfig,
 ) -> Result<(), RunError> {
     use crate::runtime::init_and_exec;
 
-    init_and_exec(rootfs, cmd, args, permissions)?;
+    init_and_exec(rootfs, cmd, args, permissions, config)?;
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::process::ExitStatusExt;
+
+    #[test]
+    fn killed_app_reports_128_plus_signal() {
+        // Raw wait statuses: exit code in the high byte, signal in the low bits
+        assert_eq!(
+            RunOutcome::from_status(ExitStatus::from_raw(3 << 8)).exit_code,
+            3
+        );
+        assert_eq!(
+            RunOutcome::from_status(ExitStatus::from_raw(9)).exit_code,
+            137
+        );
+        assert_eq!(
+            RunOutcome::from_status(ExitStatus::from_raw(15)).exit_code,
+            143
+        );
+    }
+}
@@ -0,0 +1,228 @@
+//! Selftest command implementation
+//!
+//! Runs the container runtime end to end against a throwaway overlay on a
+//! base image, timing each step. Like a real launch, the container side runs
+//! in helper processes: `internal-selftest` enters the namespaces (which
+//! can't be left again) and starts `internal-selftest --init` as PID 1 of
+//! the new PID namespace, which mounts, pivots and runs commands inside.
+
+use crate::cli::install::setup_base_image;
+use crate::manifest::{PermissionConfig, RuntimeConfig, host_ubuntu_arch};
+use crate::runtime::{
+    ContainerConfig, pivot_to_container, setup_container_mounts, setup_container_namespaces,
+    setup_user_namespace,
+};
+use crate::storage::{
+    BaseInfo, paths, read_base_info_for_rootfs, remove_dir_all_force, write_base_info_for_dir,
+};
+use std::fmt::Display;
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+use std::time::Instant;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum SelftestError {
+    #[error("Install error: {0}")]
+    InstallError(#[from] crate::cli::InstallError),
+
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Self-test failed: {0}")]
+    Failed(String),
+}
+
+/// Check that containers can be created on this machine
+///
+/// Uses the base image of an installed app if there is one; otherwise the
+/// default Ubuntu base is downloaded first. The scratch layer is removed
+/// afterwards, the base image is kept.
+pub fn self_test() -> Result<(), SelftestError> {
+    let arch = host_ubuntu_arch().ok_or_else(|| {
+        SelftestError::Failed(format!(
+            "unsupported architecture: {}",
+            std::env::consts::ARCH
+        ))
+    })?;
+    paths::ensure_dirs()?;
+
+    println!("[voidbox] Running self-test...");
+    let test_started = Instant::now();
+
+    let started = Instant::now();
+    let runtime = RuntimeConfig::default();
+    let base = match installed_base(arch) {
+        Some(info) => Ok(info),
+        None => setup_base_image(
+            &paths::base_dir(&runtime.base, arch),
+            runtime.ubuntu_version(),
+            arch,
+            None,
+        )
+        .map(|version| BaseInfo {
+            base: runtime.base.clone(),
+            arch: arch.to_string(),
+            version,
+            deps_id: None,
+            store_id: None,
+        }),
+    };
+    let base = report("base image", started, base)
+        .ok_or_else(|| SelftestError::Failed("no usable base image".into()))?;
+    println!(
+        "[voidbox] Using {} ({}, {})",
+        base.base, base.version, base.arch
+    );
+
+    let scratch = paths::data_dir().join(format!("selftest-{}", std::process::id()));
+    let result = run_in_scratch(&scratch, &base);
+
+    let started = Instant::now();
+    report("cleanup", started, remove_dir_all_force(&scratch));
+
+    if !result? {
+        return Err(SelftestError::Failed("see the failed step above".into()));
+    }
+    println!(
+        "[voidbox] Self-test passed in {} ms.",
+        test_started.elapsed().as_millis()
+    );
+    Ok(())
+}
+
+/// Base image of an installed app for `arch`, if any is still present
+fn installed_base(arch: &str) -> Option<BaseInfo> {
+    let entries = fs::read_dir(paths::apps_dir()).ok()?;
+    entries
+        .flatten()
+        .filter_map(|entry| read_base_info_for_rootfs(&entry.path().join("rootfs")).ok()?)
+        .find(|info| info.arch == arch && paths::base_dir(&info.base, &info.arch).exists())
+        .map(|info| BaseInfo {
+            deps_id: None,
+            store_id: None,
+            ..info
+        })
+}
+
+/// Set up the scratch overlay and run the container steps on it
+fn run_in_scratch(scratch: &Path, base: &BaseInfo) -> Result<bool, SelftestError> {
+    let rootfs = scratch.join("rootfs");
+    fs::create_dir_all(&rootfs)?;
+    write_base_info_for_dir(scratch, base)
+        .map_err(|e| SelftestError::Failed(format!("scratch layer: {}", e)))?;
+
+    let status = Command::new(std::env::current_exe()?)
+        .arg("internal-selftest")
+        .arg(&rootfs)
+        .status()?;
+    Ok(status.success())
+}
+
+/// Namespace step of the self-test (`internal-selftest`)
+///
+/// Returns whether this and every later step passed.
+pub fn self_test_namespaces(rootfs: &Path) -> Result<bool, SelftestError> {
+    let started = Instant::now();
+    let namespaces = setup_user_namespace(false).and_then(|()| setup_container_namespaces());
+    if report("namespaces", started, namespaces).is_none() {
+        return Ok(false);
+    }
+
+    // The first child is PID 1 of the new PID namespace
+    let status = Command::new(std::env::current_exe()?)
+        .arg("internal-selftest")
+        .arg(rootfs)
+        .arg("--init")
+        .status()?;
+    Ok(status.success())
+}
+
+/// Container steps of the self-test (`internal-selftest --init`)
+///
+/// Returns whether all of them passed.
+pub fn self_test_container(rootfs: &Path) -> bool {
+    // What an app gets by default
+    let permissions = PermissionConfig::default();
+
+    let started = Instant::now();
+    let config = ContainerConfig::default();
+    let mounts = setup_container_mounts(rootfs, &permissions, &config);
+    if report("mounts", started, mounts).is_none() {
+        return false;
+    }
+
+    let started = Instant::now();
+    if report(
+        "pivot_root",
+        started,
+        pivot_to_container(rootfs, &permissions, &config),
+    )
+    .is_none()
+    {
+        return false;
+    }
+
+    // A fresh /proc shows this process as PID 1
+    let started = Instant::now();
+    let proc = fs::read_to_string("/proc/self/stat").and_then(|stat| {
+        if stat.starts_with("1 ") {
+            Ok(())
+        } else {
+            Err(std::io::Error::other("/proc is not the container's"))
+        }
+    });
+    let proc_ok = report("proc", started, proc).is_some();
+
+    let started = Instant::now();
+    let echo = command_output("echo", &["voidbox"]).and_then(|output| {
+        if output.trim() == "voidbox" {
+            Ok(())
+        } else {
+            Err(format!("unexpected output: {:?}", output))
+        }
+    });
+    let echo_ok = report("echo", started, echo).is_some();
+
+    let started = Instant::now();
+    let os_release = command_output("cat", &["/etc/os-release"]);
+    let os_release_ok = match report("os-release", started, os_release) {
+        Some(content) => {
+            if let Some(name) = content.lines().find_map(|l| l.strip_prefix("PRETTY_NAME=")) {
+                println!("[voidbox]   {}", name.trim_matches('"'));
+            }
+            true
+        }
+        None => false,
+    };
+
+    proc_ok && echo_ok && os_release_ok
+}
+
+/// Run a command in the container and return its stdout
+fn command_output(cmd: &str, args: &[&str]) -> Result<String, String> {
+    let output = Command::new(cmd)
+        .args(args)
+        .output()
+        .map_err(|e| format!("{}: {}", cmd, e))?;
+    if !output.status.success() {
+        return Err(format!("{} exited with {}", cmd, output.status));
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+}
+
+/// Print the outcome and duration of one step
+fn report<T, E: Display>(step: &str, started: Instant, result: Result<T, E>) -> Option<T> {
+    let ms = started.elapsed().as_millis();
+    match result {
+        Ok(value) => {
+            println!("[voidbox] {:<12} ok ({} ms)", step, ms);
+            Some(value)
+        }
+        Err(e) => {
+            println!("[voidbox] {:<12} FAILED ({} ms): {}", step, ms, e);
+            None
+        }
+    }
+}
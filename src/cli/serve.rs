@@ -0,0 +1,260 @@
+//! Serve command implementation - control socket for GUI frontends
+//!
+//! Frontends connect to a Unix socket and send one JSON request per line:
+//!
+//! ```text
+//! {"id": 1, "method": "install", "params": {"source": "/path/to/app.toml"}}
+//! {"id": 2, "method": "list"}
+//! {"id": 3, "method": "remove", "params": {"app": "brave", "purge": false}}
+//! {"id": 4, "method": "cancel"}
+//! ```
+//!
+//! Each request gets one reply line, `{"id": 1, "result": ...}` or
+//! `{"id": 1, "error": "..."}`. Installs run in the background and report
+//! `{"id": 1, "event": "progress", "fraction": 0.4, "message": "..."}` lines
+//! on the connection that started them until their reply is sent. Only one
+//! install or remove runs at a time; `cancel` stops an install like Ctrl-C
+//! would.
+
+use crate::cli::{InstallOptions, get_installed_apps, install_app, remove_app};
+use crate::storage::{clear_interrupt, paths, request_interrupt};
+use serde::Deserialize;
+use serde_json::{Value, json};
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::os::unix::fs::PermissionsExt;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::sync::mpsc::channel;
+use std::sync::{Arc, Mutex};
+use std::{fs, thread};
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ServeError {
+    #[error("Another voidbox is already serving on {0}")]
+    AlreadyRunning(String),
+
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+}
+
+/// A request line from a frontend
+#[derive(Deserialize)]
+struct Request {
+    #[serde(default)]
+    id: Value,
+    #[serde(flatten)]
+    method: Method,
+}
+
+#[derive(Deserialize)]
+#[serde(tag = "method", content = "params", rename_all = "lowercase")]
+enum Method {
+    Install {
+        source: String,
+        #[serde(default)]
+        force: bool,
+        #[serde(default)]
+        version: Option<String>,
+    },
+    List,
+    Remove {
+        app: String,
+        #[serde(default)]
+        purge: bool,
+    },
+    Cancel,
+}
+
+/// Write side of a connection, shared with its install's progress thread
+type Replies = Arc<Mutex<UnixStream>>;
+
+/// Operation the server is busy with
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Operation {
+    Install,
+    Remove,
+}
+
+/// Operation running right now, if any
+type BusyState = Arc<Mutex<Option<Operation>>>;
+
+/// Marks the server busy with an install or remove until dropped, so a
+/// panicking install can't leave it busy for good
+struct Busy(BusyState);
+
+impl Busy {
+    fn acquire(state: &BusyState, operation: Operation) -> Option<Self> {
+        let mut current = state.lock().unwrap_or_else(|e| e.into_inner());
+        if current.is_some() {
+            return None;
+        }
+        *current = Some(operation);
+        Some(Self(Arc::clone(state)))
+    }
+}
+
+impl Drop for Busy {
+    fn drop(&mut self) {
+        *self.0.lock().unwrap_or_else(|e| e.into_inner()) = None;
+    }
+}
+
+/// Listen for frontend requests on `socket` (default: `paths::control_socket_path`)
+///
+/// Runs until the process is killed. The socket is only accessible to the
+/// current user.
+pub fn serve(socket: Option<&Path>) -> Result<(), ServeError> {
+    let socket = socket.map_or_else(paths::control_socket_path, Path::to_path_buf);
+    match UnixStream::connect(&socket) {
+        Ok(_) => return Err(ServeError::AlreadyRunning(socket.display().to_string())),
+        Err(e) if matches!(e.kind(), ErrorKind::NotFound) => {}
+        // Left behind by a server that did not exit cleanly
+        Err(_) => fs::remove_file(&socket)?,
+    }
+
+    paths::ensure_dirs()?;
+    let listener = UnixListener::bind(&socket)?;
+    fs::set_permissions(&socket, fs::Permissions::from_mode(0o600))?;
+    crate::info!("Serving on {}", socket.display());
+
+    let busy = BusyState::default();
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                crate::warn!("Warning: Failed to accept connection: {}", e);
+                continue;
+            }
+        };
+        let busy = Arc::clone(&busy);
+        thread::spawn(move || {
+            if let Err(e) = handle_connection(stream, &busy) {
+                crate::debug!("Control connection closed: {}", e);
+            }
+        });
+    }
+    Ok(())
+}
+
+fn handle_connection(stream: UnixStream, busy: &BusyState) -> std::io::Result<()> {
+    let replies: Replies = Arc::new(Mutex::new(stream.try_clone()?));
+    for line in BufReader::new(stream).lines() {
+        let line = line?;
+        if line.trim().is_empty() {
+            continue;
+        }
+        let request = match serde_json::from_str::<Request>(&line) {
+            Ok(request) => request,
+            Err(e) => {
+                send(
+                    &replies,
+                    json!({ "id": null, "error": format!("bad request: {}", e) }),
+                );
+                continue;
+            }
+        };
+
+        let id = request.id;
+        let reply = match request.method {
+            Method::Install {
+                source,
+                force,
+                version,
+            } => match Busy::acquire(busy, Operation::Install) {
+                Some(guard) => {
+                    start_install(id.clone(), source, force, version, &replies, guard);
+                    // Replied to when the install finishes
+                    continue;
+                }
+                None => Err("another install or remove is running".to_string()),
+            },
+            Method::List => get_installed_apps()
+                .map_err(|e| e.to_string())
+                .and_then(|apps| serde_json::to_value(apps).map_err(|e| e.to_string())),
+            Method::Remove { app, purge } => match Busy::acquire(busy, Operation::Remove) {
+                Some(_guard) => remove_app(&app, purge)
+                    .map(|()| Value::Null)
+                    .map_err(|e| e.to_string()),
+                None => Err("an install or remove is running".to_string()),
+            },
+            // A remove is not interruptible; cancelling it could leave the
+            // app half deleted
+            Method::Cancel => {
+                let running = *busy.lock().unwrap_or_else(|e| e.into_inner());
+                if running == Some(Operation::Install) {
+                    request_interrupt();
+                    Ok(Value::Null)
+                } else {
+                    Err("no install is running".to_string())
+                }
+            }
+        };
+        send(&replies, response(id, reply));
+    }
+    Ok(())
+}
+
+/// Run an install on a worker thread, streaming its progress to `replies`
+fn start_install(
+    id: Value,
+    source: String,
+    force: bool,
+    version: Option<String>,
+    replies: &Replies,
+    busy: Busy,
+) {
+    // A cancel of the previous install must not carry over
+    clear_interrupt();
+    let replies = Arc::clone(replies);
+    thread::spawn(move || {
+        let (sender, receiver) = channel::<(f32, String)>();
+        let events = {
+            let replies = Arc::clone(&replies);
+            let id = id.clone();
+            thread::spawn(move || {
+                for (fraction, message) in receiver {
+                    send(
+                        &replies,
+                        json!({
+                            "id": id,
+                            "event": "progress",
+                            "fraction": fraction,
+                            "message": message,
+                        }),
+                    );
+                }
+            })
+        };
+
+        let options = InstallOptions {
+            force,
+            version,
+            progress: Some(sender),
+            ..Default::default()
+        };
+        let result = install_app(&source, &options);
+
+        // Flush the remaining progress events before the reply
+        drop(options);
+        events.join().ok();
+        drop(busy);
+        send(
+            &replies,
+            response(id, result.map(|()| Value::Null).map_err(|e| e.to_string())),
+        );
+    });
+}
+
+fn response(id: Value, result: Result<Value, String>) -> Value {
+    match result {
+        Ok(result) => json!({ "id": id, "result": result }),
+        Err(error) => json!({ "id": id, "error": error }),
+    }
+}
+
+/// Write one line to the frontend; a vanished frontend is not an error
+fn send(replies: &Replies, message: Value) {
+    let mut stream = replies.lock().unwrap_or_else(|e| e.into_inner());
+    let _ = writeln!(stream, "{}", message);
+}
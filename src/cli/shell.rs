@@ -1,9 +1,12 @@
 //! Shell command implementation
 
-use crate::manifest::{PermissionConfig, parse_manifest_file};
+use crate::cli::run::prepare_app_home;
+use crate::manifest::{BridgeConfig, PermissionConfig, parse_manifest_file};
 use crate::runtime::{
-    setup_container_namespaces, setup_user_namespace, spawn_container_init, start_host_bridge,
+    ContainerConfig, setup_container_namespaces, setup_user_namespace, spawn_container_init,
+    start_host_bridge,
 };
+use crate::settings::load_bridge_config;
 use crate::storage::paths;
 use nix::sys::wait::{WaitStatus, waitpid};
 use nix::unistd::{ForkResult, fork};
@@ -21,6 +24,9 @@ pub enum ShellError {
     #[error("Namespace error: {0}")]
     NamespaceError(#[from] crate::runtime::NamespaceError),
 
+    #[error("Settings error: {0}")]
+    SettingsError(#[from] crate::settings::SettingsError),
+
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
 
@@ -32,7 +38,15 @@ pub enum ShellError {
 }
 
 /// Open a shell in an app's container
-pub fn shell(app_name: &str, dev_mode: bool) -> Result<(), ShellError> {
+///
+/// With `as_user`, the shell runs as an unprivileged user instead of
+/// container root, to reproduce how the app behaves as a normal user.
+pub fn shell(
+    app_name: &str,
+    dev_mode: bool,
+    as_user: bool,
+    native: bool,
+) -> Result<(), ShellError> {
     let manifest_path = paths::manifest_path(app_name);
     if !manifest_path.exists() {
         return Err(ShellError::NotInstalled(app_name.to_string()));
@@ -53,19 +67,42 @@ pub fn shell(app_name: &str, dev_mode: bool) -> Result<(), ShellError> {
 
     // Always enable dev_mode for shell access (or if explicitly requested)
     permissions.dev_mode = dev_mode || true;
+    if native {
+        permissions.native_mode = true;
+    }
 
-    println!("[voidbox] Opening shell in {} container...", app_name);
-    println!("[voidbox] Type 'exit' to leave the container.");
-    println!();
+    crate::info!("Opening shell in {} container...", app_name);
+    crate::info!("Type 'exit' to leave the container.");
 
     let shell = "/bin/bash".to_string();
     let args: Vec<String> = vec![];
+    let config = ContainerConfig {
+        env: manifest.environment.clone(),
+        persist_home: if manifest.runtime.persist_home {
+            Some(prepare_app_home(app_name)?)
+        } else {
+            None
+        },
+        drop_privileges: as_user,
+        dns: manifest.runtime.dns_servers(),
+        host_libraries: manifest.runtime.extra_packages_from_host.clone(),
+        hostname: manifest.runtime.hostname.clone(),
+        ..Default::default()
+    };
 
     // If native_mode, use host bridge
     if permissions.native_mode {
-        shell_with_host_bridge(&rootfs, &shell, &args, &permissions)?;
+        let bridge_config = load_bridge_config()?.unwrap_or_else(|| manifest.bridge.clone());
+        shell_with_host_bridge(
+            &rootfs,
+            &shell,
+            &args,
+            &permissions,
+            &config,
+            &bridge_config,
+        )?;
     } else {
-        shell_in_container(&rootfs, &shell, &args, &permissions)?;
+        shell_in_container(&rootfs, &shell, &args, &permissions, &config)?;
     }
 
     Ok(())
@@ -77,12 +114,13 @@ fn shell_in_container(
     shell: &str,
     args: &[String],
     permissions: &PermissionConfig,
+    config: &ContainerConfig,
 ) -> Result<(), ShellError> {
     setup_user_namespace(permissions.native_mode)?;
     setup_container_namespaces()?;
 
     let self_exe = std::env::current_exe()?;
-    let status = spawn_container_init(&self_exe, rootfs, shell, args, permissions)
+    let status = spawn_container_init(&self_exe, rootfs, shell, args, permissions, config)
         .map_err(|e| ShellError::Failed(e.to_string()))?;
 
     if !status.success() {
@@ -98,9 +136,11 @@ fn shell_with_host_bridge(
     shell: &str,
     args: &[String],
     permissions: &PermissionConfig,
+    config: &ContainerConfig,
+    bridge_config: &BridgeConfig,
 ) -> Result<(), ShellError> {
     // Start the host bridge BEFORE forking
-    let bridge_handle = start_host_bridge()?;
+    let bridge_handle = start_host_bridge(bridge_config)?;
     let bridge_port = bridge_handle.port();
 
     match unsafe { fork() } {
@@ -117,7 +157,7 @@ fn shell_with_host_bridge(
                     Ok(_) => continue,
                     Err(nix::errno::Errno::ECHILD) => break,
                     Err(e) => {
-                        eprintln!("[voidbox] Wait error: {}", e);
+                        crate::warn!("Wait error: {}", e);
                         break;
                     }
                 }
@@ -134,7 +174,7 @@ fn shell_with_host_bridge(
             setup_container_namespaces()?;
 
             let self_exe = std::env::current_exe()?;
-            let status = spawn_container_init(&self_exe, rootfs, shell, args, permissions)
+            let status = spawn_container_init(&self_exe, rootfs, shell, args, permissions, config)
                 .map_err(|e| ShellError::Failed(e.to_string()))?;
 
             std::process::exit(status.code().unwrap_or(1));
@@ -0,0 +1,102 @@
+//! Stop command implementation
+
+use crate::storage::paths;
+use nix::errno::Errno;
+use nix::sys::signal::{Signal, kill};
+use nix::unistd::Pid;
+use std::fs;
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, Instant};
+use thiserror::Error;
+
+/// How long the app gets to exit after SIGTERM before it is killed
+const STOP_GRACE_PERIOD: Duration = Duration::from_secs(5);
+
+#[derive(Error, Debug)]
+pub enum StopError {
+    #[error("App not installed: {0}")]
+    NotInstalled(String),
+
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+
+    #[error("Stop failed: {0}")]
+    Failed(String),
+}
+
+/// Stop a running app by terminating its container
+///
+/// The PID in `run.pid` is the init of the container's PID namespace, so
+/// once it exits the kernel kills everything else the app started.
+pub fn stop_app(app_name: &str) -> Result<(), StopError> {
+    if !paths::app_dir(app_name).exists() {
+        return Err(StopError::NotInstalled(app_name.to_string()));
+    }
+
+    let pid_path = paths::app_pid_path(app_name);
+    let Some(pid) = read_container_pid(&pid_path) else {
+        crate::info!("{} is not running.", app_name);
+        return Ok(());
+    };
+    if !is_container_init(pid) {
+        // Left behind by a crashed launch, or the PID now belongs to
+        // something else
+        fs::remove_file(&pid_path).ok();
+        crate::info!("{} is not running.", app_name);
+        return Ok(());
+    }
+
+    crate::info!("Stopping {}...", app_name);
+    send_signal(pid, Signal::SIGTERM)?;
+
+    let deadline = Instant::now() + STOP_GRACE_PERIOD;
+    while is_container_init(pid) && Instant::now() < deadline {
+        thread::sleep(Duration::from_millis(100));
+    }
+    if is_container_init(pid) {
+        crate::info!("{} did not exit, killing it.", app_name);
+        send_signal(pid, Signal::SIGKILL)?;
+    }
+
+    fs::remove_file(&pid_path).ok();
+    crate::info!("{} stopped.", app_name);
+    Ok(())
+}
+
+/// PID recorded in an app's `run.pid`
+pub(crate) fn read_container_pid(pid_path: &Path) -> Option<Pid> {
+    let content = fs::read_to_string(pid_path).ok()?;
+    content.trim().parse().ok().map(Pid::from_raw)
+}
+
+/// Check that `pid` is a live voidbox container init (not a zombie or a
+/// reused PID)
+pub(crate) fn is_container_init(pid: Pid) -> bool {
+    // Signal 0 only checks that the process exists and can be signalled
+    if kill(pid, None).is_err() {
+        return false;
+    }
+    let proc_dir = format!("/proc/{}", pid);
+    let is_init = fs::read(format!("{}/cmdline", proc_dir)).is_ok_and(|cmdline| {
+        cmdline
+            .split(|b| *b == 0)
+            .any(|arg| arg == b"internal-init")
+    });
+    // The state follows the parenthesized command name in /proc/<pid>/stat
+    let is_zombie = fs::read_to_string(format!("{}/stat", proc_dir))
+        .ok()
+        .and_then(|stat| {
+            stat.rsplit_once(") ")
+                .map(|(_, rest)| rest.starts_with('Z'))
+        })
+        .unwrap_or(true);
+    is_init && !is_zombie
+}
+
+fn send_signal(pid: Pid, signal: Signal) -> Result<(), StopError> {
+    match kill(pid, signal) {
+        Ok(()) | Err(Errno::ESRCH) => Ok(()),
+        Err(e) => Err(StopError::Failed(format!("{} {}: {}", signal, pid, e))),
+    }
+}
@@ -1,14 +1,19 @@
 //! Update command implementation
 
-use crate::cli::install::install_app;
+use crate::cli::install::{
+    InstallOptions, gitlab_releases_url, install_app, rebuild_app_deps, version_at_json_path,
+};
 use crate::manifest::{InstalledApp, SourceConfig, parse_manifest_file};
-use crate::storage::{paths, download_string, BaseInfo};
+use crate::settings::load_global_config;
+use crate::storage::{
+    paths, download_string, http_get, is_offline, read_base_info_for_rootfs, InstallLock,
+};
 use serde::Deserialize;
 use serde_json::Value;
 use std::collections::HashSet;
 use std::fs;
-use std::path::Path;
-use std::process::{Command, Stdio};
+use std::io::Read;
+use std::process::{Child, Command, Stdio};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -30,6 +35,9 @@ pub enum UpdateError {
 
     #[error("Update failed: {0}")]
     Failed(String),
+
+    #[error("Network error: {0}")]
+    NetworkError(String),
 }
 
 #[derive(Debug, Clone, Copy)]
@@ -45,6 +53,11 @@ struct GitHubRelease {
     tag_name: String,
 }
 
+#[derive(Deserialize)]
+struct GitLabRelease {
+    tag_name: String,
+}
+
 /// Get latest version from GitHub
 fn get_latest_github_version(owner: &str, repo: &str) -> Result<String, UpdateError> {
     let api_url = format!(
@@ -52,10 +65,9 @@ fn get_latest_github_version(owner: &str, repo: &str) -> Result<String, UpdateEr
         owner, repo
     );
 
-    let mut resp = ureq::get(&api_url)
-        .header("User-Agent", crate::APP_NAME)
+    let mut resp = http_get(&api_url)
         .call()
-        .map_err(|e| UpdateError::Failed(format!("GitHub API error: {}", e)))?;
+        .map_err(|e| UpdateError::NetworkError(format!("GitHub API error: {}", e)))?;
 
     let body = resp
         .body_mut()
@@ -68,9 +80,45 @@ fn get_latest_github_version(owner: &str, repo: &str) -> Result<String, UpdateEr
     Ok(release.tag_name.trim_start_matches('v').to_string())
 }
 
-fn get_latest_direct_version(version_url: &str) -> Result<Option<String>, UpdateError> {
+/// Get latest version from GitLab (releases are returned newest first)
+fn get_latest_gitlab_version(project_id: &str) -> Result<String, UpdateError> {
+    let api_url = gitlab_releases_url(project_id);
+
+    let mut resp = http_get(&api_url)
+        .call()
+        .map_err(|e| UpdateError::NetworkError(format!("GitLab API error: {}", e)))?;
+
+    let body = resp
+        .body_mut()
+        .read_to_string()
+        .map_err(|e| UpdateError::Failed(format!("Failed to read response: {}", e)))?;
+
+    latest_gitlab_version(&body, project_id)
+}
+
+fn latest_gitlab_version(body: &str, project_id: &str) -> Result<String, UpdateError> {
+    let releases: Vec<GitLabRelease> = serde_json::from_str(body)
+        .map_err(|e| UpdateError::Failed(format!("Failed to parse GitLab response: {}", e)))?;
+
+    releases
+        .first()
+        .map(|release| release.tag_name.trim_start_matches('v').to_string())
+        .ok_or_else(|| {
+            UpdateError::Failed(format!(
+                "No releases found for GitLab project {}",
+                project_id
+            ))
+        })
+}
+
+fn get_latest_direct_version(
+    version_url: &str,
+    json_path: Option<&str>,
+) -> Result<Option<String>, UpdateError> {
     let content = download_string(version_url)?;
-    Ok(parse_version_response(&content))
+    Ok(json_path
+        .and_then(|path| version_at_json_path(&content, path))
+        .or_else(|| parse_version_response(&content)))
 }
 
 fn parse_version_response(content: &str) -> Option<String> {
@@ -111,7 +159,7 @@ fn parse_version_response(content: &str) -> Option<String> {
 }
 
 /// Get installed version of an app
-fn get_installed_version(app_name: &str) -> Option<String> {
+pub(crate) fn get_installed_record(app_name: &str) -> Option<InstalledApp> {
     let db_path = paths::database_path();
     if !db_path.exists() {
         return None;
@@ -120,9 +168,7 @@ fn get_installed_version(app_name: &str) -> Option<String> {
     let content = fs::read_to_string(&db_path).ok()?;
     let apps: Vec<InstalledApp> = serde_json::from_str(&content).ok()?;
 
-    apps.into_iter()
-        .find(|a| a.name == app_name)
-        .and_then(|a| a.version)
+    apps.into_iter().find(|a| a.name == app_name)
 }
 
 /// Compare versions (returns true if latest > installed)
@@ -140,7 +186,14 @@ fn is_newer_version(installed: &str, latest: &str) -> bool {
 }
 
 /// Update a specific app
-pub fn update_app(app_name: &str, force: bool) -> Result<UpdateOutcome, UpdateError> {
+///
+/// With `rebuild_deps`, the app's dependency layers are rebuilt from its
+/// manifest first, whether or not a newer release is available.
+pub fn update_app(
+    app_name: &str,
+    force: bool,
+    rebuild_deps: bool,
+) -> Result<UpdateOutcome, UpdateError> {
     let manifest_path = paths::manifest_path(app_name);
 
     if !manifest_path.exists() {
@@ -151,14 +204,52 @@ pub fn update_app(app_name: &str, force: bool) -> Result<UpdateOutcome, UpdateEr
     let manifest = parse_manifest_file(&manifest_path)?;
     let display_name = &manifest.app.display_name;
 
+    if is_offline() {
+        crate::info!("{} - offline mode, skipping update check", display_name);
+        return Ok(UpdateOutcome::Skipped);
+    }
+
+    let installed = get_installed_record(app_name);
+    if installed.as_ref().is_some_and(|a| a.frozen) {
+        crate::info!(
+            "{} is frozen - skipping (run `voidbox unfreeze {}` to update it)",
+            display_name,
+            app_name
+        );
+        return Ok(UpdateOutcome::Skipped);
+    }
+
+    if rebuild_deps {
+        rebuild_app_deps(app_name, &manifest)?;
+    }
+
+    let pinned_version = installed.as_ref().and_then(|a| a.pinned_version.clone());
+
+    // Pinned apps stay on their version; --force reinstalls the pinned version
+    if let Some(pinned) = pinned_version.as_deref().filter(|_| !force) {
+        crate::info!(
+            "{} is pinned to v{} - skipping (reinstall without --version to unpin)",
+            display_name,
+            pinned
+        );
+        return Ok(UpdateOutcome::Skipped);
+    }
+
     // Get installed version
-    let installed_version = get_installed_version(app_name).or_else(|| manifest.app.version.clone());
+    let installed_version = installed
+        .and_then(|a| a.version)
+        .or_else(|| manifest.app.version.clone());
 
     // Check for updates based on source type
     let latest_version = match &manifest.source {
         SourceConfig::Github { owner, repo, .. } => Some(get_latest_github_version(owner, repo)?),
-        SourceConfig::Direct { version_url, .. } => match version_url.as_deref() {
-            Some(url) => get_latest_direct_version(url)?,
+        SourceConfig::Gitlab { project_id, .. } => Some(get_latest_gitlab_version(project_id)?),
+        SourceConfig::Direct {
+            version_url,
+            version_json_path,
+            ..
+        } => match version_url.as_deref() {
+            Some(url) => get_latest_direct_version(url, version_json_path.as_deref())?,
             None => None,
         },
         SourceConfig::Local { .. } => None,
@@ -167,89 +258,200 @@ pub fn update_app(app_name: &str, force: bool) -> Result<UpdateOutcome, UpdateEr
     // Compare versions
     if !force {
         match &manifest.source {
-            SourceConfig::Github { .. } => {
+            SourceConfig::Github { .. } | SourceConfig::Gitlab { .. } => {
                 let Some(latest) = latest_version.as_deref() else {
-                    println!(
-                        "[voidbox] {} - cannot check for updates right now",
-                        display_name
-                    );
+                    crate::info!("{} - cannot check for updates right now", display_name);
                     return Ok(UpdateOutcome::Unknown);
                 };
                 let Some(installed) = installed_version.as_deref() else {
-                    println!(
-                        "[voidbox] {} - cannot determine installed version (use --force to update)",
+                    crate::info!(
+                        "{} - cannot determine installed version (use --force to update)",
                         display_name
                     );
                     return Ok(UpdateOutcome::Unknown);
                 };
                 if !is_newer_version(installed, latest) {
-                    println!("[voidbox] {} is up to date (v{})", display_name, installed);
+                    crate::info!("{} is up to date (v{})", display_name, installed);
                     return Ok(UpdateOutcome::UpToDate);
                 }
-                println!(
-                    "[voidbox] {} update available: v{} -> v{}",
-                    display_name, installed, latest
+                crate::info!(
+                    "{} update available: v{} -> v{}",
+                    display_name,
+                    installed,
+                    latest
                 );
             }
             SourceConfig::Direct { version_url, .. } => match version_url {
                 Some(_) => {
                     let Some(latest) = latest_version.as_deref() else {
-                        println!(
-                            "[voidbox] {} - cannot check for updates right now",
-                            display_name
-                        );
+                        crate::info!("{} - cannot check for updates right now", display_name);
                         return Ok(UpdateOutcome::Unknown);
                     };
                     let Some(installed) = installed_version.as_deref() else {
-                        println!(
-                            "[voidbox] {} - cannot determine installed version (use --force to update)",
+                        crate::info!(
+                            "{} - cannot determine installed version (use --force to update)",
                             display_name
                         );
                         return Ok(UpdateOutcome::Unknown);
                     };
                     if !is_newer_version(installed, latest) {
-                        println!("[voidbox] {} is up to date (v{})", display_name, installed);
+                        crate::info!("{} is up to date (v{})", display_name, installed);
                         return Ok(UpdateOutcome::UpToDate);
                     }
-                    println!(
-                        "[voidbox] {} update available: v{} -> v{}",
-                        display_name, installed, latest
+                    crate::info!(
+                        "{} update available: v{} -> v{}",
+                        display_name,
+                        installed,
+                        latest
                     );
                 }
                 None => {
-                    println!(
-                        "[voidbox] {} - cannot check for updates (direct source)",
+                    crate::info!(
+                        "{} - cannot check for updates (direct source)",
                         display_name
                     );
                     return Ok(UpdateOutcome::Skipped);
                 }
             },
             SourceConfig::Local { .. } => {
-                println!(
-                    "[voidbox] {} - cannot check for updates (local source)",
-                    display_name
-                );
+                crate::info!("{} - cannot check for updates (local source)", display_name);
                 return Ok(UpdateOutcome::Skipped);
             }
         }
     }
 
-    println!("[voidbox] Updating {}...", display_name);
+    crate::info!("Updating {}...", display_name);
 
     // Reinstall the app (force=true to overwrite)
-    install_app(manifest_path.to_str().unwrap(), true)?;
+    install_app(
+        manifest_path.to_str().unwrap(),
+        &InstallOptions {
+            force: true,
+            version: pinned_version,
+            ..Default::default()
+        },
+    )?;
 
     Ok(UpdateOutcome::Updated)
 }
 
-/// Read base info from a base.json file
-fn read_base_json(path: &Path) -> Option<BaseInfo> {
-    let content = fs::read_to_string(path).ok()?;
-    serde_json::from_str(&content).ok()
+/// Print a notice if a newer release of an app is available
+///
+/// Only GitHub/GitLab sources are checked and nothing is installed; updates
+/// only land through `voidbox update`. Used by the background check started
+/// at launch, which also shows a desktop notification (`desktop_notify`) for
+/// launches from the app menu, where stderr goes nowhere.
+pub fn print_update_notice(app_name: &str, desktop_notify: bool) -> Result<(), UpdateError> {
+    let manifest = parse_manifest_file(&paths::manifest_path(app_name))?;
+    let installed = get_installed_record(app_name);
+    if installed
+        .as_ref()
+        .is_some_and(|a| a.pinned_version.is_some() || a.frozen)
+    {
+        return Ok(());
+    }
+
+    let Some(installed_version) = installed
+        .and_then(|a| a.version)
+        .or_else(|| manifest.app.version.clone())
+    else {
+        return Ok(());
+    };
+
+    let latest = match &manifest.source {
+        SourceConfig::Github { owner, repo, .. } => get_latest_github_version(owner, repo)?,
+        SourceConfig::Gitlab { project_id, .. } => get_latest_gitlab_version(project_id)?,
+        SourceConfig::Direct { .. } | SourceConfig::Local { .. } => return Ok(()),
+    };
+
+    if is_newer_version(&installed_version, &latest) {
+        let display_name = &manifest.app.display_name;
+        crate::notice!(
+            "{} update available: v{} -> v{} (run `voidbox update {}`)",
+            display_name,
+            installed_version,
+            latest,
+            app_name
+        );
+        if desktop_notify {
+            crate::gui::notify(
+                &format!("{} update available", display_name),
+                &format!(
+                    "v{} is out (you have v{}). Run `voidbox update {}` to install it.",
+                    latest, installed_version, app_name
+                ),
+            );
+        }
+    }
+
+    Ok(())
+}
+
+/// Update check running in the background during a launch
+///
+/// Its stderr notice is held back until the app exits, so it doesn't land in
+/// the middle of the app's own terminal output.
+pub struct UpdateCheck {
+    child: Child,
+}
+
+impl UpdateCheck {
+    /// Print the notice of a finished check; one still running is stopped
+    pub fn finish(mut self) {
+        if !matches!(self.child.try_wait(), Ok(Some(_))) {
+            return;
+        }
+        let mut notice = String::new();
+        if let Some(mut stderr) = self.child.stderr.take() {
+            let _ = stderr.read_to_string(&mut notice);
+        }
+        eprint!("{}", notice);
+    }
+}
+
+impl Drop for UpdateCheck {
+    fn drop(&mut self) {
+        // Reaped here either way, so it never lingers as a zombie
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+/// Check for an app update in a separate process
+///
+/// A separate process is used rather than a thread because the launch path
+/// unshares namespaces, which requires a single-threaded process. The launch
+/// never waits on it; if the check is slow the notice simply shows next time.
+/// `from_menu` launches get a desktop notification instead of the stderr line.
+pub fn spawn_update_check(app_name: &str, from_menu: bool) -> Option<UpdateCheck> {
+    let enabled = load_global_config().is_ok_and(|config| config.auto_update(app_name));
+    if !enabled || is_offline() || get_installed_record(app_name).is_some_and(|a| a.frozen) {
+        return None;
+    }
+
+    let self_exe = std::env::current_exe().ok()?;
+
+    // Decided here: the check runs without a terminal either way. --quiet
+    // hides the notification as well as the stderr line.
+    let mut command = Command::new(self_exe);
+    command.args(["internal-update-check", app_name]);
+    if from_menu && crate::logging::enabled(crate::logging::Level::Info) {
+        command.arg("--notify");
+    }
+    let child = command
+        .stdin(Stdio::null())
+        .stdout(Stdio::null())
+        .stderr(Stdio::piped())
+        .spawn()
+        .ok()?;
+    Some(UpdateCheck { child })
 }
 
 /// Get all unique deps_ids from installed apps
-fn get_all_deps_ids() -> Result<HashSet<String>, UpdateError> {
+///
+/// Fails if any app's base info is unreadable, so `clean` can never prune a
+/// deps layer that is still in use.
+pub(crate) fn get_all_deps_ids() -> std::io::Result<HashSet<String>> {
     let mut deps_ids = HashSet::new();
     let apps_dir = paths::apps_dir();
 
@@ -259,12 +461,18 @@ fn get_all_deps_ids() -> Result<HashSet<String>, UpdateError> {
 
     for entry in fs::read_dir(&apps_dir)? {
         let entry = entry?;
-        if entry.file_type()?.is_dir() {
-            let base_json = entry.path().join("base.json");
-            if let Some(info) = read_base_json(&base_json) {
-                if let Some(deps_id) = info.deps_id {
-                    deps_ids.insert(deps_id);
-                }
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+        match read_base_info_for_rootfs(&entry.path().join("rootfs")) {
+            Ok(Some(info)) => deps_ids.extend(info.deps_id),
+            Ok(None) => {}
+            Err(e) => {
+                return Err(std::io::Error::other(format!(
+                    "could not read base info for {}: {}",
+                    entry.file_name().to_string_lossy(),
+                    e
+                )));
             }
         }
     }
@@ -274,6 +482,7 @@ fn get_all_deps_ids() -> Result<HashSet<String>, UpdateError> {
 
 /// Upgrade system packages in a deps layer
 fn upgrade_deps_layer(deps_id: &str) -> Result<(), UpdateError> {
+    let _lock = InstallLock::acquire()?;
     let deps_rootfs = paths::deps_rootfs_dir(deps_id);
     let deps_layer = paths::deps_layer_dir(deps_id);
 
@@ -284,7 +493,7 @@ fn upgrade_deps_layer(deps_id: &str) -> Result<(), UpdateError> {
         )));
     }
 
-    println!("[voidbox] Upgrading system packages in {}...", deps_id);
+    crate::info!("Upgrading system packages in {}...", deps_id);
 
     // Create upgrade script
     let upgrade_script = r#"#!/bin/bash
@@ -324,7 +533,7 @@ echo "System packages upgraded!"
 
     let status = Command::new(&exe_to_use)
         .args(["internal-run", deps_rootfs.to_str().unwrap(), "/upgrade.sh"])
-        .stdout(Stdio::inherit())
+        .stdout(crate::logging::child_stdout())
         .stderr(Stdio::inherit())
         .status()
         .map_err(|e| UpdateError::Failed(format!("Failed to run upgrade: {}", e)))?;
@@ -344,10 +553,15 @@ echo "System packages upgraded!"
 
 /// Update all installed apps and system packages
 pub fn update_all(force: bool) -> Result<(), UpdateError> {
+    if is_offline() {
+        crate::info!("Offline mode - skipping updates.");
+        return Ok(());
+    }
+
     let db_path = paths::database_path();
 
     if !db_path.exists() {
-        println!("[voidbox] No apps installed.");
+        crate::info!("No apps installed.");
         return Ok(());
     }
 
@@ -356,30 +570,27 @@ pub fn update_all(force: bool) -> Result<(), UpdateError> {
         .map_err(|e| UpdateError::Failed(format!("Failed to parse database: {}", e)))?;
 
     if apps.is_empty() {
-        println!("[voidbox] No apps installed.");
+        crate::info!("No apps installed.");
         return Ok(());
     }
 
     // First, upgrade system packages in all shared deps layers
     let deps_ids = get_all_deps_ids()?;
     if !deps_ids.is_empty() {
-        println!(
-            "[voidbox] Upgrading system packages in {} shared layer(s)...",
+        crate::info!(
+            "Upgrading system packages in {} shared layer(s)...",
             deps_ids.len()
         );
         for deps_id in &deps_ids {
             if let Err(e) = upgrade_deps_layer(deps_id) {
-                println!("[voidbox] Warning: Failed to upgrade {}: {}", deps_id, e);
+                crate::warn!("Warning: Failed to upgrade {}: {}", deps_id, e);
             }
         }
-        println!("[voidbox] System packages upgraded.");
+        crate::info!("System packages upgraded.");
     }
 
     // Then check and update app binaries
-    println!(
-        "[voidbox] Checking {} app(s) for updates...",
-        apps.len()
-    );
+    crate::info!("Checking {} app(s) for updates...", apps.len());
 
     let mut updated = 0;
     let mut up_to_date = 0;
@@ -388,19 +599,19 @@ pub fn update_all(force: bool) -> Result<(), UpdateError> {
     let mut failed = 0;
 
     for app in &apps {
-        match update_app(&app.name, force) {
+        match update_app(&app.name, force, false) {
             Ok(UpdateOutcome::Updated) => updated += 1,
             Ok(UpdateOutcome::UpToDate) => up_to_date += 1,
             Ok(UpdateOutcome::Skipped) => skipped += 1,
             Ok(UpdateOutcome::Unknown) => unknown += 1,
             Err(e) => {
-                println!("[voidbox] Failed to update {}: {}", app.name, e);
+                crate::warn!("Failed to update {}: {}", app.name, e);
                 failed += 1;
             }
         }
     }
 
-    println!("[voidbox] Update check complete!");
+    crate::info!("Update check complete!");
     if updated > 0 {
         println!("  {} updated", updated);
     }
@@ -422,7 +633,12 @@ pub fn update_all(force: bool) -> Result<(), UpdateError> {
 
 /// Self-update voidbox
 pub fn self_update(force: bool) -> Result<(), UpdateError> {
-    println!("[voidbox] Checking for updates...");
+    if is_offline() {
+        crate::info!("Offline mode - skipping self-update.");
+        return Ok(());
+    }
+
+    crate::info!("Checking for updates...");
     println!("  Installed: v{}", crate::VERSION);
 
     let status = self_update::backends::github::Update::configure()
@@ -450,11 +666,11 @@ pub fn self_update(force: bool) -> Result<(), UpdateError> {
     };
 
     if !force && !is_newer {
-        println!("[voidbox] Already running latest version.");
+        crate::info!("Already running latest version.");
         return Ok(());
     }
 
-    println!("[voidbox] Updating to v{}...", latest_version);
+    crate::info!("Updating to v{}...", latest_version);
 
     self_update::backends::github::Update::configure()
         .repo_owner(crate::SELF_UPDATE_OWNER)
@@ -467,10 +683,19 @@ pub fn self_update(force: bool) -> Result<(), UpdateError> {
         .update()
         .map_err(|e| UpdateError::Failed(format!("Update failed: {}", e)))?;
 
-    println!(
-        "[voidbox] Updated to v{}! Please restart voidbox.",
-        latest_version
-    );
+    crate::info!("Updated to v{}! Please restart voidbox.", latest_version);
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn gitlab_fixture_reports_newest_version() {
+        let body = include_str!("../../tests/fixtures/gitlab_releases.json");
+        assert_eq!(latest_gitlab_version(body, "example/app").unwrap(), "1.4.2");
+        assert!(latest_gitlab_version("[]", "example/app").is_err());
+    }
+}
@@ -0,0 +1,114 @@
+//! Validate command implementation
+
+use crate::cli::install::resolve_release;
+use crate::manifest::{
+    AppManifest, SeccompProfile, SourceConfig, parse_manifest_file, validate_manifest,
+};
+use crate::storage::{http_head, is_offline};
+use std::path::Path;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum ValidateError {
+    #[error("Manifest error: {0}")]
+    ManifestError(#[from] crate::manifest::ManifestError),
+}
+
+/// Check a manifest before publishing it
+///
+/// Parse and validation errors fail the command. Unreachable sources and
+/// suspicious settings are only reported as warnings.
+pub fn validate_manifest_file(path: &Path) -> Result<(), ValidateError> {
+    let manifest = parse_manifest_file(path)?;
+    validate_manifest(&manifest)?;
+
+    let mut warnings = check_settings(&manifest);
+    if is_offline() {
+        crate::info!("Offline mode - skipping source checks.");
+    } else {
+        warnings.extend(check_source(&manifest));
+    }
+
+    for warning in &warnings {
+        crate::warn!("Warning: {}", warning);
+    }
+    crate::info!(
+        "{} is valid ({} warning{}).",
+        path.display(),
+        warnings.len(),
+        if warnings.len() == 1 { "" } else { "s" }
+    );
+    Ok(())
+}
+
+/// Settings that are allowed but probably not what the author meant
+fn check_settings(manifest: &AppManifest) -> Vec<String> {
+    let mut warnings = Vec::new();
+    let permissions = &manifest.permissions;
+    let runtime = &manifest.runtime;
+
+    if permissions.dev_mode && !permissions.network {
+        warnings.push(
+            "dev_mode = true with network = false: host tools like pip and npm \
+             will not reach the network"
+                .to_string(),
+        );
+    }
+    if runtime.persist_home && permissions.home {
+        warnings.push(
+            "persist_home = true replaces the host home, so home = true has no effect".to_string(),
+        );
+    }
+    if !manifest.bridge.allowed_commands.is_empty() && !permissions.native_mode {
+        warnings.push("[bridge] is only used in native mode (native_mode = false)".to_string());
+    }
+    if runtime.chromium
+        && manifest.security.seccomp == SeccompProfile::Strict
+        && !manifest.binary.args.iter().any(|a| a == "--no-sandbox")
+    {
+        warnings.push(
+            "seccomp = \"strict\" blocks namespaces, which Chromium's sandbox needs \
+             (add --no-sandbox or use the default profile)"
+                .to_string(),
+        );
+    }
+    let host_arch = crate::manifest::host_ubuntu_arch();
+    if let Some(host_arch) = host_arch.filter(|arch| !runtime.supports_arch(arch)) {
+        warnings.push(format!(
+            "runtime.arch ({}) does not include this machine ({})",
+            runtime.arch.join(", "),
+            host_arch
+        ));
+    }
+
+    warnings
+}
+
+/// Check that the download URL (and GitHub/GitLab project) can be reached
+fn check_source(manifest: &AppManifest) -> Vec<String> {
+    if let SourceConfig::Local { path, .. } = &manifest.source {
+        if !path.exists() {
+            return vec![format!("local source does not exist: {}", path.display())];
+        }
+        return Vec::new();
+    }
+
+    crate::info!("Checking source...");
+    let release = match resolve_release(manifest, None) {
+        Ok(Some(release)) => release,
+        Ok(None) => return Vec::new(),
+        Err(e) => return vec![format!("could not resolve the source: {}", e)],
+    };
+
+    crate::info!("Latest version: {}", release.version);
+    match http_head(&release.url).call() {
+        Ok(_) => {
+            crate::info!("Download reachable: {}", release.url);
+            Vec::new()
+        }
+        Err(e) => vec![format!(
+            "download URL not reachable ({}): {}",
+            e, release.url
+        )],
+    }
+}
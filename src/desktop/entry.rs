@@ -9,6 +9,9 @@ use thiserror::Error;
 pub enum DesktopError {
     #[error("Failed to create desktop entry: {0}")]
     CreateError(#[from] std::io::Error),
+
+    #[error("Failed to register MIME handler: {0}")]
+    MimeError(String),
 }
 
 /// Generate a .desktop file for an app
@@ -20,8 +23,11 @@ pub fn create_desktop_entry(manifest: &AppManifest) -> Result<(), DesktopError>
     }
 
     let icon_path = paths::app_icon_path(&manifest.app.name);
+    let svg_icon_path = paths::app_icon_svg_path(&manifest.app.name);
     let icon_value = if icon_path.exists() {
         icon_path.to_string_lossy().to_string()
+    } else if svg_icon_path.exists() {
+        svg_icon_path.to_string_lossy().to_string()
     } else {
         // Fallback to generic icon
         "application-x-executable".to_string()
@@ -64,10 +70,23 @@ pub fn create_desktop_entry(manifest: &AppManifest) -> Result<(), DesktopError>
         " -- %F"
     };
 
+    // Absolute path so launching works even when ~/.local/bin isn't in PATH
     let exec_path = paths::voidbox_exe_path();
-    let exec_value = exec_path.to_string_lossy();
+    let exec_value = quote_exec_arg(&exec_path.to_string_lossy());
+
+    let actions = if manifest.desktop.actions.is_empty() {
+        String::new()
+    } else {
+        let ids: Vec<&str> = manifest
+            .desktop
+            .actions
+            .iter()
+            .map(|a| a.id.as_str())
+            .collect();
+        format!("Actions={};\n", ids.join(";"))
+    };
 
-    let content = format!(
+    let mut content = format!(
         r#"[Desktop Entry]
 Name={}
 Comment={}
@@ -77,7 +96,7 @@ Terminal=false
 Type=Application
 Categories={}
 StartupWMClass={}
-{}{}
+{}{}{}
 "#,
         manifest.app.display_name,
         manifest.app.description,
@@ -89,14 +108,57 @@ StartupWMClass={}
         wm_class,
         keywords,
         mime_types,
+        actions,
     );
 
+    let action_blocks: Vec<String> = manifest
+        .desktop
+        .actions
+        .iter()
+        .map(|action| {
+            let mut exec = format!("{} run {}", exec_value, manifest.app.name);
+            if !action.args.is_empty() {
+                exec.push_str(" --");
+                for arg in &action.args {
+                    exec.push(' ');
+                    exec.push_str(&quote_exec_arg(arg));
+                }
+            }
+            format!(
+                "[Desktop Action {}]\nName={}\nExec={}\n",
+                action.id, action.name, exec
+            )
+        })
+        .collect();
+    content.push_str(&action_blocks.join("\n"));
+
     fs::write(&desktop_path, content)?;
-    update_desktop_database();
 
     Ok(())
 }
 
+/// Quote an argument for a desktop entry `Exec=` line
+///
+/// Per the Desktop Entry spec, `"`, `` ` ``, `$` and `\` are backslash-escaped
+/// inside the quotes, and backslashes are escaped once more because `Exec`
+/// is a string value. A literal `%` is written as `%%`.
+fn quote_exec_arg(arg: &str) -> String {
+    let mut quoted = String::from("\"");
+    for c in arg.chars() {
+        match c {
+            '"' | '`' | '$' => {
+                quoted.push_str("\\\\");
+                quoted.push(c);
+            }
+            '\\' => quoted.push_str("\\\\\\\\"),
+            '%' => quoted.push_str("%%"),
+            _ => quoted.push(c),
+        }
+    }
+    quoted.push('"');
+    quoted
+}
+
 /// Remove a .desktop file for an app
 pub fn remove_desktop_entry(app_name: &str) -> Result<(), DesktopError> {
     let desktop_path = paths::app_desktop_path(app_name);
@@ -115,3 +177,23 @@ pub fn update_desktop_database() {
             .output();
     }
 }
+
+/// Update the user's hicolor icon theme cache
+pub fn update_icon_cache() {
+    // Also optional - only matters if the theme dir exists
+    let Some(hicolor) = dirs::data_dir().map(|d| d.join("icons/hicolor")) else {
+        return;
+    };
+    if hicolor.exists() {
+        let _ = std::process::Command::new("gtk-update-icon-cache")
+            .args(["-f", "-t"])
+            .arg(hicolor)
+            .output();
+    }
+}
+
+/// Refresh launcher menus after desktop entries or icons change (best-effort)
+pub fn refresh_desktop_caches() {
+    update_desktop_database();
+    update_icon_cache();
+}
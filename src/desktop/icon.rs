@@ -3,6 +3,7 @@
 use crate::storage::paths;
 use std::fs;
 use std::path::Path;
+use std::process::Command;
 use thiserror::Error;
 use walkdir::WalkDir;
 
@@ -20,18 +21,15 @@ pub fn extract_icon(app_name: &str, icon_path: Option<&str>) -> Result<(), IconE
     let layer_dir = paths::app_layer_dir(app_name);
     let rootfs_dir = paths::app_rootfs_dir(app_name);
     let app_rootfs = if layer_dir.exists() { layer_dir } else { rootfs_dir };
-    let icon_dest = paths::app_icon_path(app_name);
 
-    if let Some(parent) = icon_dest.parent() {
-        fs::create_dir_all(parent)?;
-    }
+    fs::create_dir_all(paths::icons_dir())?;
 
     // If specific path provided, try it directly first
     if let Some(path) = icon_path {
         // Try as a relative path from rootfs
         let full_path = app_rootfs.join(path);
         if full_path.exists() {
-            fs::copy(&full_path, &icon_dest)?;
+            install_icon_file(&full_path, app_name)?;
             return Ok(());
         }
 
@@ -41,7 +39,7 @@ pub fn extract_icon(app_name: &str, icon_path: Option<&str>) -> Result<(), IconE
             for entry in WalkDir::new(&opt_path).max_depth(10) {
                 if let Ok(entry) = entry {
                     if entry.path().ends_with(path) {
-                        fs::copy(entry.path(), &icon_dest)?;
+                        install_icon_file(entry.path(), app_name)?;
                         return Ok(());
                     }
                 }
@@ -53,7 +51,7 @@ pub fn extract_icon(app_name: &str, icon_path: Option<&str>) -> Result<(), IconE
         for entry in WalkDir::new(&app_rootfs).max_depth(12) {
             if let Ok(entry) = entry {
                 if entry.file_name() == filename {
-                    fs::copy(entry.path(), &icon_dest)?;
+                    install_icon_file(entry.path(), app_name)?;
                     return Ok(());
                 }
             }
@@ -77,7 +75,7 @@ pub fn extract_icon(app_name: &str, icon_path: Option<&str>) -> Result<(), IconE
             let name = entry.file_name().to_string_lossy().to_lowercase();
             for pattern in &patterns {
                 if name == pattern.to_lowercase() {
-                    fs::copy(entry.path(), &icon_dest)?;
+                    install_icon_file(entry.path(), app_name)?;
                     return Ok(());
                 }
             }
@@ -88,11 +86,106 @@ pub fn extract_icon(app_name: &str, icon_path: Option<&str>) -> Result<(), IconE
     Ok(())
 }
 
+/// Size of PNGs rasterized from SVG icons
+const RASTER_SIZE: &str = "256";
+
+/// Copy an icon into the icons dir, rasterizing SVGs to PNG when possible
+///
+/// If `rsvg-convert` is unavailable or fails, the SVG is kept as-is at
+/// `app_icon_svg_path` and the desktop entry references that instead.
+fn install_icon_file(source: &Path, app_name: &str) -> Result<(), IconError> {
+    install_icon_to(
+        source,
+        &paths::app_icon_path(app_name),
+        &paths::app_icon_svg_path(app_name),
+    )
+}
+
+fn install_icon_to(source: &Path, png_dest: &Path, svg_dest: &Path) -> Result<(), IconError> {
+    let is_svg = source
+        .extension()
+        .is_some_and(|ext| ext.eq_ignore_ascii_case("svg"));
+
+    if !is_svg {
+        fs::copy(source, png_dest)?;
+        fs::remove_file(svg_dest).ok();
+        return Ok(());
+    }
+
+    if rasterize_svg(source, png_dest) {
+        fs::remove_file(svg_dest).ok();
+        return Ok(());
+    }
+
+    fs::copy(source, svg_dest)?;
+    fs::remove_file(png_dest).ok();
+    Ok(())
+}
+
+/// Rasterize an SVG with rsvg-convert, returning false if that isn't possible
+fn rasterize_svg(source: &Path, dest: &Path) -> bool {
+    let Ok(rsvg_convert) = which::which("rsvg-convert") else {
+        return false;
+    };
+
+    let ok = Command::new(rsvg_convert)
+        .args(["--width", RASTER_SIZE, "--height", RASTER_SIZE])
+        .arg("--keep-aspect-ratio")
+        .arg("--output")
+        .arg(dest)
+        .arg(source)
+        .status()
+        .is_ok_and(|s| s.success());
+
+    if !ok {
+        fs::remove_file(dest).ok();
+    }
+    ok
+}
+
 /// Remove icon for an app
 pub fn remove_icon(app_name: &str) -> Result<(), IconError> {
-    let icon_path = paths::app_icon_path(app_name);
-    if icon_path.exists() {
-        fs::remove_file(icon_path)?;
+    for icon_path in [
+        paths::app_icon_path(app_name),
+        paths::app_icon_svg_path(app_name),
+    ] {
+        if icon_path.exists() {
+            fs::remove_file(icon_path)?;
+        }
     }
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINIMAL_SVG: &str = r##"<svg xmlns="http://www.w3.org/2000/svg" width="16" height="16"><rect width="16" height="16" fill="#3a7"/></svg>"##;
+
+    #[test]
+    fn svg_icon_is_installed_as_png_or_svg() {
+        let dir = std::env::temp_dir().join(format!("voidbox-test-icon-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+
+        let source = dir.join("app.svg");
+        let png_dest = dir.join("installed.png");
+        let svg_dest = dir.join("installed.svg");
+        fs::write(&source, MINIMAL_SVG).unwrap();
+        fs::write(&png_dest, b"stale").unwrap();
+
+        install_icon_to(&source, &png_dest, &svg_dest).unwrap();
+
+        if which::which("rsvg-convert").is_ok() {
+            // Rasterized: a real PNG replaces the stale one, no SVG left
+            assert!(fs::read(&png_dest).unwrap().starts_with(b"\x89PNG"));
+            assert!(!svg_dest.exists());
+        } else {
+            // Fallback: the SVG is kept as-is and the stale PNG removed
+            assert_eq!(fs::read_to_string(&svg_dest).unwrap(), MINIMAL_SVG);
+            assert!(!png_dest.exists());
+        }
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
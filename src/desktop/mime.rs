@@ -0,0 +1,90 @@
+//! MIME handler registration (default application for URLs and file types)
+
+use super::DesktopError;
+use crate::manifest::AppManifest;
+use std::fs;
+use std::process::Command;
+
+/// Register the app as the default handler for its manifest MIME types
+///
+/// Does nothing when the manifest lists no MIME types or `xdg-mime` is not
+/// installed.
+pub fn register_mime_handlers(manifest: &AppManifest) -> Result<(), DesktopError> {
+    if manifest.desktop.mime_types.is_empty() {
+        return Ok(());
+    }
+
+    let Ok(xdg_mime) = which::which("xdg-mime") else {
+        crate::info!("Note: xdg-mime not found, skipping MIME handler registration");
+        return Ok(());
+    };
+
+    let desktop_file = desktop_file_name(&manifest.app.name);
+    for mime in &manifest.desktop.mime_types {
+        let status = Command::new(&xdg_mime)
+            .args(["default", &desktop_file, mime])
+            .status()?;
+
+        if !status.success() {
+            return Err(DesktopError::MimeError(format!(
+                "xdg-mime default {} {} exited with {}",
+                desktop_file, mime, status
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+/// Remove the app from the user's default MIME handlers
+///
+/// `xdg-mime` has no "unset", so this edits `~/.config/mimeapps.list`
+/// directly and drops any association pointing at the app's desktop file.
+pub fn unregister_mime_handlers(app_name: &str) -> Result<(), DesktopError> {
+    let Some(config_dir) = dirs::config_dir() else {
+        return Ok(());
+    };
+    let mimeapps = config_dir.join("mimeapps.list");
+    if !mimeapps.exists() {
+        return Ok(());
+    }
+
+    let desktop_file = desktop_file_name(app_name);
+    let content = fs::read_to_string(&mimeapps)?;
+    let mut changed = false;
+    let mut output = String::with_capacity(content.len());
+
+    for line in content.lines() {
+        let Some((mime, handlers)) = line.split_once('=') else {
+            output.push_str(line);
+            output.push('\n');
+            continue;
+        };
+
+        let kept: Vec<&str> = handlers
+            .split(';')
+            .filter(|h| !h.is_empty() && *h != desktop_file)
+            .collect();
+
+        if kept.len() == handlers.split(';').filter(|h| !h.is_empty()).count() {
+            output.push_str(line);
+            output.push('\n');
+            continue;
+        }
+
+        changed = true;
+        if !kept.is_empty() {
+            output.push_str(&format!("{}={};\n", mime, kept.join(";")));
+        }
+    }
+
+    if changed {
+        fs::write(&mimeapps, output)?;
+    }
+
+    Ok(())
+}
+
+fn desktop_file_name(app_name: &str) -> String {
+    format!("voidbox-{}.desktop", app_name)
+}
@@ -2,8 +2,10 @@
 
 mod entry;
 mod icon;
+mod mime;
 mod symlink;
 
 pub use entry::*;
 pub use icon::*;
+pub use mime::*;
 pub use symlink::*;
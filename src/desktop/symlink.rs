@@ -2,12 +2,20 @@
 
 use crate::storage::paths;
 use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
 use thiserror::Error;
 
+/// Marker comment written above the PATH line so --fix-path stays idempotent
+const PATH_MARKER: &str = "# Added by voidbox: put ~/.local/bin on PATH";
+
 #[derive(Error, Debug)]
 pub enum SymlinkError {
     #[error("Failed to create symlink: {0}")]
     CreateError(#[from] std::io::Error),
+
+    #[error("Could not detect a supported shell (bash, zsh, fish) from $SHELL")]
+    UnknownShell,
 }
 
 /// Create a wrapper script for an app in ~/.local/bin
@@ -77,9 +85,75 @@ pub fn install_self() -> Result<(), SymlinkError> {
         fs::set_permissions(&install_path, fs::Permissions::from_mode(0o755))?;
     }
 
+    if !paths::is_bin_dir_in_path() {
+        warn_bin_dir_not_in_path();
+    }
+
     Ok(())
 }
 
+/// Print a prominent warning that ~/.local/bin is missing from PATH
+pub fn warn_bin_dir_not_in_path() {
+    eprintln!();
+    crate::warn!(
+        "WARNING: {} is not in your PATH.",
+        paths::bin_dir().display()
+    );
+    crate::notice!("The 'voidbox' command and app launchers won't be found by your shell.");
+    match detect_shell_rc() {
+        Some(rc) => crate::notice!("Run 'voidbox --fix-path' to add it to {}", rc.display()),
+        None => {
+            crate::notice!("Add this to your shell config: export PATH=\"$HOME/.local/bin:$PATH\"")
+        }
+    }
+    eprintln!();
+}
+
+/// Detect the rc file of the user's login shell from $SHELL
+pub fn detect_shell_rc() -> Option<PathBuf> {
+    let shell = std::env::var("SHELL").ok()?;
+    let name = shell.rsplit('/').next()?;
+    let home = dirs::home_dir()?;
+
+    match name {
+        "bash" => Some(home.join(".bashrc")),
+        "zsh" => Some(home.join(".zshrc")),
+        "fish" => Some(dirs::config_dir()?.join("fish/config.fish")),
+        _ => None,
+    }
+}
+
+/// Append ~/.local/bin to PATH in the user's shell rc file
+/// Returns the rc path and whether it was changed (false if already present)
+pub fn fix_path_in_shell_rc() -> Result<(PathBuf, bool), SymlinkError> {
+    let rc = detect_shell_rc().ok_or(SymlinkError::UnknownShell)?;
+
+    let existing = fs::read_to_string(&rc).unwrap_or_default();
+    if existing.contains(PATH_MARKER) {
+        return Ok((rc, false));
+    }
+
+    let export_line = if rc.ends_with("config.fish") {
+        "fish_add_path -g $HOME/.local/bin"
+    } else {
+        "export PATH=\"$HOME/.local/bin:$PATH\""
+    };
+
+    if let Some(parent) = rc.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut file = fs::OpenOptions::new().create(true).append(true).open(&rc)?;
+    let separator = if existing.is_empty() || existing.ends_with('\n') {
+        ""
+    } else {
+        "\n"
+    };
+    write!(file, "{}\n{}\n{}\n", separator, PATH_MARKER, export_line)?;
+
+    Ok((rc, true))
+}
+
 /// Check if voidbox is installed
 pub fn is_installed() -> bool {
     paths::install_path().exists()
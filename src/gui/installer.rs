@@ -5,8 +5,8 @@ use std::thread;
 use crate::bundle;
 use crate::cli;
 use crate::desktop::install_self;
-use crate::manifest::parse_manifest;
-use crate::storage::paths;
+use crate::manifest::{InstallSource, parse_manifest};
+use crate::storage::{paths, request_interrupt};
 
 // Theme colors - Black with red accents
 const BG_COLOR: Color32 = Color32::from_rgb(18, 18, 18);
@@ -32,6 +32,7 @@ pub struct InstallerApp {
     state: InstallerState,
     recv: Receiver<InstallStatus>,
     sender: Sender<InstallStatus>, // Kept to clone for the thread
+    cancelling: bool,
 }
 
 enum InstallerState {
@@ -39,12 +40,14 @@ enum InstallerState {
     Installing { progress: f32, message: String },
     Done { message: String },
     Error { message: String },
+    Cancelled,
 }
 
 enum InstallStatus {
     Progress(f32, String),
     Success(String),
     Error(String),
+    Cancelled,
 }
 
 impl InstallerApp {
@@ -55,6 +58,7 @@ impl InstallerApp {
             state: InstallerState::Confirmation,
             recv,
             sender,
+            cancelling: false,
         }
     }
 
@@ -84,7 +88,16 @@ impl InstallerApp {
                     let _ = sender.send(InstallStatus::Success(msg));
                 }
                 Err(e) => {
-                    let _ = sender.send(InstallStatus::Error(e.to_string()));
+                    let error = cli::RunnerError::from(e);
+                    if matches!(error, cli::RunnerError::Interrupted(_)) {
+                        let _ = sender.send(InstallStatus::Cancelled);
+                        return;
+                    }
+                    let message = match error.hint() {
+                        Some(hint) => format!("{}\n\n{}", error, hint),
+                        None => error.to_string(),
+                    };
+                    let _ = sender.send(InstallStatus::Error(message));
                 }
             },
         );
@@ -149,22 +162,27 @@ fn perform_installation(
                 "Parsing manifest...".to_string(),
             ));
 
-            let bundle_data = bundle::extract_embedded_bundle()?;
+            // Forward real install progress into the remaining 30-100% of the bar
+            let mut install_options = cli::InstallOptions {
+                progress: Some(forward_progress(sender.clone(), 0.3)),
+                ..Default::default()
+            };
+
+            let bundle_data = bundle::extract_embedded_bundle(&bundle::load_trusted_keys()?)?;
             let installed_name = if let Some(bundle_data) = bundle_data {
+                cli::check_embedded_signature(&bundle_data.signature)?;
+                install_options.source = Some(InstallSource::Bundle {
+                    path: std::env::current_exe()?,
+                });
                 let manifest = parse_manifest(&bundle_data.manifest_content)?;
                 let installed_name = manifest.app.display_name.clone();
-                let _ = sender.send(InstallStatus::Progress(
-                    0.5,
-                    "Extracting bundle...".to_string(),
-                ));
-
                 let install_result = cli::install_app_from_bundle(
                     &bundle_data.manifest_content,
                     &bundle_data.archive_path,
                     &bundle_data.archive_ext,
-                    false,
+                    &install_options,
                 );
-                bundle_data.cleanup();
+                drop(bundle_data);
                 install_result?;
                 installed_name
             } else {
@@ -175,16 +193,10 @@ fn perform_installation(
                 paths::ensure_dirs()?;
                 std::fs::write(&manifest_path, manifest_content)?;
 
-                // We can't easily get granular progress from the CLI functions yet without refactoring,
-                // so we'll just show indeterminate progress or "Installing..."
-                let _ = sender.send(InstallStatus::Progress(
-                    0.5,
-                    "Downloading and extracting...".to_string(),
-                ));
-
                 // Install the app
+                install_options.source = Some(InstallSource::embedded(&name));
                 // Note: This blocks until done
-                cli::install_app_from_manifest(&manifest, false)?;
+                cli::install_app_from_manifest(&manifest, &install_options)?;
                 display_name
             };
 
@@ -201,6 +213,20 @@ fn perform_installation(
     }
 }
 
+/// Create an install progress sender that forwards updates to the GUI,
+/// scaled into the `offset..1.0` range of the progress bar
+fn forward_progress(sender: Sender<InstallStatus>, offset: f32) -> cli::InstallProgress {
+    let (progress_tx, progress_rx) = channel::<(f32, String)>();
+    thread::spawn(move || {
+        // Ends once the install drops its InstallOptions
+        for (fraction, message) in progress_rx {
+            let scaled = offset + (1.0 - offset) * fraction.clamp(0.0, 1.0);
+            let _ = sender.send(InstallStatus::Progress(scaled, message));
+        }
+    });
+    progress_tx
+}
+
 impl eframe::App for InstallerApp {
     fn update(&mut self, ctx: &egui::Context, _frame: &mut eframe::Frame) {
         // Poll for updates from the thread
@@ -218,6 +244,9 @@ impl eframe::App for InstallerApp {
                 InstallStatus::Error(msg) => {
                     self.state = InstallerState::Error { message: msg };
                 }
+                InstallStatus::Cancelled => {
+                    self.state = InstallerState::Cancelled;
+                }
             }
         }
 
@@ -328,9 +357,27 @@ impl eframe::App for InstallerApp {
                             ui.add_space(20.0);
                             ui.add(
                                 egui::ProgressBar::new(*progress)
-                                    .animate(true)
+                                    .show_percentage()
                                     .fill(ACCENT_COLOR),
                             );
+                            ui.add_space(20.0);
+                            // The install stops at its next download chunk or
+                            // extracted file and removes what it left behind
+                            let label = if self.cancelling {
+                                "Cancelling..."
+                            } else {
+                                "Cancel"
+                            };
+                            if ui
+                                .add_enabled(
+                                    !self.cancelling,
+                                    egui::Button::new(RichText::new(label).size(14.0)),
+                                )
+                                .clicked()
+                            {
+                                self.cancelling = true;
+                                request_interrupt();
+                            }
                         }
                         InstallerState::Done { message } => {
                             ui.add_space(10.0);
@@ -364,6 +411,26 @@ impl eframe::App for InstallerApp {
                                 std::process::exit(1);
                             }
                         }
+                        InstallerState::Cancelled => {
+                            ui.add_space(10.0);
+                            ui.label(RichText::new("✗").size(40.0).color(TEXT_SECONDARY));
+                            ui.add_space(10.0);
+                            ui.label(
+                                RichText::new("Installation Cancelled")
+                                    .size(18.0)
+                                    .color(TEXT_PRIMARY),
+                            );
+                            ui.add_space(10.0);
+                            ui.label(
+                                RichText::new("The install was stopped and cleaned up.")
+                                    .size(12.0)
+                                    .color(TEXT_SECONDARY),
+                            );
+                            ui.add_space(25.0);
+                            if ui.button(RichText::new("Close").size(14.0)).clicked() {
+                                std::process::exit(130);
+                            }
+                        }
                     }
                 });
             });
@@ -19,6 +19,15 @@ pub fn is_gui_mode() -> bool {
     !atty::is(atty::Stream::Stdin)
 }
 
+/// Check whether voidbox was started from the app menu or a file manager
+///
+/// Neither stdin nor stderr is a terminal then, so anything printed to
+/// stderr is lost and has to go to a log file or a desktop notification.
+pub fn launched_from_menu() -> bool {
+    use std::io::IsTerminal;
+    !std::io::stdin().is_terminal() && !std::io::stderr().is_terminal()
+}
+
 /// Check if any GUI dialog tool is available
 pub fn has_gui_support() -> bool {
     which_dialog().is_some()
@@ -6,6 +6,7 @@ pub mod cli;
 pub mod bundle;
 pub mod desktop;
 pub mod gui;
+pub mod logging;
 pub mod manifest;
 pub mod runtime;
 pub mod settings;
@@ -32,5 +33,8 @@ pub const SELF_UPDATE_REPO: &str = "voidbox";
 /// Container hostname
 pub const CONTAINER_HOSTNAME: &str = "voidbox";
 
+/// UID/GID of the unprivileged user commands can be dropped to in the container
+pub const CONTAINER_USER_ID: u32 = 1000;
+
 /// Ubuntu releases URL for fetching base images
 pub const UBUNTU_RELEASES_URL: &str = "https://cdimage.ubuntu.com/ubuntu-base/releases/";
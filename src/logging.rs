@@ -0,0 +1,173 @@
+//! Leveled diagnostic output
+//!
+//! Regular `[voidbox] ...` messages go through `info!` (stdout), `notice!`
+//! (stderr, for output that must stay out of an app's stdout) and `warn!`.
+//! `--quiet` (`VOIDBOX_QUIET`) hides all three, leaving only errors. Extra
+//! detail for bug reports goes through `debug!` and `trace!`, which only
+//! print when `VOIDBOX_LOG` asks for it. `-v`/`-vv` and `-q` set the
+//! variables, so re-spawned voidbox processes (the container init,
+//! `internal-run`) inherit the level.
+
+use std::sync::OnceLock;
+
+/// Environment variable holding the log level (set by `-v`)
+pub const LOG_ENV: &str = "VOIDBOX_LOG";
+
+/// Environment variable that limits output to errors (set by `-q`)
+pub const QUIET_ENV: &str = "VOIDBOX_QUIET";
+
+/// Verbosity levels, least to most detailed
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Level {
+    Error,
+    Warn,
+    Info,
+    Debug,
+    Trace,
+}
+
+impl Level {
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().to_lowercase().as_str() {
+            "error" => Some(Self::Error),
+            "warn" | "warning" => Some(Self::Warn),
+            "info" => Some(Self::Info),
+            "debug" => Some(Self::Debug),
+            "trace" => Some(Self::Trace),
+            _ => None,
+        }
+    }
+
+    fn name(self) -> &'static str {
+        match self {
+            Self::Error => "error",
+            Self::Warn => "warn",
+            Self::Info => "info",
+            Self::Debug => "debug",
+            Self::Trace => "trace",
+        }
+    }
+}
+
+/// Level requested by `VOIDBOX_LOG`, else error with `VOIDBOX_QUIET` and
+/// info otherwise
+fn level_from_env() -> Level {
+    std::env::var(LOG_ENV)
+        .ok()
+        .and_then(|v| Level::parse(&v))
+        .unwrap_or_else(|| {
+            if is_quiet() {
+                Level::Error
+            } else {
+                Level::Info
+            }
+        })
+}
+
+/// Check whether `VOIDBOX_QUIET` is set
+fn is_quiet() -> bool {
+    matches!(
+        std::env::var(QUIET_ENV).as_deref(),
+        Ok("1") | Ok("true") | Ok("yes")
+    )
+}
+
+/// Current level, read once per process
+pub fn level() -> Level {
+    static LEVEL: OnceLock<Level> = OnceLock::new();
+    *LEVEL.get_or_init(level_from_env)
+}
+
+/// Check whether messages at `level` are printed
+pub fn enabled(level: Level) -> bool {
+    level <= self::level()
+}
+
+/// Raise the level for `-v` (debug) or `-vv` (trace)
+///
+/// Must run before anything logs and before any threads are started, since
+/// it exports the level through the environment.
+pub fn set_verbosity(count: u8) {
+    let level = match count {
+        0 => return,
+        1 => Level::Debug,
+        _ => Level::Trace,
+    };
+    if level > level_from_env() {
+        unsafe {
+            std::env::set_var(LOG_ENV, level.name());
+        }
+    }
+}
+
+/// Only print errors (`-q`)
+///
+/// Same constraints as `set_verbosity`.
+pub fn set_quiet() {
+    unsafe {
+        std::env::set_var(QUIET_ENV, "1");
+    }
+}
+
+/// Stdout for setup scripts and hooks run during installs and updates
+///
+/// Their output is status chatter like `info!`, so `--quiet` discards it.
+/// Stderr stays attached, since it carries their errors.
+pub fn child_stdout() -> std::process::Stdio {
+    if enabled(Level::Info) {
+        std::process::Stdio::inherit()
+    } else {
+        std::process::Stdio::null()
+    }
+}
+
+/// Print a status message on stdout (hidden by `--quiet`)
+#[macro_export]
+macro_rules! info {
+    ($($arg:tt)*) => {
+        if $crate::logging::enabled($crate::logging::Level::Info) {
+            println!("[voidbox] {}", format_args!($($arg)*));
+        }
+    };
+}
+
+/// Print a status message on stderr, keeping it out of an app's own output
+/// (hidden by `--quiet`)
+#[macro_export]
+macro_rules! notice {
+    ($($arg:tt)*) => {
+        if $crate::logging::enabled($crate::logging::Level::Info) {
+            eprintln!("[voidbox] {}", format_args!($($arg)*));
+        }
+    };
+}
+
+/// Print a warning on stderr (hidden by `--quiet`)
+#[macro_export]
+macro_rules! warn {
+    ($($arg:tt)*) => {
+        if $crate::logging::enabled($crate::logging::Level::Warn) {
+            eprintln!("[voidbox] {}", format_args!($($arg)*));
+        }
+    };
+}
+
+/// Print a message at debug level (`-v`)
+#[macro_export]
+macro_rules! debug {
+    ($($arg:tt)*) => {
+        if $crate::logging::enabled($crate::logging::Level::Debug) {
+            eprintln!("[voidbox] {}", format_args!($($arg)*));
+        }
+    };
+}
+
+/// Print a message at trace level (`-vv`)
+#[macro_export]
+macro_rules! trace {
+    ($($arg:tt)*) => {
+        if $crate::logging::enabled($crate::logging::Level::Trace) {
+            eprintln!("[voidbox] {}", format_args!($($arg)*));
+        }
+    };
+}
@@ -7,16 +7,19 @@
 //! 2. Launcher mode: when invoked as `void_brave`, `void_discord`, etc.
 //!    (uses argv[0] detection, similar to busybox)
 
-use clap::{Parser, Subcommand};
+use clap::{ArgAction, Parser, Subcommand};
+use std::net::IpAddr;
 use std::path::PathBuf;
+use std::time::Duration;
 
 use voidbox::bundle;
 use voidbox::cli;
 use voidbox::desktop::install_self;
 use voidbox::gui;
-use voidbox::manifest::PermissionConfig;
+use voidbox::manifest::{InstallSource, MountConfig, PermissionConfig};
 use voidbox::runtime::{
-    init_and_exec, setup_container_namespaces, setup_user_namespace, spawn_container_init,
+    ContainerConfig, DisplayBackend, init_and_exec, setup_container_namespaces,
+    setup_user_namespace, spawn_container_init,
 };
 use voidbox::storage::paths;
 
@@ -25,6 +28,29 @@ use voidbox::storage::paths;
 #[command(version = voidbox::VERSION)]
 #[command(about = "Universal Linux App Platform - portable, isolated application environments")]
 struct Cli {
+    /// Add ~/.local/bin to PATH in your shell rc file if it is missing
+    #[arg(long)]
+    fix_path: bool,
+
+    /// Skip all update checks and other optional network calls
+    /// (also enabled by VOIDBOX_OFFLINE=1)
+    #[arg(long, global = true)]
+    offline: bool,
+
+    /// Limit download speed in bytes per second, e.g. 500K or 2M
+    /// (also set by VOIDBOX_MAX_RATE)
+    #[arg(long, global = true, value_name = "RATE", value_parser = voidbox::storage::parse_rate)]
+    max_download_rate: Option<u64>,
+
+    /// Print more detail (-v for debug, -vv for every mount and apt step;
+    /// also set by VOIDBOX_LOG=debug|trace)
+    #[arg(short, long, action = ArgAction::Count, global = true)]
+    verbose: u8,
+
+    /// Only print errors (also enabled by VOIDBOX_QUIET=1)
+    #[arg(short, long, global = true, conflicts_with = "verbose")]
+    quiet: bool,
+
     #[command(subcommand)]
     command: Option<Commands>,
 }
@@ -39,6 +65,30 @@ enum Commands {
         /// Force reinstall even if already installed
         #[arg(long, short)]
         force: bool,
+
+        /// Install a specific version instead of the latest (pins the app)
+        #[arg(long)]
+        version: Option<String>,
+
+        /// Show what would be downloaded and installed without doing it
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Rebuild the shared dependency layer even if it already exists
+        #[arg(long)]
+        rebuild_deps: bool,
+
+        /// Bind-mount a local source directory at run time instead of copying it
+        #[arg(long)]
+        symlink_local: bool,
+
+        /// Install as a separate profile, run as <app>@<profile>
+        #[arg(long)]
+        profile: Option<String>,
+
+        /// Install for another architecture (amd64 or arm64; needs qemu binfmt to run)
+        #[arg(long)]
+        arch: Option<String>,
     },
 
     /// Remove an installed app
@@ -51,6 +101,56 @@ enum Commands {
         purge: bool,
     },
 
+    /// Reinstall an app from its stored manifest (keeps app data)
+    Reinstall {
+        /// App name to reinstall
+        app: String,
+
+        /// Also remove all app data
+        #[arg(long)]
+        purge: bool,
+    },
+
+    /// Rename an installed app or change its display name
+    Rename {
+        /// Current app name
+        app: String,
+
+        /// New app name (pass the current name to only change the display name)
+        new_name: String,
+
+        /// New display name shown in menus
+        #[arg(long)]
+        display_name: Option<String>,
+    },
+
+    /// Open an app's manifest in $EDITOR
+    Edit {
+        /// App name
+        app: String,
+    },
+
+    /// List running apps
+    Ps,
+
+    /// Stop a running app
+    Stop {
+        /// App name
+        app: String,
+    },
+
+    /// Hold an app at its current version (skipped by updates)
+    Freeze {
+        /// App name
+        app: String,
+    },
+
+    /// Let a frozen app be updated again
+    Unfreeze {
+        /// App name
+        app: String,
+    },
+
     /// Run an installed app
     Run {
         /// App name to run
@@ -64,13 +164,77 @@ enum Commands {
         #[arg(long)]
         dev: bool,
 
-        /// Additional arguments to pass to the app
+        /// Use native mode (host /usr, /lib and /etc, commands on the host)
+        #[arg(long)]
+        native: bool,
+
+        /// Set an environment variable in the container (repeatable)
+        #[arg(long = "env", value_name = "KEY=VALUE", value_parser = cli::parse_env_assignment)]
+        env: Vec<(String, String)>,
+
+        /// Save the app's output to its log (see `voidbox logs`)
+        #[arg(long)]
+        log: bool,
+
+        /// Run under X11 even on a Wayland session
+        #[arg(long, conflicts_with = "wayland_only")]
+        x11_only: bool,
+
+        /// Run under Wayland only (DISPLAY is unset)
+        #[arg(long)]
+        wayland_only: bool,
+
+        /// Use an empty home that is discarded when the app exits
+        #[arg(long)]
+        isolate_home: bool,
+
+        /// Nameserver for the container instead of the manifest's (repeatable)
+        #[arg(long = "dns", value_name = "IP")]
+        dns: Vec<IpAddr>,
+
+        /// Bind a host path into the container for this launch (repeatable)
+        #[arg(long = "bind", value_name = "SRC:DST[:ro]", value_parser = cli::parse_bind_mount)]
+        binds: Vec<MountConfig>,
+
+        /// Run on a virtual Xvfb display (for machines without a session)
+        #[arg(long, conflicts_with = "wayland_only")]
+        headless: bool,
+
+        /// Give the app only notifications and portals on the session bus
+        #[arg(long)]
+        dbus_proxy: bool,
+
+        /// Stop the app after this many seconds (exit code 124)
+        #[arg(long, value_name = "SECS")]
+        time_limit: Option<u64>,
+
+        /// Print the resolved binary, arguments and permissions without launching
+        #[arg(long)]
+        print_command: bool,
+
+        /// Extra argument for the app binary (repeatable)
+        #[arg(long = "arg", value_name = "ARG", allow_hyphen_values = true)]
+        extra_args: Vec<String>,
+
+        /// Run the arguments after `--` as a command instead of the app
+        #[arg(long)]
+        exec: bool,
+
+        /// Additional arguments to pass to the app (the command with --exec)
         #[arg(last = true)]
         args: Vec<String>,
     },
 
     /// List installed apps
-    List,
+    List {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+
+        /// Show the apps bundled with voidbox instead of installed ones
+        #[arg(long)]
+        available: bool,
+    },
 
     /// Update apps
     Update {
@@ -80,6 +244,10 @@ enum Commands {
         /// Force update even if already on latest
         #[arg(long, short)]
         force: bool,
+
+        /// Rebuild the app's dependency layers from its manifest
+        #[arg(long, requires = "app")]
+        rebuild_deps: bool,
     },
 
     /// Update voidbox itself
@@ -89,6 +257,17 @@ enum Commands {
         force: bool,
     },
 
+    /// Check that containers work on this machine
+    Selftest,
+
+    /// Accept install/list/remove requests from GUI frontends on a socket
+    #[command(hide = true)]
+    Serve {
+        /// Socket path (default: $XDG_RUNTIME_DIR/voidbox.sock)
+        #[arg(long)]
+        socket: Option<PathBuf>,
+    },
+
     /// Open a shell in an app's container
     Shell {
         /// App name
@@ -97,6 +276,14 @@ enum Commands {
         /// Enable developer mode (mount host tools)
         #[arg(long)]
         dev: bool,
+
+        /// Open the shell as an unprivileged user instead of container root
+        #[arg(long)]
+        user: bool,
+
+        /// Use native mode (host /usr, /lib and /etc, commands on the host)
+        #[arg(long)]
+        native: bool,
     },
 
     /// Show information about voidbox or a specific app
@@ -105,6 +292,36 @@ enum Commands {
         app: Option<String>,
     },
 
+    /// Check a manifest for errors and unreachable sources
+    Validate {
+        /// Manifest TOML file
+        manifest: PathBuf,
+    },
+
+    /// Show disk usage of installed apps and shared layers
+    Du {
+        /// Output as JSON
+        #[arg(long)]
+        json: bool,
+    },
+
+    /// Show the output captured from an app's last run
+    Logs {
+        /// App name
+        app: String,
+
+        /// Show the run before the latest one
+        #[arg(long)]
+        previous: bool,
+    },
+
+    /// Remove base images and dependency layers no longer used by any app
+    Clean {
+        /// Only report what would be removed
+        #[arg(long)]
+        dry_run: bool,
+    },
+
     /// Uninstall voidbox completely
     Uninstall {
         /// Also remove all app data
@@ -126,10 +343,32 @@ enum Commands {
         /// Serialized permissions JSON
         #[arg(long)]
         permissions: Option<String>,
+        /// Serialized container config JSON
+        #[arg(long)]
+        config: Option<String>,
         #[arg(last = true)]
         args: Vec<String>,
     },
 
+    /// Internal background update check started at launch (do not use manually)
+    #[command(hide = true)]
+    InternalUpdateCheck {
+        app: String,
+
+        /// Also show a desktop notification
+        #[arg(long)]
+        notify: bool,
+    },
+
+    /// Internal container steps of `selftest` (do not use manually)
+    #[command(hide = true)]
+    InternalSelftest {
+        rootfs: PathBuf,
+        /// Run as the PID namespace's init
+        #[arg(long)]
+        init: bool,
+    },
+
     /// Internal run command for setup scripts (do not use manually)
     #[command(hide = true)]
     InternalRun {
@@ -153,6 +392,10 @@ enum BundleCommands {
         /// Output .voidbox file
         #[arg(long, short)]
         output: Option<PathBuf>,
+
+        /// Sign the bundle with this key (from `bundle keygen`)
+        #[arg(long)]
+        sign_key: Option<PathBuf>,
     },
 
     /// Install from a .voidbox file
@@ -163,10 +406,37 @@ enum BundleCommands {
         /// Run the app after install
         #[arg(long)]
         run: bool,
+
+        /// Install even if the bundle is not signed by a trusted key
+        #[arg(long)]
+        allow_unsigned: bool,
+    },
+
+    /// Show a bundle's app, permissions and size without installing it
+    Info {
+        /// Bundle file path
+        bundle: PathBuf,
+    },
+
+    /// Create a key for signing bundles
+    Keygen {
+        /// Where to write the private key (the public key goes to <file>.pub)
+        key: PathBuf,
     },
 }
 
-fn main() -> Result<(), Box<dyn std::error::Error>> {
+fn main() {
+    if let Err(e) = run() {
+        let error = cli::RunnerError::from(e);
+        eprintln!("Error: {}", error);
+        if let Some(hint) = error.hint() {
+            eprintln!("[voidbox] {}", hint);
+        }
+        std::process::exit(error.exit_code());
+    }
+}
+
+fn run() -> Result<(), Box<dyn std::error::Error>> {
     // Check if we're running as a launcher (void_brave, void_discord, etc.)
     // This uses argv[0] detection similar to busybox
     if let Some(app_name) = cli::should_run_as_launcher() {
@@ -196,56 +466,188 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
 
     let cli = Cli::parse();
 
+    voidbox::logging::set_verbosity(cli.verbose);
+    if cli.quiet {
+        voidbox::logging::set_quiet();
+    }
+
+    if cli.offline {
+        // Exported so library code and re-spawned voidbox processes see it too
+        unsafe {
+            std::env::set_var(voidbox::storage::OFFLINE_ENV, "1");
+        }
+    }
+    if let Some(rate) = cli.max_download_rate {
+        unsafe {
+            std::env::set_var(voidbox::storage::MAX_RATE_ENV, rate.to_string());
+        }
+    }
+
+    // A dry-run install must not create anything on disk
+    let dry_run = matches!(cli.command, Some(Commands::Install { dry_run: true, .. }));
+
     // Ensure data directories exist
-    paths::ensure_dirs()?;
+    if !dry_run {
+        paths::ensure_dirs()?;
+    }
 
-    let command = cli.command.unwrap_or(Commands::List);
+    if cli.fix_path {
+        let (rc, changed) = voidbox::desktop::fix_path_in_shell_rc()?;
+        if changed {
+            voidbox::info!("Added ~/.local/bin to PATH in {}", rc.display());
+            voidbox::info!("Open a new terminal (or source the file) to apply it.");
+        } else {
+            voidbox::info!("PATH entry already present in {}", rc.display());
+        }
+        if cli.command.is_none() {
+            return Ok(());
+        }
+    }
 
-    // Self-install on first run (skip for internal commands)
-    if !matches!(
-        command,
-        Commands::InternalInit { .. } | Commands::InternalRun { .. }
-    ) {
+    let command = cli.command.unwrap_or(Commands::List {
+        json: false,
+        available: false,
+    });
+
+    // Self-install on first run (skip for internal commands and dry runs)
+    if !dry_run
+        && !matches!(
+            command,
+            Commands::InternalInit { .. }
+                | Commands::InternalRun { .. }
+                | Commands::InternalSelftest { .. }
+                | Commands::InternalUpdateCheck { .. }
+        )
+    {
         if !voidbox::desktop::is_installed() {
             if let Err(e) = install_self() {
-                eprintln!("[voidbox] Warning: Self-installation failed: {}", e);
-            } else if !paths::is_bin_dir_in_path() {
-                eprintln!(
-                    "[voidbox] Warning: {} is not in your PATH.",
-                    paths::bin_dir().display()
-                );
-                eprintln!(
-                    "[voidbox] Add this to your shell config: export PATH=\"$HOME/.local/bin:$PATH\""
-                );
+                voidbox::warn!("Warning: Self-installation failed: {}", e);
             }
         }
     }
 
     match command {
-        Commands::Install { source, force } => {
-            cli::install_app(&source, force)?;
+        Commands::Install {
+            source,
+            force,
+            version,
+            dry_run,
+            rebuild_deps,
+            symlink_local,
+            profile,
+            arch,
+        } => {
+            let options = cli::InstallOptions {
+                force,
+                version,
+                dry_run,
+                rebuild_deps,
+                symlink_local,
+                profile,
+                arch,
+                ..Default::default()
+            };
+            cli::install_app(&source, &options)?;
         }
 
         Commands::Remove { app, purge } => {
             cli::remove_app(&app, purge)?;
         }
 
+        Commands::Reinstall { app, purge } => {
+            cli::reinstall_app(&app, purge)?;
+        }
+
+        Commands::Rename {
+            app,
+            new_name,
+            display_name,
+        } => {
+            cli::rename_app(&app, &new_name, display_name.as_deref())?;
+        }
+
+        Commands::Edit { app } => {
+            cli::edit_manifest(&app)?;
+        }
+
+        Commands::Ps => {
+            cli::list_running()?;
+        }
+
+        Commands::Stop { app } => {
+            cli::stop_app(&app)?;
+        }
+
+        Commands::Freeze { app } => {
+            cli::set_app_frozen(&app, true)?;
+        }
+
+        Commands::Unfreeze { app } => {
+            cli::set_app_frozen(&app, false)?;
+        }
+
         Commands::Run {
             app,
             url,
             dev,
+            native,
+            env,
+            log,
+            x11_only,
+            wayland_only,
+            isolate_home,
+            dns,
+            binds,
+            headless,
+            dbus_proxy,
+            time_limit,
+            print_command,
+            extra_args,
+            exec,
             args,
         } => {
-            cli::run_app(&app, &args, url.as_deref(), dev)?;
+            let display = if x11_only {
+                Some(DisplayBackend::X11)
+            } else if wayland_only {
+                Some(DisplayBackend::Wayland)
+            } else {
+                None
+            };
+            let options = cli::RunOptions {
+                url,
+                extra_args,
+                exec,
+                dev_mode: dev,
+                native,
+                env,
+                log,
+                display,
+                dns,
+                isolate_home,
+                time_limit: time_limit.map(Duration::from_secs),
+                headless,
+                binds,
+                print_command,
+                dbus_proxy,
+            };
+            exit_on_failure(cli::run_app(&app, &args, &options)?);
         }
 
-        Commands::List => {
-            cli::list_apps()?;
+        Commands::List { json, available } => {
+            if available {
+                cli::list_available_apps(json)?;
+            } else {
+                cli::list_apps(json)?;
+            }
         }
 
-        Commands::Update { app, force } => match app {
+        Commands::Update {
+            app,
+            force,
+            rebuild_deps,
+        } => match app {
             Some(app_name) => {
-                let _ = cli::update_app(&app_name, force)?;
+                let _ = cli::update_app(&app_name, force, rebuild_deps)?;
             }
             None => cli::update_all(force)?,
         },
@@ -254,8 +656,21 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             cli::self_update(force)?;
         }
 
-        Commands::Shell { app, dev } => {
-            cli::shell(&app, dev)?;
+        Commands::Selftest => {
+            cli::self_test()?;
+        }
+
+        Commands::Serve { socket } => {
+            cli::serve(socket.as_deref())?;
+        }
+
+        Commands::Shell {
+            app,
+            dev,
+            user,
+            native,
+        } => {
+            cli::shell(&app, dev, user, native)?;
         }
 
         Commands::Info { app } => match app {
@@ -263,6 +678,22 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             None => cli::show_voidbox_info()?,
         },
 
+        Commands::Validate { manifest } => {
+            cli::validate_manifest_file(&manifest)?;
+        }
+
+        Commands::Du { json } => {
+            cli::disk_usage(json)?;
+        }
+
+        Commands::Logs { app, previous } => {
+            cli::show_logs(&app, previous)?;
+        }
+
+        Commands::Clean { dry_run } => {
+            cli::clean(dry_run)?;
+        }
+
         Commands::Uninstall { purge } => {
             uninstall_voidbox(purge)?;
         }
@@ -272,11 +703,24 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 manifest,
                 archive,
                 output,
+                sign_key,
+            } => {
+                cli::bundle_create(&manifest, &archive, output.as_deref(), sign_key.as_deref())?;
+            }
+            BundleCommands::Install {
+                bundle,
+                run,
+                allow_unsigned,
             } => {
-                cli::bundle_create(&manifest, &archive, output.as_deref())?;
+                if let Some(outcome) = cli::bundle_install(&bundle, run, allow_unsigned)? {
+                    exit_on_failure(outcome);
+                }
+            }
+            BundleCommands::Info { bundle } => {
+                cli::bundle_info(&bundle)?;
             }
-            BundleCommands::Install { bundle, run } => {
-                cli::bundle_install(&bundle, run)?;
+            BundleCommands::Keygen { key } => {
+                cli::bundle_keygen(&key)?;
             }
         },
 
@@ -284,15 +728,40 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
             rootfs,
             cmd,
             permissions,
+            config,
             args,
         } => {
             // This runs inside the new namespace after fork
-            // Parse permissions from JSON or use defaults
+            // Parse permissions from JSON or use defaults. A malformed value
+            // aborts: falling back to defaults would drop the app's sandbox
+            // settings without a word.
             let perms = match permissions {
-                Some(json) => serde_json::from_str(&json).unwrap_or_default(),
+                Some(json) => serde_json::from_str(&json)
+                    .map_err(|e| format!("Invalid container permissions: {}", e))?,
                 None => PermissionConfig::default(),
             };
-            init_and_exec(&rootfs, &cmd, &args, &perms)?;
+            let config = match config {
+                Some(json) => serde_json::from_str(&json)
+                    .map_err(|e| format!("Invalid container config: {}", e))?,
+                None => ContainerConfig::default(),
+            };
+            init_and_exec(&rootfs, &cmd, &args, &perms, &config)?;
+        }
+
+        Commands::InternalUpdateCheck { app, notify } => {
+            // Failures are expected when offline or rate limited - stay quiet
+            let _ = cli::print_update_notice(&app, notify);
+        }
+
+        Commands::InternalSelftest { rootfs, init } => {
+            let passed = if init {
+                cli::self_test_container(&rootfs)
+            } else {
+                cli::self_test_namespaces(&rootfs)?
+            };
+            if !passed {
+                std::process::exit(1);
+            }
         }
 
         Commands::InternalRun { rootfs, cmd, args } => {
@@ -311,12 +780,20 @@ fn main() -> Result<(), Box<dyn std::error::Error>> {
                 fonts: false,  // Don't mount fonts - let packages install
                 themes: false, // Don't mount themes/icons - let packages install
                 native_mode: false,
+                dbus_filter: false,
             };
             setup_user_namespace(permissions.native_mode)?;
             setup_container_namespaces()?;
 
             let self_exe = std::env::current_exe()?;
-            let status = spawn_container_init(&self_exe, &rootfs, &cmd, &args, &permissions)?;
+            let status = spawn_container_init(
+                &self_exe,
+                &rootfs,
+                &cmd,
+                &args,
+                &permissions,
+                &ContainerConfig::default(),
+            )?;
 
             if !status.success() {
                 std::process::exit(status.code().unwrap_or(1));
@@ -331,10 +808,11 @@ fn uninstall_voidbox(purge: bool) -> Result<(), Box<dyn std::error::Error>> {
     use voidbox::manifest::InstalledApp;
     use voidbox::storage::remove_dir_all_force;
 
+    // The prompt is shown even with --quiet, so its context is too
     if purge {
-        println!("[voidbox] This will remove voidbox and ALL app data.");
+        println!("This will remove voidbox and ALL app data.");
     } else {
-        println!("[voidbox] This will remove voidbox but keep app data.");
+        println!("This will remove voidbox but keep app data.");
     }
     print!("Continue? [y/N] ");
     std::io::Write::flush(&mut std::io::stdout())?;
@@ -343,11 +821,11 @@ fn uninstall_voidbox(purge: bool) -> Result<(), Box<dyn std::error::Error>> {
     std::io::stdin().read_line(&mut input)?;
 
     if input.trim().to_lowercase() != "y" {
-        println!("[voidbox] Uninstall cancelled.");
+        voidbox::info!("Uninstall cancelled.");
         return Ok(());
     }
 
-    println!("[voidbox] Uninstalling...");
+    voidbox::info!("Uninstalling...");
 
     // Remove install binary
     let install_path = paths::install_path();
@@ -428,27 +906,42 @@ fn uninstall_voidbox(purge: bool) -> Result<(), Box<dyn std::error::Error>> {
     }
 
     println!();
-    println!("[voidbox] Uninstall complete!");
+    voidbox::info!("Uninstall complete!");
 
     Ok(())
 }
 
 /// App launcher mode - triggered when invoked as void_<app>
 fn run_as_launcher(app_name: &str) -> Result<(), Box<dyn std::error::Error>> {
-    if let Err(e) = cli::run_launcher(app_name) {
-        if gui::is_gui_mode() {
-            gui::show_error(
-                &format!("Voidbox Error"),
-                &format!("Failed to launch {}:\n\n{}", app_name, e),
-            );
-        } else {
-            eprintln!("Error: {}", e);
+    match cli::run_launcher(app_name) {
+        Ok(outcome) => exit_on_failure(outcome),
+        Err(e) => {
+            let error = cli::RunnerError::from(Box::<dyn std::error::Error>::from(e));
+            let hint = error.hint().unwrap_or_default();
+            if gui::is_gui_mode() {
+                gui::show_error(
+                    &format!("Voidbox Error"),
+                    &format!("Failed to launch {}:\n\n{}\n\n{}", app_name, error, hint),
+                );
+            } else {
+                eprintln!("Error: {}", error);
+                if !hint.is_empty() {
+                    eprintln!("[voidbox] {}", hint);
+                }
+            }
+            std::process::exit(error.exit_code());
         }
-        std::process::exit(1);
     }
     Ok(())
 }
 
+/// Exit with the app's status if it failed, like a direct launch would
+fn exit_on_failure(outcome: cli::RunOutcome) {
+    if !outcome.success() {
+        std::process::exit(outcome.exit_code);
+    }
+}
+
 /// GUI installation mode - triggered when double-clicking the binary
 fn gui_install_mode() -> Result<(), Box<dyn std::error::Error>> {
     use voidbox::desktop;
@@ -500,36 +993,33 @@ fn gui_bundle_install_mode(
 fn cli_bundle_install_mode(
     info: bundle::BundleManifestInfo,
 ) -> Result<(), Box<dyn std::error::Error>> {
-    use voidbox::storage::paths;
+    let bundle_data = bundle::extract_embedded_bundle(&bundle::load_trusted_keys()?)?
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::NotFound, "Bundle data missing"))?;
+    cli::check_embedded_signature(&bundle_data.signature)?;
 
     if !voidbox::desktop::is_installed() {
         install_self()?;
-        if !paths::is_bin_dir_in_path() {
-            eprintln!(
-                "[voidbox] Warning: {} is not in your PATH.",
-                paths::bin_dir().display()
-            );
-            eprintln!(
-                "[voidbox] Add this to your shell config: export PATH=\"$HOME/.local/bin:$PATH\""
-            );
-        }
     }
 
-    println!("[voidbox] Installing {}...", info.display_name);
-    let bundle_data = bundle::extract_embedded_bundle()?.ok_or_else(|| {
-        std::io::Error::new(std::io::ErrorKind::NotFound, "Bundle data missing")
-    })?;
+    voidbox::info!("Installing {}...", info.display_name);
+    let options = cli::InstallOptions {
+        source: Some(InstallSource::Bundle {
+            path: std::env::current_exe()?,
+        }),
+        ..Default::default()
+    };
     let install_result = cli::install_app_from_bundle(
         &bundle_data.manifest_content,
         &bundle_data.archive_path,
         &bundle_data.archive_ext,
-        false,
+        &options,
     );
-    bundle_data.cleanup();
+    drop(bundle_data);
     install_result?;
-    println!(
-        "[voidbox] {} installed. Run with: voidbox run {}",
-        info.display_name, info.app_name
+    voidbox::info!(
+        "{} installed. Run with: voidbox run {}",
+        info.display_name,
+        info.app_name
     );
     Ok(())
 }
@@ -1,8 +1,16 @@
 //! Manifest parsing functions
+//!
+//! A manifest may start from another one with a top-level
+//! `extends = "<path or URL>"`. The base is loaded first and the manifest is
+//! merged over it: tables are merged key by key, everything else (arrays
+//! included) is replaced. Relative paths are resolved against the including
+//! manifest's location.
 
 use super::schema::AppManifest;
-use std::path::Path;
+use crate::storage::http_get;
+use std::path::{Path, PathBuf};
 use thiserror::Error;
+use toml::Table;
 
 #[derive(Error, Debug)]
 pub enum ManifestError {
@@ -19,16 +27,39 @@ pub enum ManifestError {
     NotFound(String),
 }
 
+/// How many `extends` a manifest may chain through
+const MAX_EXTENDS_DEPTH: usize = 8;
+
+/// Where a manifest was loaded from, for resolving relative `extends`
+enum Origin {
+    File(PathBuf),
+    Url(String),
+}
+
 /// Parse a manifest from a TOML file
 pub fn parse_manifest_file(path: &Path) -> Result<AppManifest, ManifestError> {
     let content = std::fs::read_to_string(path)?;
-    parse_manifest_str(&content)
+    let path = path.canonicalize()?;
+    let mut chain = vec![path.display().to_string()];
+    let table = resolve_extends(&content, &Origin::File(path), &mut chain)?;
+    Ok(table.try_into()?)
 }
 
 /// Parse a manifest from a TOML string
+///
+/// Strings come from bundles, launchers and saved copies, which have no
+/// location to resolve `extends` against and must not fetch content a
+/// bundle signature doesn't cover, so `extends` is rejected.
 pub fn parse_manifest_str(content: &str) -> Result<AppManifest, ManifestError> {
-    let manifest: AppManifest = toml::from_str(content)?;
-    Ok(manifest)
+    let table: Table = toml::from_str(content)?;
+    if table.contains_key("extends") {
+        return Err(ManifestError::ValidationError(
+            "extends is only supported in manifest files and URLs; \
+             bundled and embedded manifests must be self-contained"
+                .into(),
+        ));
+    }
+    Ok(table.try_into()?)
 }
 
 /// Parse a manifest from a TOML string (alias for convenience)
@@ -38,15 +69,140 @@ pub fn parse_manifest(content: &str) -> Result<AppManifest, ManifestError> {
 
 /// Parse a manifest from a URL
 pub fn parse_manifest_url(url: &str) -> Result<AppManifest, ManifestError> {
-    let mut resp = ureq::get(url)
-        .header("User-Agent", crate::APP_NAME)
+    let content = fetch_manifest(url)?;
+    let mut chain = vec![url.to_string()];
+    let origin = Origin::Url(url.to_string());
+    let table = resolve_extends(&content, &origin, &mut chain)?;
+    Ok(table.try_into()?)
+}
+
+/// Manifest file contents with any `extends` chain merged in
+///
+/// For copies that must stand on their own, e.g. inside a bundle. A manifest
+/// without `extends` is returned as written.
+pub fn flatten_manifest_file(path: &Path) -> Result<String, ManifestError> {
+    let content = std::fs::read_to_string(path)?;
+    let table: Table = toml::from_str(&content)?;
+    if !table.contains_key("extends") {
+        return Ok(content);
+    }
+
+    let path = path.canonicalize()?;
+    let mut chain = vec![path.display().to_string()];
+    let table = resolve_extends(&content, &Origin::File(path), &mut chain)?;
+    toml::to_string_pretty(&table)
+        .map_err(|e| ManifestError::ValidationError(format!("Failed to serialize: {}", e)))
+}
+
+fn fetch_manifest(url: &str) -> Result<String, ManifestError> {
+    let mut resp = http_get(url)
         .call()
         .map_err(|e| ManifestError::ValidationError(format!("HTTP error: {}", e)))?;
 
-    let content = resp
-        .body_mut()
+    resp.body_mut()
         .read_to_string()
-        .map_err(|e| ManifestError::ValidationError(format!("Failed to read response: {}", e)))?;
+        .map_err(|e| ManifestError::ValidationError(format!("Failed to read response: {}", e)))
+}
+
+/// Parse `content` and merge it over the manifests it extends
+///
+/// `chain` holds the locations being loaded, to catch cycles.
+fn resolve_extends(
+    content: &str,
+    origin: &Origin,
+    chain: &mut Vec<String>,
+) -> Result<Table, ManifestError> {
+    let mut table: Table = toml::from_str(content)?;
+    let Some(extends) = table.remove("extends") else {
+        return Ok(table);
+    };
+    let extends = extends.as_str().ok_or_else(|| {
+        ManifestError::ValidationError("extends must be a path or URL string".into())
+    })?;
+
+    if chain.len() > MAX_EXTENDS_DEPTH {
+        return Err(ManifestError::ValidationError(format!(
+            "extends is nested more than {} levels deep",
+            MAX_EXTENDS_DEPTH
+        )));
+    }
+
+    let base_origin = resolve_location(extends, origin)?;
+    let location = match &base_origin {
+        Origin::File(path) => path.display().to_string(),
+        Origin::Url(url) => url.clone(),
+    };
+    if chain.contains(&location) {
+        return Err(ManifestError::ValidationError(format!(
+            "extends cycle: {} -> {}",
+            chain.join(" -> "),
+            location
+        )));
+    }
+
+    let base_content = match &base_origin {
+        Origin::File(path) => std::fs::read_to_string(path)?,
+        Origin::Url(url) => fetch_manifest(url)?,
+    };
+    chain.push(location);
+    let mut base = resolve_extends(&base_content, &base_origin, chain)?;
+    chain.pop();
+
+    merge_tables(&mut base, table);
+    Ok(base)
+}
+
+/// Where `extends` points, relative to the manifest that contains it
+fn resolve_location(extends: &str, origin: &Origin) -> Result<Origin, ManifestError> {
+    if extends.starts_with("http://") || extends.starts_with("https://") {
+        return Ok(Origin::Url(extends.to_string()));
+    }
+
+    let path = match origin {
+        Origin::Url(url) => {
+            // Relative to the directory part of the URL
+            let base = url.rsplit_once('/').map_or(url.as_str(), |(dir, _)| dir);
+            return Ok(Origin::Url(format!(
+                "{}/{}",
+                base,
+                extends.trim_start_matches("./")
+            )));
+        }
+        Origin::File(file) => file.parent().unwrap_or(Path::new("/")).join(extends),
+    };
+    let path = path
+        .canonicalize()
+        .map_err(|_| ManifestError::NotFound(path.display().to_string()))?;
+    Ok(Origin::File(path))
+}
+
+/// Merge `over` into `base`: tables recursively, other values replaced
+fn merge_tables(base: &mut Table, over: Table) {
+    for (key, value) in over {
+        match (base.get_mut(&key), value) {
+            (Some(toml::Value::Table(base_table)), toml::Value::Table(over_table)) => {
+                merge_tables(base_table, over_table);
+            }
+            (_, value) => {
+                base.insert(key, value);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn manifest_strings_reject_extends() {
+        let content = r#"
+            extends = "https://example.com/base.toml"
 
-    parse_manifest_str(&content)
+            [app]
+            name = "demo"
+        "#;
+        let err = parse_manifest_str(content).unwrap_err();
+        assert!(err.to_string().contains("extends"), "{}", err);
+    }
 }
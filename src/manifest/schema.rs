@@ -1,6 +1,8 @@
 //! Manifest schema definitions
 
 use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::net::IpAddr;
 use std::path::PathBuf;
 
 /// Complete app manifest structure
@@ -16,6 +18,31 @@ pub struct AppManifest {
     pub desktop: DesktopConfig,
     #[serde(default)]
     pub permissions: PermissionConfig,
+    #[serde(default)]
+    pub bridge: BridgeConfig,
+    #[serde(default)]
+    pub environment: BTreeMap<String, String>,
+    #[serde(default)]
+    pub hooks: HooksConfig,
+    #[serde(default)]
+    pub security: SecurityConfig,
+    #[serde(default)]
+    pub mounts: Vec<MountConfig>,
+}
+
+impl AppManifest {
+    /// Copy of the manifest installed as the separate profile `<name>@<profile>`
+    ///
+    /// Profiles share the base image and dependency layer but get their own
+    /// app directory, launchers and persistent home, so their data stays apart.
+    pub fn with_profile(&self, profile: &str) -> Self {
+        let mut manifest = self.clone();
+        let base_name = self.app.name.split('@').next().unwrap_or_default();
+        manifest.app.name = format!("{}@{}", base_name, profile);
+        manifest.app.display_name = format!("{} ({})", self.app.display_name, profile);
+        manifest.runtime.persist_home = true;
+        manifest
+    }
 }
 
 /// Basic app information
@@ -48,11 +75,25 @@ pub enum SourceConfig {
         #[serde(default)]
         asset_extension: Option<String>,
     },
+    /// GitLab releases (gitlab.com)
+    Gitlab {
+        /// Numeric project ID or "group/project" path
+        project_id: String,
+        #[serde(default = "default_linux")]
+        asset_os: String,
+        #[serde(default = "default_arch")]
+        asset_arch: String,
+        #[serde(default)]
+        asset_extension: Option<String>,
+    },
     /// Direct download URL
     Direct {
         url: String,
         #[serde(default)]
         version_url: Option<String>,
+        /// Dotted path to the version in a JSON `version_url` response (e.g. "data.tag")
+        #[serde(default)]
+        version_json_path: Option<String>,
         #[serde(default)]
         archive_type: Option<String>,
     },
@@ -70,10 +111,20 @@ fn default_linux() -> String {
 }
 
 fn default_arch() -> String {
-    match std::env::consts::ARCH {
-        "x86_64" => "amd64".to_string(),
-        "aarch64" => "arm64".to_string(),
-        _ => "amd64".to_string(),
+    host_ubuntu_arch().unwrap_or("amd64").to_string()
+}
+
+/// Host architecture in Ubuntu/Debian naming (None if unsupported)
+pub fn host_ubuntu_arch() -> Option<&'static str> {
+    ubuntu_arch_name(std::env::consts::ARCH)
+}
+
+/// Map an architecture name (Rust or Ubuntu style) to Ubuntu naming
+pub fn ubuntu_arch_name(arch: &str) -> Option<&'static str> {
+    match arch {
+        "x86_64" | "amd64" => Some("amd64"),
+        "aarch64" | "arm64" => Some("arm64"),
+        _ => None,
     }
 }
 
@@ -84,17 +135,86 @@ pub struct RuntimeConfig {
     pub base: String,
     #[serde(default)]
     pub arch: Vec<String>,
+    /// Forward later launches to the running container instead of starting another
+    #[serde(default)]
+    pub single_instance: bool,
+    /// Give the app its own persistent home under the app directory
+    /// instead of mounting the host home
+    #[serde(default)]
+    pub persist_home: bool,
+    /// Run the app as an unprivileged user instead of container root
+    /// (avoids "running as root" warnings)
+    #[serde(default)]
+    pub run_as_user: bool,
+    /// Use the host's Wayland session when available (false forces X11)
+    #[serde(default = "default_true")]
+    pub wayland: bool,
+    /// Chromium/Electron app: pass `--ozone-platform` flags matching the
+    /// display backend
+    #[serde(default)]
+    pub chromium: bool,
+    /// The binary is a launcher that forks the real app and exits: report the
+    /// exit code of the last process to finish instead
+    #[serde(default)]
+    pub wait_all: bool,
+    /// Nameservers written to the container's /etc/resolv.conf (empty = the
+    /// host's resolv.conf copied into the base image)
+    #[serde(default)]
+    pub dns: Vec<String>,
+    /// Host library directories bound read-only at the same path instead of
+    /// installing the packages that provide them (ties the app to the
+    /// host's library versions)
+    #[serde(default)]
+    pub extra_packages_from_host: Vec<String>,
+    /// Hostname inside the container (unset = "voidbox"; native mode keeps
+    /// the host's)
+    #[serde(default)]
+    pub hostname: Option<String>,
 }
 
 fn default_base() -> String {
     "ubuntu:24.04".to_string()
 }
 
+impl RuntimeConfig {
+    /// Ubuntu release pinned by `base` (e.g. "24.04" for "ubuntu:24.04");
+    /// None for "ubuntu" or "ubuntu:latest"
+    pub fn ubuntu_version(&self) -> Option<&str> {
+        self.base
+            .strip_prefix("ubuntu:")
+            .filter(|version| *version != "latest")
+    }
+
+    /// Check whether the app runs on the given arch (empty list = any)
+    pub fn supports_arch(&self, arch: &str) -> bool {
+        let wanted = ubuntu_arch_name(arch);
+        self.arch.is_empty()
+            || self
+                .arch
+                .iter()
+                .any(|a| wanted.is_some() && ubuntu_arch_name(a) == wanted)
+    }
+
+    /// Parsed `dns` nameservers (invalid entries are rejected by validation)
+    pub fn dns_servers(&self) -> Vec<IpAddr> {
+        self.dns.iter().filter_map(|s| s.parse().ok()).collect()
+    }
+}
+
 impl Default for RuntimeConfig {
     fn default() -> Self {
         Self {
             base: default_base(),
             arch: vec!["x86_64".to_string(), "aarch64".to_string()],
+            single_instance: false,
+            persist_home: false,
+            run_as_user: false,
+            wayland: true,
+            chromium: false,
+            wait_all: false,
+            dns: Vec::new(),
+            extra_packages_from_host: Vec::new(),
+            hostname: None,
         }
     }
 }
@@ -106,6 +226,26 @@ pub struct DependencyConfig {
     pub packages: Vec<String>,
     #[serde(default)]
     pub shared: Vec<String>,
+    /// Extra apt repositories for `packages` (not used for `shared`)
+    #[serde(default)]
+    pub repos: Vec<AptRepo>,
+}
+
+/// Third-party apt repository (`[[dependencies.repos]]`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AptRepo {
+    /// Repository base URL, e.g. "https://packages.example.com/apt"
+    pub uri: String,
+    /// Suite (distribution), e.g. "stable"; a path ending in `/` for flat repos
+    pub suite: String,
+    #[serde(default)]
+    pub components: Vec<String>,
+    /// HTTPS URL of the repository's signing key (armored or binary)
+    #[serde(default)]
+    pub key_url: Option<String>,
+    /// Full fingerprint of the signing key, fetched from keyserver.ubuntu.com
+    #[serde(default)]
+    pub key_fingerprint: Option<String>,
 }
 
 /// Binary configuration
@@ -116,8 +256,14 @@ pub struct BinaryConfig {
     pub path: Option<String>,
     #[serde(default)]
     pub args: Vec<String>,
+    /// Extra args used only when a URL is opened (`run --url`)
+    #[serde(default)]
+    pub url_args: Vec<String>,
     #[serde(default)]
     pub install_dir: Option<String>,
+    /// How many directory levels to search for the binary (default 5)
+    #[serde(default)]
+    pub search_depth: Option<usize>,
 }
 
 /// Desktop entry configuration
@@ -133,6 +279,17 @@ pub struct DesktopConfig {
     pub mime_types: Vec<String>,
     #[serde(default)]
     pub keywords: Vec<String>,
+    #[serde(default)]
+    pub actions: Vec<DesktopAction>,
+}
+
+/// Extra launcher action shown in the app's right-click menu
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DesktopAction {
+    pub id: String,
+    pub name: String,
+    #[serde(default)]
+    pub args: Vec<String>,
 }
 
 /// Permission configuration - all default to true (open by default)
@@ -164,6 +321,10 @@ pub struct PermissionConfig {
     /// The app runs like a normal process but with its own rootfs overlayed
     #[serde(default)]
     pub native_mode: bool,
+    /// Reach the session bus only through a filtering xdg-dbus-proxy that
+    /// allows notifications and desktop portals
+    #[serde(default)]
+    pub dbus_filter: bool,
 }
 
 fn default_true() -> bool {
@@ -185,10 +346,64 @@ impl Default for PermissionConfig {
             fonts: true,
             themes: true,
             native_mode: false,
+            dbus_filter: false,
         }
     }
 }
 
+/// Host bridge configuration (native mode only)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct BridgeConfig {
+    /// Commands the container may run on the host, matched exactly against
+    /// the first word of the request. An empty list allows any command.
+    #[serde(default)]
+    pub allowed_commands: Vec<String>,
+}
+
+/// Install hook scripts (run as root inside the sandbox)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct HooksConfig {
+    /// Shell snippet run before the app archive is extracted
+    #[serde(default)]
+    pub pre_install: Option<String>,
+    /// Shell snippet run after the binary symlink is created
+    #[serde(default)]
+    pub post_install: Option<String>,
+}
+
+/// Extra host path bound into the sandbox (`[[mounts]]`)
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct MountConfig {
+    /// Host path; a leading `~` and `$HOME` expand to the user's home
+    pub source: String,
+    /// Path inside the container, relative to its root (a leading `/` is
+    /// ignored); expanded like `source`
+    pub target: String,
+    #[serde(default = "default_true")]
+    pub readonly: bool,
+}
+
+/// Sandbox hardening options
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct SecurityConfig {
+    /// Syscall filter applied to the app
+    #[serde(default)]
+    pub seccomp: SeccompProfile,
+}
+
+/// Seccomp syscall filter profile
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum SeccompProfile {
+    /// Block debugging, keyring, mount and kernel-tampering syscalls
+    #[default]
+    Default,
+    /// Also block namespaces, bpf, perf and io_uring
+    Strict,
+    /// No filter (for apps that break under the default profile)
+    Off,
+}
+
 /// Archive type for the app distribution
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
 #[serde(rename_all = "lowercase")]
@@ -198,6 +413,10 @@ pub enum ArchiveType {
     TarGz,
     TarXz,
     TarZst,
+    /// Debian package, installed with dpkg instead of extracted
+    Deb,
+    /// AppImage, unpacked with its own `--appimage-extract`
+    AppImage,
 }
 
 impl ArchiveType {
@@ -207,6 +426,8 @@ impl ArchiveType {
             "tar.gz" | "tgz" => Some(Self::TarGz),
             "tar.xz" | "txz" => Some(Self::TarXz),
             "tar.zst" | "tzst" => Some(Self::TarZst),
+            "deb" => Some(Self::Deb),
+            "AppImage" | "appimage" => Some(Self::AppImage),
             _ => None,
         }
     }
@@ -217,6 +438,8 @@ impl ArchiveType {
             Self::TarGz => ".tar.gz",
             Self::TarXz => ".tar.xz",
             Self::TarZst => ".tar.zst",
+            Self::Deb => ".deb",
+            Self::AppImage => ".AppImage",
         }
     }
 }
@@ -230,4 +453,61 @@ pub struct InstalledApp {
     pub base_version: Option<String>,
     pub installed_date: Option<String>,
     pub manifest_path: Option<PathBuf>,
+    /// Version the user pinned at install time (skipped by updates)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pinned_version: Option<String>,
+    /// Local source directory bind-mounted at run time (`--symlink-local`)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub linked_source: Option<PathBuf>,
+    /// Held at its current version by `voidbox freeze` (skipped by updates)
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub frozen: bool,
+    /// Where the app was installed from (kept across updates and reinstalls)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<InstallSource>,
+}
+
+/// Where an installed app came from
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "lowercase")]
+pub enum InstallSource {
+    /// Manifest downloaded from a URL
+    Url { url: String },
+    /// Manifest file on this machine
+    File { path: PathBuf },
+    /// Self-extracting `.voidbox` bundle
+    Bundle { path: PathBuf },
+    /// Manifest embedded in a `void_<app>` launcher (recorded by older
+    /// versions, whose path pointed at voidbox itself)
+    Launcher { path: PathBuf },
+    /// Manifest embedded in voidbox and installed through its launcher
+    Embedded {
+        app: String,
+        voidbox_version: String,
+    },
+}
+
+impl InstallSource {
+    /// Source for the embedded manifest of `app` in this voidbox build
+    pub fn embedded(app: &str) -> Self {
+        Self::Embedded {
+            app: app.to_string(),
+            voidbox_version: crate::VERSION.to_string(),
+        }
+    }
+}
+
+impl std::fmt::Display for InstallSource {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Url { url } => write!(f, "{}", url),
+            Self::File { path } => write!(f, "file {}", path.display()),
+            Self::Bundle { path } => write!(f, "bundle {}", path.display()),
+            Self::Launcher { path } => write!(f, "launcher {}", path.display()),
+            Self::Embedded {
+                app,
+                voidbox_version,
+            } => write!(f, "{} launcher (voidbox v{})", app, voidbox_version),
+        }
+    }
 }
@@ -1,7 +1,9 @@
 //! Manifest validation
 
 use super::ManifestError;
-use super::schema::AppManifest;
+use super::schema::{AppManifest, AptRepo, MountConfig, SourceConfig};
+use std::net::IpAddr;
+use std::path::{Component, Path};
 
 /// Validate a manifest for completeness and correctness
 pub fn validate_manifest(manifest: &AppManifest) -> Result<(), ManifestError> {
@@ -24,17 +26,431 @@ pub fn validate_manifest(manifest: &AppManifest) -> Result<(), ManifestError> {
         ));
     }
 
-    // Validate app name (lowercase, alphanumeric, hyphens only)
-    if !manifest
-        .app
-        .name
-        .chars()
-        .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
-    {
+    // Validate app name (lowercase, alphanumeric, hyphens only), with an
+    // optional `@profile` suffix following the same rules
+    let (name, profile) = match manifest.app.name.split_once('@') {
+        Some((name, profile)) => (name, Some(profile)),
+        None => (manifest.app.name.as_str(), None),
+    };
+    if !is_valid_name(name) {
         return Err(ManifestError::ValidationError(
             "app.name must be lowercase alphanumeric with hyphens only".into(),
         ));
     }
+    if profile.is_some_and(|profile| !is_valid_name(profile)) {
+        return Err(ManifestError::ValidationError(
+            "profile names must be lowercase alphanumeric with hyphens only".into(),
+        ));
+    }
+
+    // Binary name is joined onto the install dir, so it must be a plain name
+    if manifest.binary.name.contains('/') {
+        return Err(ManifestError::ValidationError(format!(
+            "binary.name must not contain path separators: {}",
+            manifest.binary.name
+        )));
+    }
+
+    if let Some(version) = &manifest.app.version {
+        semver::Version::parse(version.trim_start_matches('v')).map_err(|e| {
+            ManifestError::ValidationError(format!(
+                "app.version is not a valid semver version ({}): {}",
+                version, e
+            ))
+        })?;
+    }
+
+    for mount in &manifest.mounts {
+        validate_mount(mount)?;
+    }
+
+    for repo in &manifest.dependencies.repos {
+        validate_repo(repo)?;
+    }
+
+    let base = &manifest.runtime.base;
+    let base_ok = base == "ubuntu"
+        || base.strip_prefix("ubuntu:").is_some_and(|version| {
+            version == "latest"
+                || (version.starts_with(|c: char| c.is_ascii_digit())
+                    && version.chars().all(|c| c.is_ascii_digit() || c == '.'))
+        });
+    if !base_ok {
+        return Err(ManifestError::ValidationError(format!(
+            "runtime.base must be \"ubuntu:<version>\" or \"ubuntu:latest\": {}",
+            base
+        )));
+    }
+
+    for server in &manifest.runtime.dns {
+        server.parse::<IpAddr>().map_err(|_| {
+            ManifestError::ValidationError(format!(
+                "runtime.dns entries must be IP addresses: {}",
+                server
+            ))
+        })?;
+    }
+
+    for dir in &manifest.runtime.extra_packages_from_host {
+        validate_host_library_dir(dir)?;
+    }
+
+    let hostname = manifest.runtime.hostname.as_deref();
+    if let Some(hostname) = hostname.filter(|name| !is_valid_hostname(name)) {
+        return Err(ManifestError::ValidationError(format!(
+            "runtime.hostname must be a valid host name (letters, digits and \
+             hyphens, at most 64 characters): {}",
+            hostname
+        )));
+    }
+
+    validate_source(&manifest.source)
+}
+
+/// Check a host name against RFC 1123 and the kernel's 64-byte limit
+///
+/// Dot-separated labels of 1-63 letters, digits and hyphens, none starting
+/// or ending with a hyphen.
+fn is_valid_hostname(hostname: &str) -> bool {
+    hostname.len() <= 64
+        && hostname.split('.').all(|label| {
+            !label.is_empty()
+                && label.len() <= 63
+                && !label.starts_with('-')
+                && !label.ends_with('-')
+                && label.chars().all(|c| c.is_ascii_alphanumeric() || c == '-')
+        })
+}
 
+/// Directories `runtime.extra_packages_from_host` may name (or lie under)
+const HOST_LIBRARY_ROOTS: &[&str] = &[
+    "/lib",
+    "/lib64",
+    "/usr/lib",
+    "/usr/lib64",
+    "/usr/local/lib",
+    "/usr/share",
+];
+
+/// Check a host library dir: an absolute path at or below a library root
+pub fn validate_host_library_dir(dir: &str) -> Result<(), ManifestError> {
+    let path = Path::new(dir);
+    let allowed = path.is_absolute()
+        && !path.components().any(|c| c == Component::ParentDir)
+        && HOST_LIBRARY_ROOTS.iter().any(|root| path.starts_with(root));
+    if !allowed {
+        return Err(ManifestError::ValidationError(format!(
+            "runtime.extra_packages_from_host entries must be absolute paths under {}: {}",
+            HOST_LIBRARY_ROOTS.join(", "),
+            dir
+        )));
+    }
     Ok(())
 }
+
+/// Check an app or profile name (non-empty, lowercase alphanumeric, hyphens)
+fn is_valid_name(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '-')
+}
+
+/// Validate an extra mount: the target must stay inside the rootfs
+pub fn validate_mount(mount: &MountConfig) -> Result<(), ManifestError> {
+    if mount.source.trim().is_empty() {
+        return Err(ManifestError::ValidationError(
+            "mounts.source is required".into(),
+        ));
+    }
+
+    let target = Path::new(mount.target.trim_start_matches('/'));
+    if target.as_os_str().is_empty() {
+        return Err(ManifestError::ValidationError(format!(
+            "mounts.target must not be the container root: {}",
+            mount.target
+        )));
+    }
+    if target.components().any(|c| c == Component::ParentDir) {
+        return Err(ManifestError::ValidationError(format!(
+            "mounts.target must not contain '..': {}",
+            mount.target
+        )));
+    }
+
+    Ok(())
+}
+
+/// Validate an apt repository entry
+///
+/// Values end up in a sources file, so they must be single words.
+fn validate_repo(repo: &AptRepo) -> Result<(), ManifestError> {
+    let invalid = |message: String| Err(ManifestError::ValidationError(message));
+    let is_word = |s: &str| !s.is_empty() && !s.chars().any(|c| c.is_whitespace() || c == '"');
+
+    if !(repo.uri.starts_with("https://") || repo.uri.starts_with("http://")) || !is_word(&repo.uri)
+    {
+        return invalid(format!(
+            "dependencies.repos.uri must be an http(s) URL: {}",
+            repo.uri
+        ));
+    }
+    if !is_word(&repo.suite) {
+        return invalid(format!(
+            "dependencies.repos.suite must be a single word: {:?}",
+            repo.suite
+        ));
+    }
+    if let Some(component) = repo.components.iter().find(|c| !is_word(c)) {
+        return invalid(format!(
+            "dependencies.repos.components must be single words: {:?}",
+            component
+        ));
+    }
+
+    match (&repo.key_url, &repo.key_fingerprint) {
+        (Some(url), None) => {
+            if !url.starts_with("https://") || !is_word(url) {
+                return invalid(format!(
+                    "dependencies.repos.key_url must be an https URL: {}",
+                    url
+                ));
+            }
+        }
+        (None, Some(fingerprint)) => {
+            let hex: String = fingerprint.chars().filter(|c| *c != ' ').collect();
+            if hex.len() != 40 || !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+                return invalid(format!(
+                    "dependencies.repos.key_fingerprint must be a full 40-digit fingerprint: {}",
+                    fingerprint
+                ));
+            }
+        }
+        _ => {
+            return invalid(format!(
+                "dependencies.repos for {} needs exactly one of key_url or key_fingerprint",
+                repo.uri
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Validate source-specific fields
+fn validate_source(source: &SourceConfig) -> Result<(), ManifestError> {
+    match source {
+        SourceConfig::Github { owner, repo, .. } => {
+            if owner.trim().is_empty() || repo.trim().is_empty() {
+                return Err(ManifestError::ValidationError(
+                    "source.owner and source.repo are required for github sources".into(),
+                ));
+            }
+        }
+        SourceConfig::Gitlab { project_id, .. } => {
+            if project_id.trim().is_empty() {
+                return Err(ManifestError::ValidationError(
+                    "source.project_id is required for gitlab sources".into(),
+                ));
+            }
+        }
+        SourceConfig::Direct { url, .. } => {
+            if !url.starts_with("https://") && !url.starts_with("http://") {
+                return Err(ManifestError::ValidationError(format!(
+                    "source.url must be an http(s) URL: {}",
+                    url
+                )));
+            }
+        }
+        SourceConfig::Local { .. } => {}
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manifest::parse_manifest_str;
+
+    const VALID: &str = r#"
+        [app]
+        name = "demo"
+        display_name = "Demo"
+        version = "1.2.3"
+
+        [source]
+        type = "github"
+        owner = "example"
+        repo = "demo"
+
+        [runtime]
+        base = "ubuntu:24.04"
+
+        [binary]
+        name = "demo"
+    "#;
+
+    fn valid() -> AppManifest {
+        parse_manifest_str(VALID).unwrap()
+    }
+
+    fn source(fields: &str) -> SourceConfig {
+        toml::from_str(fields).unwrap()
+    }
+
+    fn mount(source: &str, target: &str) -> MountConfig {
+        toml::from_str(&format!("source = {:?}\ntarget = {:?}", source, target)).unwrap()
+    }
+
+    fn repo(fields: &str) -> AptRepo {
+        toml::from_str(fields).unwrap()
+    }
+
+    const KEY: &str = r#"key_url = "https://example.com/key.asc""#;
+
+    /// Expected error and how to break a valid manifest to get it
+    type Case = (&'static str, fn(&mut AppManifest));
+
+    fn assert_rejected(cases: &[Case]) {
+        for (expected, break_manifest) in cases {
+            let mut manifest = valid();
+            break_manifest(&mut manifest);
+            let error = validate_manifest(&manifest)
+                .expect_err(expected)
+                .to_string();
+            assert!(
+                error.contains(expected),
+                "expected {:?}, got {:?}",
+                expected,
+                error
+            );
+        }
+    }
+
+    #[test]
+    fn valid_manifest_passes() {
+        validate_manifest(&valid()).unwrap();
+    }
+
+    #[test]
+    fn each_invalid_field_is_reported() {
+        assert_rejected(&[
+            ("app.name is required", |m| m.app.name.clear()),
+            ("app.display_name is required", |m| {
+                m.app.display_name.clear()
+            }),
+            ("binary.name is required", |m| m.binary.name.clear()),
+            ("app.name must be lowercase", |m| {
+                m.app.name = "Demo_App".into()
+            }),
+            ("binary.name must not contain path separators", |m| {
+                m.binary.name = "bin/demo".into()
+            }),
+            ("app.version is not a valid semver", |m| {
+                m.app.version = Some("latest".into())
+            }),
+            ("source.owner and source.repo are required", |m| {
+                m.source = source("type = \"github\"\nowner = \"\"\nrepo = \"demo\"")
+            }),
+            ("source.project_id is required", |m| {
+                m.source = source("type = \"gitlab\"\nproject_id = \" \"")
+            }),
+            ("source.url must be an http(s) URL", |m| {
+                m.source = source("type = \"direct\"\nurl = \"ftp://example.com/demo.tar.gz\"")
+            }),
+        ]);
+    }
+
+    #[test]
+    fn invalid_mounts_are_reported() {
+        assert_rejected(&[
+            ("mounts.source is required", |m| {
+                m.mounts.push(mount(" ", "/data"))
+            }),
+            ("mounts.target must not be the container root", |m| {
+                m.mounts.push(mount("~/data", "/"))
+            }),
+            ("mounts.target must not contain '..'", |m| {
+                m.mounts.push(mount("~/data", "/opt/../etc"))
+            }),
+        ]);
+    }
+
+    #[test]
+    fn invalid_profile_name_is_reported() {
+        assert_rejected(&[("profile names must be lowercase", |m| {
+            m.app.name = "demo@Work".into()
+        })]);
+    }
+
+    #[test]
+    fn invalid_dns_entry_is_reported() {
+        assert_rejected(&[("runtime.dns entries must be IP addresses", |m| {
+            m.runtime.dns.push("dns.example.com".into())
+        })]);
+    }
+
+    #[test]
+    fn invalid_apt_repos_are_reported() {
+        assert_rejected(&[
+            ("dependencies.repos.uri must be an http(s) URL", |m| {
+                m.dependencies.repos.push(repo(&format!(
+                    "uri = \"ftp://example.com\"\nsuite = \"stable\"\n{}",
+                    KEY
+                )))
+            }),
+            ("dependencies.repos.suite must be a single word", |m| {
+                m.dependencies.repos.push(repo(&format!(
+                    "uri = \"https://example.com\"\nsuite = \"two words\"\n{}",
+                    KEY
+                )))
+            }),
+            ("dependencies.repos.components must be single words", |m| {
+                m.dependencies.repos.push(repo(&format!(
+                    "uri = \"https://example.com\"\nsuite = \"stable\"\n\
+                     components = [\"main contrib\"]\n{}",
+                    KEY
+                )))
+            }),
+            ("dependencies.repos.key_url must be an https URL", |m| {
+                m.dependencies.repos.push(repo(
+                    "uri = \"https://example.com\"\nsuite = \"stable\"\n\
+                     key_url = \"http://example.com/key.asc\"",
+                ))
+            }),
+            ("dependencies.repos.key_fingerprint must be a full", |m| {
+                m.dependencies.repos.push(repo(
+                    "uri = \"https://example.com\"\nsuite = \"stable\"\n\
+                     key_fingerprint = \"ABCD1234\"",
+                ))
+            }),
+            ("needs exactly one of key_url or key_fingerprint", |m| {
+                m.dependencies
+                    .repos
+                    .push(repo("uri = \"https://example.com\"\nsuite = \"stable\""))
+            }),
+        ]);
+    }
+
+    #[test]
+    fn invalid_runtime_base_is_reported() {
+        assert_rejected(&[("runtime.base must be", |m| {
+            m.runtime.base = "debian:12".into()
+        })]);
+    }
+
+    #[test]
+    fn invalid_host_package_dir_is_reported() {
+        assert_rejected(&[("runtime.extra_packages_from_host entries must be", |m| {
+            m.runtime.extra_packages_from_host.push("/etc".into())
+        })]);
+    }
+
+    #[test]
+    fn invalid_hostname_is_reported() {
+        assert_rejected(&[("runtime.hostname must be a valid host name", |m| {
+            m.runtime.hostname = Some("-demo".into())
+        })]);
+    }
+}
@@ -0,0 +1,164 @@
+//! Foreign-architecture apps through qemu-user and binfmt_misc
+//!
+//! Apps installed with `install --arch` for another architecture only run if
+//! the kernel hands their binaries to a qemu-user emulator. That emulator
+//! must be statically linked, since the container has no libraries for the
+//! host architecture. Handlers registered with the `F` (fix binary) flag are
+//! opened by the kernel up front; for the others the interpreter path is
+//! looked up at exec time, so it is bind-mounted into the container.
+
+use crate::manifest::{host_ubuntu_arch, ubuntu_arch_name};
+use crate::storage::read_base_info_for_rootfs;
+use std::fs::{self, File};
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use thiserror::Error;
+
+const BINFMT_DIR: &str = "/proc/sys/fs/binfmt_misc";
+
+/// ELF program header type of the dynamic loader entry
+const PT_INTERP: u32 = 3;
+
+#[derive(Error, Debug)]
+pub enum BinfmtError {
+    #[error(
+        "this app is built for {0}, but binfmt_misc is not mounted \
+         (sudo mount -t binfmt_misc binfmt_misc /proc/sys/fs/binfmt_misc)"
+    )]
+    NotMounted(String),
+
+    #[error(
+        "this app is built for {arch}, but no {handler} handler is registered with \
+         binfmt_misc (install qemu-user-static and binfmt-support)"
+    )]
+    NoHandler { arch: String, handler: String },
+
+    #[error(
+        "the binfmt_misc handler {0} is disabled \
+         (echo 1 | sudo tee /proc/sys/fs/binfmt_misc/{0})"
+    )]
+    Disabled(String),
+
+    #[error("the binfmt_misc handler {0} has no interpreter")]
+    NoInterpreter(String),
+
+    #[error("qemu interpreter not found: {0} (reinstall qemu-user-static)")]
+    InterpreterMissing(PathBuf),
+
+    #[error(
+        "qemu interpreter {0} is dynamically linked and cannot run inside the \
+         container (install qemu-user-static instead of qemu-user)"
+    )]
+    NotStatic(PathBuf),
+
+    #[error("base info: {0}")]
+    BaseInfo(String),
+
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+}
+
+/// Check qemu/binfmt_misc for a rootfs built for another architecture
+///
+/// Returns None for rootfs of the host architecture (or without base info)
+/// and for handlers the kernel already holds open. Otherwise returns the
+/// emulator that has to be bound into the container.
+pub fn qemu_interpreter_for(rootfs: &Path) -> Result<Option<PathBuf>, BinfmtError> {
+    let Some(info) =
+        read_base_info_for_rootfs(rootfs).map_err(|e| BinfmtError::BaseInfo(e.to_string()))?
+    else {
+        return Ok(None);
+    };
+    let Some(arch) = ubuntu_arch_name(&info.arch).filter(|arch| Some(*arch) != host_ubuntu_arch())
+    else {
+        return Ok(None);
+    };
+
+    let handler = format!("qemu-{}", qemu_arch_name(arch));
+    crate::debug!(
+        "App is built for {} (host {}), checking {}",
+        arch,
+        std::env::consts::ARCH,
+        handler
+    );
+
+    let binfmt_dir = Path::new(BINFMT_DIR);
+    if !binfmt_dir.join("status").exists() {
+        return Err(BinfmtError::NotMounted(arch.to_string()));
+    }
+    let entry = match fs::read_to_string(binfmt_dir.join(&handler)) {
+        Ok(entry) => entry,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            return Err(BinfmtError::NoHandler {
+                arch: arch.to_string(),
+                handler,
+            });
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    if entry.lines().next().map(str::trim) != Some("enabled") {
+        return Err(BinfmtError::Disabled(handler));
+    }
+    let interpreter = entry
+        .lines()
+        .find_map(|line| line.strip_prefix("interpreter "))
+        .map(|path| PathBuf::from(path.trim()))
+        .ok_or_else(|| BinfmtError::NoInterpreter(handler.clone()))?;
+    let fix_binary = entry
+        .lines()
+        .find_map(|line| line.strip_prefix("flags:"))
+        .is_some_and(|flags| flags.contains('F'));
+
+    if !interpreter.exists() {
+        return Err(BinfmtError::InterpreterMissing(interpreter));
+    }
+    if !is_static_elf(&interpreter)? {
+        return Err(BinfmtError::NotStatic(interpreter));
+    }
+
+    crate::debug!(
+        "Using {} ({})",
+        interpreter.display(),
+        if fix_binary {
+            "opened by the kernel"
+        } else {
+            "bound into the container"
+        }
+    );
+    Ok((!fix_binary).then_some(interpreter))
+}
+
+/// qemu's name for an Ubuntu architecture
+fn qemu_arch_name(arch: &str) -> &str {
+    match arch {
+        "amd64" => "x86_64",
+        "arm64" => "aarch64",
+        other => other,
+    }
+}
+
+/// Check that `path` is a 64-bit little-endian ELF without a dynamic loader
+fn is_static_elf(path: &Path) -> Result<bool, BinfmtError> {
+    let mut header = Vec::new();
+    File::open(path)?.take(64 * 1024).read_to_end(&mut header)?;
+    if header.len() < 64 || &header[..4] != b"\x7fELF" || header[4] != 2 || header[5] != 1 {
+        return Ok(false);
+    }
+
+    let read_u16 = |at: usize| u16::from_le_bytes([header[at], header[at + 1]]) as usize;
+    let phoff = u64::from_le_bytes(header[0x20..0x28].try_into().unwrap_or_default()) as usize;
+    let phentsize = read_u16(0x36);
+    let phnum = read_u16(0x38);
+
+    for index in 0..phnum {
+        let start = phoff + index * phentsize;
+        let Some(p_type) = header.get(start..start + 4) else {
+            return Ok(false);
+        };
+        if u32::from_le_bytes(p_type.try_into().unwrap_or_default()) == PT_INTERP {
+            return Ok(false);
+        }
+    }
+    Ok(true)
+}
@@ -0,0 +1,172 @@
+//! Capture app output to a per-app log file
+//!
+//! The app's stdout/stderr are piped through the init process, which copies
+//! them to the original streams and to `latest.log`. The log is opened before
+//! `pivot_root`, since the host app directory is not visible afterwards.
+
+use nix::fcntl::{OFlag, openat, renameat};
+use nix::sys::stat::Mode;
+use std::ffi::OsString;
+use std::fs::{self, File};
+use std::io::{self, Read, Seek, Write};
+use std::path::{Path, PathBuf};
+use std::process::Child;
+use std::sync::{Arc, Mutex};
+use std::thread::{self, JoinHandle};
+
+/// Log size at which the file is rotated to `<name>.1`
+pub const MAX_LOG_SIZE: u64 = 5 * 1024 * 1024;
+
+/// Log file of the current run
+///
+/// Keeps its directory open, so the log can still be rotated once
+/// `pivot_root` has hidden the host path.
+pub struct AppLog {
+    dir: File,
+    name: OsString,
+    file: File,
+}
+
+impl AppLog {
+    /// Append to the log, rotating it once it would grow past `max_size`
+    fn write_capped(&mut self, data: &[u8], max_size: u64) {
+        let size = self.file.stream_position().unwrap_or(0);
+        if size > 0 && size + data.len() as u64 > max_size && self.rotate().is_err() {
+            // Without a rotated file, start over rather than grow unbounded
+            let _ = self.file.set_len(0);
+            let _ = self.file.rewind();
+        }
+        let _ = self.file.write_all(data);
+    }
+
+    /// Move the log to `<name>.1` (replacing an older one) and start a new one
+    fn rotate(&mut self) -> io::Result<()> {
+        let mut rotated = self.name.clone();
+        rotated.push(".1");
+        renameat(
+            &self.dir,
+            self.name.as_os_str(),
+            &self.dir,
+            rotated.as_os_str(),
+        )?;
+        let fd = openat(
+            &self.dir,
+            self.name.as_os_str(),
+            OFlag::O_WRONLY | OFlag::O_CREAT | OFlag::O_TRUNC | OFlag::O_CLOEXEC,
+            Mode::from_bits_truncate(0o644),
+        )?;
+        self.file = File::from(fd);
+        Ok(())
+    }
+}
+
+/// Open a fresh log file, keeping the previous run's log as `previous.log`
+/// (and its rotated part as `previous.log.1`)
+pub fn open_app_log(path: &Path) -> io::Result<AppLog> {
+    let (Some(parent), Some(name)) = (path.parent(), path.file_name()) else {
+        return Err(io::Error::new(
+            io::ErrorKind::InvalidInput,
+            format!("invalid log path {}", path.display()),
+        ));
+    };
+    fs::create_dir_all(parent)?;
+    if path.exists() {
+        fs::rename(path, parent.join("previous.log"))?;
+    }
+    let rotated = rotated_log_path(path);
+    if rotated.exists() {
+        fs::rename(rotated, parent.join("previous.log.1"))?;
+    } else {
+        let _ = fs::remove_file(parent.join("previous.log.1"));
+    }
+
+    Ok(AppLog {
+        dir: File::open(parent)?,
+        name: name.to_os_string(),
+        file: File::create(path)?,
+    })
+}
+
+/// Path a full log is rotated to
+pub fn rotated_log_path(path: &Path) -> PathBuf {
+    let mut rotated = path.as_os_str().to_os_string();
+    rotated.push(".1");
+    rotated.into()
+}
+
+/// Copy a child's piped stdout/stderr to our own streams and the log
+///
+/// Returns the copier threads; join them once every child has exited.
+pub fn tee_child_output(child: &mut Child, log: AppLog) -> Vec<JoinHandle<()>> {
+    let log = Arc::new(Mutex::new(log));
+    let mut handles = Vec::new();
+
+    if let Some(stdout) = child.stdout.take() {
+        let log = Arc::clone(&log);
+        handles.push(thread::spawn(move || {
+            copy_stream(stdout, io::stdout(), &log)
+        }));
+    }
+    if let Some(stderr) = child.stderr.take() {
+        let log = Arc::clone(&log);
+        handles.push(thread::spawn(move || {
+            copy_stream(stderr, io::stderr(), &log)
+        }));
+    }
+
+    handles
+}
+
+fn copy_stream(mut source: impl Read, mut sink: impl Write, log: &Mutex<AppLog>) {
+    let mut buf = [0u8; 8192];
+    loop {
+        let n = match source.read(&mut buf) {
+            Ok(0) | Err(_) => break,
+            Ok(n) => n,
+        };
+
+        // The terminal may be gone (e.g. launched from a menu); keep logging
+        let _ = sink.write_all(&buf[..n]);
+        let _ = sink.flush();
+
+        if let Ok(mut log) = log.lock() {
+            log.write_capped(&buf[..n], MAX_LOG_SIZE);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn full_log_is_rotated_to_dot_one() {
+        let dir = std::env::temp_dir().join(format!("voidbox-test-log-{}", std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        let path = dir.join("latest.log");
+
+        let mut log = open_app_log(&path).unwrap();
+        log.write_capped(b"first run\n", 16);
+        log.write_capped(b"second\n", 16);
+        log.write_capped(b"third\n", 16);
+        drop(log);
+
+        assert_eq!(fs::read_to_string(&path).unwrap(), "second\nthird\n");
+        assert_eq!(
+            fs::read_to_string(rotated_log_path(&path)).unwrap(),
+            "first run\n"
+        );
+
+        // A new run keeps both parts of the last one
+        drop(open_app_log(&path).unwrap());
+        let previous = dir.join("previous.log");
+        assert_eq!(fs::read_to_string(&previous).unwrap(), "second\nthird\n");
+        assert_eq!(
+            fs::read_to_string(rotated_log_path(&previous)).unwrap(),
+            "first run\n"
+        );
+        assert!(!rotated_log_path(&path).exists());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
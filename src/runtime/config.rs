@@ -0,0 +1,90 @@
+//! Per-run container configuration passed to the init process
+
+use crate::manifest::{MountConfig, SeccompProfile};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::net::IpAddr;
+use std::path::PathBuf;
+
+/// Runtime settings for a single container launch
+///
+/// Unlike `PermissionConfig`, these are not user-facing permissions but
+/// per-launch details (environment, etc.) that the init process needs
+/// after entering the namespaces.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ContainerConfig {
+    /// Extra environment variables applied after the default setup
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+    /// Socket to listen on for launches forwarded by later invocations
+    /// (single-instance apps only)
+    #[serde(default)]
+    pub instance_socket: Option<PathBuf>,
+    /// Per-app directory mounted as the container home instead of the host home
+    #[serde(default)]
+    pub persist_home: Option<PathBuf>,
+    /// Mount an empty tmpfs as the container home (overrides `persist_home`
+    /// and the home permission); its contents are lost on exit
+    #[serde(default)]
+    pub isolate_home: bool,
+    /// Host directory bound read-only over the app's install dir, and the
+    /// container path it covers (apps installed with `--symlink-local`)
+    #[serde(default)]
+    pub linked_source: Option<(PathBuf, PathBuf)>,
+    /// Extra host binds from the manifest's `[[mounts]]` (unexpanded)
+    #[serde(default)]
+    pub mounts: Vec<MountConfig>,
+    /// Run the command as an unprivileged user instead of container root
+    #[serde(default)]
+    pub drop_privileges: bool,
+    /// Copy the app's stdout/stderr to this log file
+    #[serde(default)]
+    pub log_file: Option<PathBuf>,
+    /// Exit with the status of the last descendant to exit instead of the
+    /// direct child's
+    #[serde(default)]
+    pub wait_all: bool,
+    /// Display server the app is allowed to use
+    #[serde(default)]
+    pub display: DisplayBackend,
+    /// Syscall filter applied before starting the app (None = no filter)
+    #[serde(default)]
+    pub seccomp: Option<SeccompProfile>,
+    /// Host qemu-user emulator bound at the same path in the container
+    /// (foreign-architecture apps whose binfmt handler is resolved at exec)
+    #[serde(default)]
+    pub qemu_interpreter: Option<PathBuf>,
+    /// Nameservers for the container's /etc/resolv.conf (empty = keep the
+    /// base image's copy of the host file)
+    #[serde(default)]
+    pub dns: Vec<IpAddr>,
+    /// File to record the container init's PID in while it runs (`voidbox stop`)
+    #[serde(default)]
+    pub pid_file: Option<PathBuf>,
+    /// Host library directories bound read-only at the same path
+    #[serde(default)]
+    pub host_libraries: Vec<String>,
+    /// `DISPLAY` to use instead of the host's (Xvfb for `run --headless`)
+    #[serde(default)]
+    pub x11_display: Option<String>,
+    /// Host socket of the filtered session bus used instead of the raw one
+    /// (`[permissions] dbus_filter`)
+    #[serde(default)]
+    pub dbus_proxy: Option<PathBuf>,
+    /// Container hostname (None = `CONTAINER_HOSTNAME`)
+    #[serde(default)]
+    pub hostname: Option<String>,
+}
+
+/// Which display server an app talks to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DisplayBackend {
+    /// Wayland if the session has a socket, X11 otherwise
+    #[default]
+    Auto,
+    /// X11 only; the Wayland socket is hidden
+    X11,
+    /// Wayland only; DISPLAY is unset
+    Wayland,
+}
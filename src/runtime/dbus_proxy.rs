@@ -0,0 +1,118 @@
+//! Filtered session bus for `[permissions] dbus_filter` (`run --dbus-proxy`)
+//!
+//! xdg-dbus-proxy runs on the host, outside the container's namespaces, and
+//! listens on a socket in XDG_RUNTIME_DIR, which the container shares. Only
+//! the names in `TALK_NAMES` can be reached through it. The container's
+//! `DBUS_SESSION_BUS_ADDRESS` points at the proxy and the raw bus socket is
+//! masked.
+
+use nix::sys::signal::{Signal, kill};
+use nix::unistd::Pid;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use thiserror::Error;
+
+/// Bus names the app may talk to: notifications and desktop portals
+const TALK_NAMES: &[&str] = &["org.freedesktop.Notifications", "org.freedesktop.portal.*"];
+
+#[derive(Error, Debug)]
+pub enum DbusProxyError {
+    #[error("xdg-dbus-proxy not found")]
+    NotFound,
+
+    #[error("No session bus to filter")]
+    NoSessionBus,
+
+    #[error("xdg-dbus-proxy failed to start: {0}")]
+    StartFailed(String),
+
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+}
+
+/// A running xdg-dbus-proxy, stopped when dropped
+pub struct DbusProxy {
+    child: Child,
+    socket: PathBuf,
+}
+
+impl DbusProxy {
+    /// Start the proxy and wait until it accepts connections
+    pub fn start() -> Result<Self, DbusProxyError> {
+        let address =
+            std::env::var("DBUS_SESSION_BUS_ADDRESS").map_err(|_| DbusProxyError::NoSessionBus)?;
+        let runtime_dir = std::env::var("XDG_RUNTIME_DIR")
+            .map_err(|_| DbusProxyError::StartFailed("XDG_RUNTIME_DIR is not set".to_string()))?;
+
+        let dir = Path::new(&runtime_dir).join("voidbox");
+        fs::create_dir_all(&dir)?;
+        let socket = dir.join(format!("dbus-proxy-{}", std::process::id()));
+        let _ = fs::remove_file(&socket);
+
+        let mut child = Command::new("xdg-dbus-proxy")
+            .arg("--fd=1")
+            .arg(&address)
+            .arg(&socket)
+            .arg("--filter")
+            .args(TALK_NAMES.iter().map(|name| format!("--talk={}", name)))
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => DbusProxyError::NotFound,
+                _ => DbusProxyError::IoError(e),
+            })?;
+
+        // The proxy writes a byte once it is listening, or exits
+        let mut ready = [0u8; 1];
+        let started = match child.stdout.take() {
+            Some(mut stdout) => stdout.read(&mut ready)? == 1,
+            None => false,
+        };
+        if !started {
+            let _ = child.kill();
+            let status = child.wait()?;
+            return Err(DbusProxyError::StartFailed(format!(
+                "exited with {}",
+                status
+            )));
+        }
+
+        crate::debug!(
+            "Started xdg-dbus-proxy on {} (pid {})",
+            socket.display(),
+            child.id()
+        );
+        Ok(Self { child, socket })
+    }
+
+    /// Host path of the filtered bus socket
+    pub fn socket(&self) -> &Path {
+        &self.socket
+    }
+}
+
+impl Drop for DbusProxy {
+    fn drop(&mut self) {
+        let _ = kill(Pid::from_raw(self.child.id() as i32), Signal::SIGTERM);
+        let _ = self.child.wait();
+        let _ = fs::remove_file(&self.socket);
+    }
+}
+
+/// Filesystem socket of the host's session bus, if its address has one
+///
+/// Abstract sockets (`unix:abstract=`) have no path and can't be masked.
+pub fn session_bus_socket() -> Option<PathBuf> {
+    let address = std::env::var("DBUS_SESSION_BUS_ADDRESS").ok()?;
+    address.split(';').find_map(|entry| {
+        entry
+            .strip_prefix("unix:")?
+            .split(',')
+            .find_map(|key_value| key_value.strip_prefix("path="))
+            .map(PathBuf::from)
+    })
+}
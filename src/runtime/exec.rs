@@ -1,7 +1,12 @@
 //! Process execution in container
 
+use super::{
+    BinfmtError, ContainerConfig, InstanceListener, apply_seccomp, open_app_log,
+    qemu_interpreter_for, tee_child_output,
+};
 use crate::manifest::PermissionConfig;
-use nix::unistd::execvp;
+use nix::sys::signal::{SaFlags, SigAction, SigHandler, SigSet, Signal, kill, sigaction};
+use nix::unistd::{Pid, execvp};
 use std::ffi::CString;
 use std::fs;
 use std::path::Path;
@@ -15,6 +20,9 @@ pub enum ExecError {
 
     #[error("IO error: {0}")]
     IoError(#[from] std::io::Error),
+
+    #[error("Foreign architecture: {0}")]
+    ForeignArch(#[from] BinfmtError),
 }
 
 /// Execute a command, replacing the current process
@@ -38,10 +46,19 @@ pub fn spawn_container_init(
     cmd: &str,
     args: &[String],
     permissions: &PermissionConfig,
+    config: &ContainerConfig,
 ) -> Result<std::process::ExitStatus, ExecError> {
-    // Serialize permissions to JSON for passing via command line
+    // Apps for another architecture need a usable qemu-user setup
+    let mut config = config.clone();
+    if let Some(interpreter) = qemu_interpreter_for(rootfs)? {
+        config.qemu_interpreter = Some(interpreter);
+    }
+
+    // Serialize permissions and config to JSON for passing via command line
     let permissions_json = serde_json::to_string(permissions)
         .map_err(|e| ExecError::ExecFailed(format!("failed to serialize permissions: {}", e)))?;
+    let config_json = serde_json::to_string(&config)
+        .map_err(|e| ExecError::ExecFailed(format!("failed to serialize config: {}", e)))?;
 
     let mut command = Command::new(self_exe);
     command
@@ -50,6 +67,8 @@ pub fn spawn_container_init(
         .arg(cmd)
         .arg("--permissions")
         .arg(&permissions_json)
+        .arg("--config")
+        .arg(&config_json)
         .arg("--")
         .args(args)
         .stdin(Stdio::inherit())
@@ -61,7 +80,41 @@ pub fn spawn_container_init(
 
     let mut child = command.spawn()?;
 
-    Ok(child.wait()?)
+    // Killing the namespace init takes the whole container down with it
+    let pid = child.id().to_string();
+    if let Some(Err(e)) = config.pid_file.as_ref().map(|path| fs::write(path, &pid)) {
+        crate::debug!("Could not record the container PID: {}", e);
+    }
+
+    let status = child.wait();
+
+    // A later launch may have replaced the file; only remove our own
+    if let Some(pid_file) = config
+        .pid_file
+        .as_deref()
+        .filter(|path| fs::read_to_string(path).is_ok_and(|content| content.trim() == pid))
+    {
+        fs::remove_file(pid_file).ok();
+    }
+
+    Ok(status?)
+}
+
+/// Relay SIGTERM to every other process in the container's PID namespace
+fn forward_sigterm() {
+    extern "C" fn relay(_: libc::c_int) {
+        // kill(-1) from the namespace init reaches everything but itself
+        let _ = kill(Pid::from_raw(-1), Signal::SIGTERM);
+    }
+
+    let action = SigAction::new(
+        SigHandler::Handler(relay),
+        SaFlags::SA_RESTART,
+        SigSet::empty(),
+    );
+    if let Err(e) = unsafe { sigaction(Signal::SIGTERM, &action) } {
+        crate::debug!("Could not handle SIGTERM: {}", e);
+    }
 }
 
 /// Start dbus daemon inside container
@@ -87,37 +140,50 @@ pub fn init_and_exec(
     cmd: &str,
     args: &[String],
     permissions: &PermissionConfig,
+    config: &ContainerConfig,
 ) -> Result<(), ExecError> {
     use super::mount::{
         pivot_to_container, setup_container_env, setup_container_mounts, setup_host_bridge_shims,
-        setup_user_identity,
+        setup_unprivileged_identity, setup_user_identity,
     };
-    use nix::sys::wait::{WaitStatus, waitpid};
-    use nix::unistd::Pid;
+    use super::namespace::enter_unprivileged_namespace;
 
-    setup_container_mounts(rootfs, permissions)
+    // Open the log while the host app directory is still reachable
+    let log = match config.log_file.as_deref() {
+        Some(path) => match open_app_log(path) {
+            Ok(file) => Some(file),
+            Err(e) => {
+                crate::warn!("Warning: Cannot open log {}: {}", path.display(), e);
+                None
+            }
+        },
+        None => None,
+    };
+
+    setup_container_mounts(rootfs, permissions, config)
         .map_err(|e| ExecError::ExecFailed(format!("mount setup: {}", e)))?;
 
-    // Setup user identity masquerade (makes whoami return host username)
-    if permissions.native_mode {
+    // Setup user identity: an unprivileged account when dropping privileges,
+    // otherwise the root masquerade (makes whoami return host username)
+    if config.drop_privileges {
+        setup_unprivileged_identity(rootfs)
+            .map_err(|e| ExecError::ExecFailed(format!("user identity setup: {}", e)))?;
+    } else if permissions.native_mode {
         setup_user_identity(rootfs)
             .map_err(|e| ExecError::ExecFailed(format!("user identity setup: {}", e)))?;
     }
 
-    pivot_to_container(rootfs, permissions)
+    pivot_to_container(rootfs, permissions, config)
         .map_err(|e| ExecError::ExecFailed(format!("pivot_root: {}", e)))?;
 
-    setup_container_env(permissions);
+    setup_container_env(permissions, config);
 
     // Setup host bridge shims (sudo, host-exec) if bridge port is available
     if let Ok(port_str) = std::env::var("VOIDBOX_BRIDGE_PORT") {
         if let Ok(port) = port_str.parse::<u16>() {
             let token = std::env::var("VOIDBOX_BRIDGE_TOKEN").unwrap_or_default();
             if let Err(e) = setup_host_bridge_shims(port, &token) {
-                eprintln!(
-                    "[voidbox] Warning: Failed to setup host bridge shims: {}",
-                    e
-                );
+                crate::warn!("Warning: Failed to setup host bridge shims: {}", e);
             }
         }
     }
@@ -135,31 +201,141 @@ pub fn init_and_exec(
         libc::prctl(libc::PR_SET_CHILD_SUBREAPER, 1, 0, 0, 0);
     }
 
-    // Spawn app as child process
+    // Drop root last: everything above needs capabilities in the container.
+    // This must happen before any thread is started.
+    if config.drop_privileges {
+        let id = crate::CONTAINER_USER_ID;
+        enter_unprivileged_namespace(id, id)
+            .map_err(|e| ExecError::ExecFailed(format!("drop privileges: {}", e)))?;
+    }
+
+    // Filter syscalls once setup no longer needs mount and friends. Threads
+    // and processes started from here on (listener, app) inherit it.
+    if let Some(profile) = config.seccomp {
+        apply_seccomp(profile).map_err(|e| ExecError::ExecFailed(format!("seccomp: {}", e)))?;
+    }
+
+    // Accept launches forwarded by later invocations (single-instance apps)
+    let instance = match config.instance_socket.as_deref() {
+        Some(socket) => match InstanceListener::start(socket, cmd) {
+            Ok(listener) => Some(listener),
+            Err(e) => {
+                crate::warn!("Warning: Single-instance socket unavailable: {}", e);
+                None
+            }
+        },
+        None => None,
+    };
+
+    // Spawn app as child process (output piped through us when logging)
+    let output = || {
+        if log.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::inherit()
+        }
+    };
     let mut child = Command::new(cmd)
         .args(args)
         .stdin(Stdio::inherit())
-        .stdout(Stdio::inherit())
-        .stderr(Stdio::inherit())
+        .stdout(output())
+        .stderr(output())
         .spawn()
         .map_err(|e| ExecError::ExecFailed(format!("{}: {}", cmd, e)))?;
 
+    let log_threads = match log {
+        Some(file) => tee_child_output(&mut child, file),
+        None => Vec::new(),
+    };
+
+    // As PID 1 we only get signals we handle; pass `voidbox stop` on to the app
+    forward_sigterm();
+
     // Wait for direct child first
     let status = child
         .wait()
         .map_err(|e| ExecError::ExecFailed(format!("wait: {}", e)))?;
-    let exit_code = status.code().unwrap_or(1);
+    let exit_code = reap_orphans(status.code().unwrap_or(1), config.wait_all);
+
+    // Every writer is gone now; let the copiers flush the rest of the output
+    for handle in log_threads {
+        let _ = handle.join();
+    }
+
+    // process::exit skips destructors, so remove the socket explicitly
+    if let Some(instance) = &instance {
+        instance.close();
+    }
+
+    std::process::exit(exit_code);
+}
+
+/// Keep reaping orphaned children until none remain, returning the exit code
+///
+/// This handles apps that spawn processes and exit (like VSCode's launcher).
+/// With `wait_all`, the last descendant to exit decides the exit code, since
+/// the direct child may only have been a launcher script.
+fn reap_orphans(mut exit_code: i32, wait_all: bool) -> i32 {
+    use nix::sys::wait::{WaitStatus, waitpid};
 
-    // Keep reaping orphaned children until none remain
-    // This handles apps that spawn processes and exit (like VSCode's launcher)
     loop {
         match waitpid(Pid::from_raw(-1), None) {
-            Ok(WaitStatus::Exited(_, _)) | Ok(WaitStatus::Signaled(_, _, _)) => continue,
+            Ok(WaitStatus::Exited(_, code)) => {
+                if wait_all {
+                    exit_code = code;
+                }
+            }
+            Ok(WaitStatus::Signaled(_, signal, _)) => {
+                if wait_all {
+                    exit_code = 128 + signal as i32;
+                }
+            }
             Ok(_) => continue,
             Err(nix::errno::Errno::ECHILD) => break, // No more children
             Err(_) => break,
         }
     }
+    exit_code
+}
 
-    std::process::exit(exit_code);
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Set when the test binary is re-run to play the container's PID 1
+    const HARNESS_ENV: &str = "VOIDBOX_TEST_REAP_HARNESS";
+
+    /// The launcher exits 0 at once; the grandchild it double-forks is
+    /// reparented to us and exits 7 later
+    const LAUNCHER: &str = "(sleep 0.2; exit 7) & exit 0";
+
+    /// Run the launcher as a subreaper in a fresh copy of the test binary,
+    /// so reaping with waitpid(-1) can't steal other tests' children
+    fn run_harness(wait_all: bool) -> Option<i32> {
+        Command::new(std::env::current_exe().unwrap())
+            .args([
+                "--exact",
+                "runtime::exec::tests::wait_all_reports_grandchild_exit",
+            ])
+            .args(["--test-threads=1", "--quiet"])
+            .env(HARNESS_ENV, if wait_all { "wait_all" } else { "direct" })
+            .stdout(Stdio::null())
+            .status()
+            .unwrap()
+            .code()
+    }
+
+    #[test]
+    fn wait_all_reports_grandchild_exit() {
+        if let Ok(mode) = std::env::var(HARNESS_ENV) {
+            unsafe {
+                libc::prctl(libc::PR_SET_CHILD_SUBREAPER, 1, 0, 0, 0);
+            }
+            let status = Command::new("sh").args(["-c", LAUNCHER]).status().unwrap();
+            std::process::exit(reap_orphans(status.code().unwrap_or(1), mode == "wait_all"));
+        }
+
+        assert_eq!(run_harness(true), Some(7));
+        assert_eq!(run_harness(false), Some(0));
+    }
 }
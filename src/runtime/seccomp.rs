@@ -0,0 +1,113 @@
+//! Seccomp syscall filtering for the app process
+
+use crate::manifest::SeccompProfile;
+use seccompiler::{BpfProgram, SeccompAction, SeccompFilter, TargetArch};
+use std::collections::BTreeMap;
+use thiserror::Error;
+
+#[derive(Error, Debug)]
+pub enum SeccompError {
+    #[error("Unsupported architecture for seccomp: {0}")]
+    UnsupportedArch(String),
+
+    #[error("Failed to build seccomp filter: {0}")]
+    BuildFailed(String),
+
+    #[error("Failed to apply seccomp filter: {0}")]
+    ApplyFailed(String),
+}
+
+/// Syscalls blocked by the default profile
+///
+/// Kept small so Electron/Chromium apps keep working: these are only needed
+/// for the kernel keyring, or changing mounts and the kernel itself, none of
+/// which an app needs once the container is set up.
+const DEFAULT_BLOCKED: &[i64] = &[
+    libc::SYS_keyctl,
+    libc::SYS_add_key,
+    libc::SYS_request_key,
+    libc::SYS_mount,
+    libc::SYS_umount2,
+    libc::SYS_pivot_root,
+    libc::SYS_open_by_handle_at,
+    libc::SYS_kexec_load,
+    libc::SYS_kexec_file_load,
+    libc::SYS_init_module,
+    libc::SYS_finit_module,
+    libc::SYS_delete_module,
+    libc::SYS_reboot,
+    libc::SYS_swapon,
+    libc::SYS_swapoff,
+    libc::SYS_acct,
+];
+
+/// Additional syscalls blocked by the strict profile
+///
+/// These break apps that rely on their own sandbox (Chromium without
+/// `--no-sandbox`, Flatpak-style helpers), on io_uring, or on crash reporters
+/// and debuggers that attach to their own processes.
+const STRICT_BLOCKED: &[i64] = &[
+    libc::SYS_ptrace,
+    libc::SYS_process_vm_readv,
+    libc::SYS_process_vm_writev,
+    libc::SYS_bpf,
+    libc::SYS_perf_event_open,
+    libc::SYS_userfaultfd,
+    libc::SYS_unshare,
+    libc::SYS_setns,
+    libc::SYS_personality,
+    libc::SYS_syslog,
+    libc::SYS_name_to_handle_at,
+    libc::SYS_io_uring_setup,
+    libc::SYS_io_uring_enter,
+    libc::SYS_io_uring_register,
+];
+
+/// Build the BPF program for a profile (None for `off`)
+///
+/// Blocked syscalls fail with EPERM rather than killing the process, so
+/// apps that probe for them degrade gracefully.
+pub fn build_seccomp_filter(profile: SeccompProfile) -> Result<Option<BpfProgram>, SeccompError> {
+    let blocked: Vec<i64> = match profile {
+        SeccompProfile::Off => return Ok(None),
+        SeccompProfile::Default => DEFAULT_BLOCKED.to_vec(),
+        SeccompProfile::Strict => DEFAULT_BLOCKED
+            .iter()
+            .chain(STRICT_BLOCKED)
+            .copied()
+            .collect(),
+    };
+
+    let arch = TargetArch::try_from(std::env::consts::ARCH)
+        .map_err(|_| SeccompError::UnsupportedArch(std::env::consts::ARCH.to_string()))?;
+
+    // An empty rule list matches the syscall unconditionally
+    let rules: BTreeMap<i64, Vec<_>> = blocked
+        .into_iter()
+        .map(|syscall| (syscall, Vec::new()))
+        .collect();
+
+    let filter = SeccompFilter::new(
+        rules,
+        SeccompAction::Allow,
+        SeccompAction::Errno(libc::EPERM as u32),
+        arch,
+    )
+    .map_err(|e| SeccompError::BuildFailed(e.to_string()))?;
+
+    let program =
+        BpfProgram::try_from(filter).map_err(|e| SeccompError::BuildFailed(e.to_string()))?;
+    Ok(Some(program))
+}
+
+/// Install the profile's filter on the calling thread
+///
+/// Inherited by every thread and process started afterwards. Also sets
+/// `no_new_privs`, so setuid binaries no longer gain privileges.
+pub fn apply_seccomp(profile: SeccompProfile) -> Result<(), SeccompError> {
+    let Some(program) = build_seccomp_filter(profile)? else {
+        return Ok(());
+    };
+
+    seccompiler::apply_filter(&program).map_err(|e| SeccompError::ApplyFailed(e.to_string()))
+}
@@ -0,0 +1,76 @@
+//! Virtual X server for apps launched without a display (`run --headless`)
+//!
+//! Xvfb runs on the host, outside the container's namespaces, and picks a
+//! free display number itself (`-displayfd`). Its socket in /tmp/.X11-unix
+//! reaches the container through the /tmp bind mount.
+
+use nix::sys::signal::{Signal, kill};
+use nix::unistd::Pid;
+use std::io::{BufRead, BufReader};
+use std::process::{Child, Command, Stdio};
+use thiserror::Error;
+
+/// Screen size and depth of the virtual display
+const SCREEN: &str = "1920x1080x24";
+
+#[derive(Error, Debug)]
+pub enum XvfbError {
+    #[error("Xvfb not found - install it to run apps headless (e.g. sudo apt install xvfb)")]
+    NotFound,
+
+    #[error("Xvfb failed to start: {0}")]
+    StartFailed(String),
+
+    #[error("IO error: {0}")]
+    IoError(#[from] std::io::Error),
+}
+
+/// A running Xvfb server, stopped when dropped
+pub struct HeadlessDisplay {
+    child: Child,
+    display: String,
+}
+
+impl HeadlessDisplay {
+    /// Start Xvfb and wait until it accepts connections
+    pub fn start() -> Result<Self, XvfbError> {
+        let mut child = Command::new("Xvfb")
+            .args(["-displayfd", "1", "-nolisten", "tcp"])
+            .args(["-screen", "0", SCREEN])
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            .spawn()
+            .map_err(|e| match e.kind() {
+                std::io::ErrorKind::NotFound => XvfbError::NotFound,
+                _ => XvfbError::IoError(e),
+            })?;
+
+        // Xvfb writes the display number once it is ready
+        let mut line = String::new();
+        if let Some(stdout) = child.stdout.take() {
+            BufReader::new(stdout).read_line(&mut line)?;
+        }
+        let Ok(number) = line.trim().parse::<u32>() else {
+            let _ = child.kill();
+            let status = child.wait()?;
+            return Err(XvfbError::StartFailed(format!("exited with {}", status)));
+        };
+
+        let display = format!(":{}", number);
+        crate::debug!("Started Xvfb on {} (pid {})", display, child.id());
+        Ok(Self { child, display })
+    }
+
+    /// Value for `DISPLAY`
+    pub fn display(&self) -> &str {
+        &self.display
+    }
+}
+
+impl Drop for HeadlessDisplay {
+    fn drop(&mut self) {
+        let _ = kill(Pid::from_raw(self.child.id() as i32), Signal::SIGTERM);
+        let _ = self.child.wait();
+    }
+}
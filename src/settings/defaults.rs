@@ -26,6 +26,8 @@ pub fn merge_permissions(
             fonts: ov.fonts,
             themes: ov.themes,
             native_mode: ov.native_mode,
+            // Saved overrides never ask about it, so they can't turn it off
+            dbus_filter: manifest.dbus_filter || ov.dbus_filter,
         },
         None => manifest.clone(),
     }
@@ -0,0 +1,86 @@
+//! User-wide voidbox configuration
+
+use super::SettingsError;
+use crate::storage::paths;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::fs;
+
+/// Options from `~/.config/voidbox/config.toml`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GlobalConfig {
+    /// Ask which of an app's permissions to grant the first time it runs
+    #[serde(default)]
+    pub prompt_permissions: bool,
+    /// Check for app updates in the background when an app is launched
+    #[serde(default = "default_true")]
+    pub auto_update: bool,
+    /// Per-app options (`[apps.<name>]`), overriding the ones above
+    #[serde(default)]
+    pub apps: BTreeMap<String, AppConfig>,
+}
+
+/// Options for a single app in the global config
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct AppConfig {
+    #[serde(default)]
+    pub auto_update: Option<bool>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for GlobalConfig {
+    fn default() -> Self {
+        Self {
+            prompt_permissions: false,
+            auto_update: true,
+            apps: BTreeMap::new(),
+        }
+    }
+}
+
+impl GlobalConfig {
+    /// Check whether launching `app_name` looks for updates
+    pub fn auto_update(&self, app_name: &str) -> bool {
+        self.apps
+            .get(app_name)
+            .and_then(|app| app.auto_update)
+            .unwrap_or(self.auto_update)
+    }
+}
+
+/// Load the global config (defaults if the file does not exist)
+pub fn load_global_config() -> Result<GlobalConfig, SettingsError> {
+    let config_path = paths::global_config_path();
+
+    if !config_path.exists() {
+        return Ok(GlobalConfig::default());
+    }
+
+    let content = fs::read_to_string(config_path)?;
+    Ok(toml::from_str(&content)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn app_entry_overrides_global_auto_update() {
+        let config: GlobalConfig = toml::from_str(
+            r#"
+auto_update = false
+
+[apps.firefox]
+auto_update = true
+"#,
+        )
+        .unwrap();
+
+        assert!(config.auto_update("firefox"));
+        assert!(!config.auto_update("gimp"));
+        assert!(GlobalConfig::default().auto_update("gimp"));
+    }
+}
@@ -1,7 +1,9 @@
 //! Settings and permission management
 
 mod defaults;
+mod global;
 mod overrides;
 
 pub use defaults::*;
+pub use global::*;
 pub use overrides::*;
@@ -1,6 +1,6 @@
 //! User permission overrides
 
-use crate::manifest::PermissionConfig;
+use crate::manifest::{BridgeConfig, PermissionConfig};
 use crate::storage::paths;
 use std::fs;
 use thiserror::Error;
@@ -15,6 +15,9 @@ pub enum SettingsError {
 
     #[error("Failed to save settings: {0}")]
     SaveError(String),
+
+    #[error("Invalid settings: {0}")]
+    Invalid(String),
 }
 
 /// Load user settings overrides for an app
@@ -55,3 +58,37 @@ pub fn remove_overrides(app_name: &str) -> Result<(), SettingsError> {
     }
     Ok(())
 }
+
+/// Load the user-level host bridge config, if present
+///
+/// When it exists it takes precedence over the manifest's `[bridge]` section,
+/// so users can restrict what an app they don't fully trust may run on the host.
+pub fn load_bridge_config() -> Result<Option<BridgeConfig>, SettingsError> {
+    let config_path = paths::bridge_config_path();
+
+    if !config_path.exists() {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(&config_path)?;
+    let table: toml::Table = toml::from_str(&content)?;
+
+    // A key the bridge doesn't know would otherwise leave the allowlist
+    // empty, which allows every command
+    if let Some(key) = table.keys().find(|key| *key != "allowed_commands") {
+        let hint = if key == "bridge" {
+            " (put allowed_commands at the top level, without a [bridge] header)"
+        } else {
+            ""
+        };
+        return Err(SettingsError::Invalid(format!(
+            "unknown key '{}' in {}{}",
+            key,
+            config_path.display(),
+            hint
+        )));
+    }
+    let config: BridgeConfig = table.try_into()?;
+
+    Ok(Some(config))
+}
@@ -0,0 +1,180 @@
+//! Crash-safe metadata writes
+//!
+//! JSON state (the app database, base.json) is written to a temporary file
+//! next to the destination and renamed over it, so an interrupted write
+//! leaves the previous version intact. Read-modify-write cycles on shared
+//! files are serialized with an advisory lock on a `<file>.lock` sibling.
+//!
+//! Installs additionally hold `InstallLock` while touching the shared base
+//! image and dependency layers, so two launchers installing at once take
+//! turns instead of building the same layer twice.
+
+use crate::storage::paths;
+use nix::errno::Errno;
+use nix::fcntl::{Flock, FlockArg};
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Numbers temp files, so threads writing the same file don't share one
+static TMP_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Write `contents` to `path` atomically (temp file + rename)
+pub fn write_atomic(path: &Path, contents: impl AsRef<[u8]>) -> std::io::Result<()> {
+    write_atomic_with(path, |file| file.write_all(contents.as_ref()))
+}
+
+/// `write_atomic` with the temp file filled in by `write`
+fn write_atomic_with(
+    path: &Path,
+    write: impl FnOnce(&mut File) -> std::io::Result<()>,
+) -> std::io::Result<()> {
+    let tmp_path = sibling_path(
+        path,
+        &format!(
+            "tmp.{}.{}",
+            std::process::id(),
+            TMP_COUNTER.fetch_add(1, Ordering::Relaxed)
+        ),
+    );
+
+    let result = (|| {
+        let mut file = File::create(&tmp_path)?;
+        write(&mut file)?;
+        file.sync_all()?;
+        fs::rename(&tmp_path, path)
+    })();
+
+    if result.is_err() {
+        fs::remove_file(&tmp_path).ok();
+    }
+    result
+}
+
+/// Exclusive lock guarding `path`, released when dropped
+pub type FileLock = Flock<File>;
+
+/// Take an exclusive advisory lock for `path` (blocks until available)
+///
+/// The lock lives on `<path>.lock`, since `path` itself is replaced by
+/// `write_atomic` and a lock on the old inode would not exclude anyone.
+pub fn lock_file(path: &Path) -> std::io::Result<FileLock> {
+    acquire_lock(&sibling_path(path, "lock"), None)
+}
+
+/// Lock `lock_path`, printing `waiting` first if another process holds it
+fn acquire_lock(lock_path: &Path, waiting: Option<&str>) -> std::io::Result<FileLock> {
+    if let Some(parent) = lock_path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let file = OpenOptions::new()
+        .create(true)
+        .truncate(false)
+        .write(true)
+        .open(lock_path)?;
+
+    let file = match Flock::lock(file, FlockArg::LockExclusiveNonblock) {
+        Ok(lock) => return Ok(lock),
+        Err((file, Errno::EWOULDBLOCK)) => file,
+        Err((_, errno)) => return Err(errno.into()),
+    };
+    if let Some(message) = waiting {
+        crate::notice!("{}", message);
+    }
+    Flock::lock(file, FlockArg::LockExclusive).map_err(|(_, errno)| errno.into())
+}
+
+/// Held install lock and how many `InstallLock`s in this process share it
+static INSTALL_LOCK: Mutex<(usize, Option<FileLock>)> = Mutex::new((0, None));
+
+/// Cross-process lock for installs, released when the last guard drops
+///
+/// Re-entrant within a process: an install that builds the base image and
+/// dependency layer takes it at each step without deadlocking on itself.
+/// Drop it before forking a container, or the child keeps it held.
+pub struct InstallLock(());
+
+impl InstallLock {
+    /// Wait for other voidbox processes to finish installing
+    pub fn acquire() -> std::io::Result<Self> {
+        let mut state = INSTALL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        if state.0 == 0 {
+            state.1 = Some(acquire_lock(
+                &paths::install_lock_path(),
+                Some("Waiting for another voidbox operation to finish..."),
+            )?);
+        }
+        state.0 += 1;
+        Ok(Self(()))
+    }
+}
+
+impl Drop for InstallLock {
+    fn drop(&mut self) {
+        let mut state = INSTALL_LOCK.lock().unwrap_or_else(|e| e.into_inner());
+        state.0 -= 1;
+        if state.0 == 0 {
+            state.1 = None;
+        }
+    }
+}
+
+/// `path` with `.suffix` appended to its file name
+fn sibling_path(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.file_name().unwrap_or_default().to_os_string();
+    name.push(".");
+    name.push(suffix);
+    path.with_file_name(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scratch_dir(name: &str) -> PathBuf {
+        let dir =
+            std::env::temp_dir().join(format!("voidbox-test-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        fs::create_dir_all(&dir).unwrap();
+        dir
+    }
+
+    fn entries(dir: &Path) -> Vec<String> {
+        let mut names: Vec<String> = fs::read_dir(dir)
+            .unwrap()
+            .map(|entry| entry.unwrap().file_name().to_string_lossy().into_owned())
+            .collect();
+        names.sort();
+        names
+    }
+
+    #[test]
+    fn replaces_the_file() {
+        let dir = scratch_dir("atomic-ok");
+        let path = dir.join("apps.json");
+        fs::write(&path, "old").unwrap();
+
+        write_atomic(&path, "new").unwrap();
+        assert_eq!(fs::read_to_string(&path).unwrap(), "new");
+        assert_eq!(entries(&dir), ["apps.json"]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn interrupted_write_keeps_the_original() {
+        let dir = scratch_dir("atomic-interrupted");
+        let path = dir.join("apps.json");
+        fs::write(&path, "original").unwrap();
+
+        let result = write_atomic_with(&path, |file| {
+            file.write_all(b"half of the new")?;
+            Err(std::io::Error::other("interrupted by signal"))
+        });
+        assert!(result.is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), "original");
+        assert_eq!(entries(&dir), ["apps.json"]);
+        fs::remove_dir_all(&dir).unwrap();
+    }
+}
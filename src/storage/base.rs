@@ -1,6 +1,6 @@
 //! Base image metadata storage
 
-use crate::storage::paths;
+use crate::storage::{paths, write_atomic};
 use serde::{Deserialize, Serialize};
 use std::fs;
 use std::path::{Path, PathBuf};
@@ -13,6 +13,9 @@ pub struct BaseInfo {
     pub version: String,
     #[serde(default)]
     pub deps_id: Option<String>,
+    /// Store entry holding the app's extracted files, if they were moved there
+    #[serde(default)]
+    pub store_id: Option<String>,
 }
 
 #[derive(Error, Debug)]
@@ -36,7 +39,7 @@ pub fn write_base_info_for_dir(dir: &Path, info: &BaseInfo) -> Result<(), BaseIn
     let path = dir.join("base.json");
     let content = serde_json::to_string_pretty(info)?;
     fs::create_dir_all(dir)?;
-    fs::write(path, content)?;
+    write_atomic(&path, content)?;
     Ok(())
 }
 
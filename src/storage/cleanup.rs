@@ -6,6 +6,18 @@ use std::path::Path;
 #[cfg(unix)]
 use std::os::unix::fs::PermissionsExt;
 
+/// Total size in bytes of all files under a path (symlinks are not followed).
+pub fn dir_size(path: &Path) -> u64 {
+    walkdir::WalkDir::new(path)
+        .follow_links(false)
+        .into_iter()
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| entry.path().symlink_metadata().ok())
+        .filter(|meta| !meta.is_dir())
+        .map(|meta| meta.len())
+        .sum()
+}
+
 /// Remove a directory tree, relaxing permissions if needed.
 pub fn remove_dir_all_force(path: &Path) -> std::io::Result<()> {
     if !path.exists() {
@@ -43,6 +55,10 @@ fn make_tree_writable(path: &Path) -> std::io::Result<()> {
                 Ok(entry) => {
                     let meta = entry.path().symlink_metadata();
                     if let Ok(meta) = meta {
+                        #[cfg(target_os = "linux")]
+                        if meta.is_dir() || meta.is_file() {
+                            clear_immutable_flags(entry.path());
+                        }
                         let mode = if meta.is_dir() { 0o700 } else { 0o600 };
                         let _ = fs::set_permissions(entry.path(), fs::Permissions::from_mode(mode));
                     }
@@ -58,3 +74,41 @@ fn make_tree_writable(path: &Path) -> std::io::Result<()> {
 
     Ok(())
 }
+
+/// Clear `chattr +i`/`+a`, which block deletion even for the owner
+///
+/// Best effort: changing these flags needs CAP_LINUX_IMMUTABLE, and files
+/// we cannot open are left alone.
+#[cfg(target_os = "linux")]
+fn clear_immutable_flags(path: &Path) {
+    use std::os::unix::fs::OpenOptionsExt;
+    use std::os::unix::io::AsRawFd;
+
+    const FS_IMMUTABLE_FL: libc::c_int = 0x10;
+    const FS_APPEND_FL: libc::c_int = 0x20;
+
+    let Ok(file) = fs::OpenOptions::new()
+        .read(true)
+        .custom_flags(libc::O_NONBLOCK | libc::O_NOFOLLOW)
+        .open(path)
+    else {
+        return;
+    };
+
+    let mut flags: libc::c_int = 0;
+    if unsafe { libc::ioctl(file.as_raw_fd(), libc::FS_IOC_GETFLAGS, &mut flags) } != 0 {
+        return;
+    }
+    if flags & (FS_IMMUTABLE_FL | FS_APPEND_FL) == 0 {
+        return;
+    }
+
+    flags &= !(FS_IMMUTABLE_FL | FS_APPEND_FL);
+    if unsafe { libc::ioctl(file.as_raw_fd(), libc::FS_IOC_SETFLAGS, &flags) } != 0 {
+        crate::debug!(
+            "Could not clear immutable flag on {}: {}",
+            path.display(),
+            std::io::Error::last_os_error()
+        );
+    }
+}
@@ -1,9 +1,12 @@
 //! File download utilities
 
+use super::http::{http_get, http_head};
+use super::interrupt::check_interrupted;
 use indicatif::{ProgressBar, ProgressStyle};
-use std::fs::File;
+use std::fs::{self, File, OpenOptions};
 use std::io::{Read, Write};
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
 use thiserror::Error;
 
 #[derive(Error, Debug)]
@@ -18,49 +21,214 @@ pub enum DownloadError {
     Failed(String),
 }
 
+/// Environment variable that disables update checks (set by `--offline`)
+pub const OFFLINE_ENV: &str = "VOIDBOX_OFFLINE";
+
+/// Check whether offline mode is enabled (`VOIDBOX_OFFLINE=1`)
+pub fn is_offline() -> bool {
+    std::env::var(OFFLINE_ENV)
+        .map(|v| matches!(v.trim(), "1" | "true" | "yes"))
+        .unwrap_or(false)
+}
+
+/// Environment variable capping download speed (set by `--max-download-rate`)
+pub const MAX_RATE_ENV: &str = "VOIDBOX_MAX_RATE";
+
+/// Download speed limit in bytes per second (`VOIDBOX_MAX_RATE`), if any
+pub fn max_download_rate() -> Option<u64> {
+    let value = std::env::var(MAX_RATE_ENV).ok()?;
+    match parse_rate(&value) {
+        Ok(rate) => Some(rate),
+        Err(e) => {
+            crate::warn!("Warning: Ignoring {}: {}", MAX_RATE_ENV, e);
+            None
+        }
+    }
+}
+
+/// Parse a rate in bytes per second, with an optional K, M or G suffix
+/// (powers of 1024)
+pub fn parse_rate(value: &str) -> Result<u64, String> {
+    let value = value.trim();
+    let (digits, unit) = match value.char_indices().find(|(_, c)| !c.is_ascii_digit()) {
+        Some((i, _)) => value.split_at(i),
+        None => (value, ""),
+    };
+    let multiplier = match unit.trim().to_ascii_uppercase().as_str() {
+        "" | "B" => 1,
+        "K" | "KB" | "KIB" => 1024,
+        "M" | "MB" | "MIB" => 1024 * 1024,
+        "G" | "GB" | "GIB" => 1024 * 1024 * 1024,
+        _ => return Err(format!("unknown unit in '{}' (use K, M or G)", value)),
+    };
+    let rate = digits
+        .parse::<u64>()
+        .map_err(|_| format!("invalid rate '{}' (bytes per second, e.g. 500K)", value))?;
+    match rate.checked_mul(multiplier) {
+        Some(0) | None => Err(format!("rate out of range: '{}'", value)),
+        Some(rate) => Ok(rate),
+    }
+}
+
+/// Token bucket that delays reads to stay under a byte rate
+///
+/// At most one second worth of bytes can be saved up, so a stall in the
+/// connection isn't followed by a burst above the limit.
+struct Throttle {
+    rate: u64,
+    tokens: f64,
+    last: Instant,
+}
+
+impl Throttle {
+    fn new(rate: u64) -> Self {
+        Self {
+            rate,
+            tokens: rate as f64,
+            last: Instant::now(),
+        }
+    }
+
+    /// Account for `bytes` just read, sleeping if they exceed the budget
+    fn consume(&mut self, bytes: usize) {
+        let now = Instant::now();
+        let refill = now.duration_since(self.last).as_secs_f64() * self.rate as f64;
+        self.tokens = (self.tokens + refill).min(self.rate as f64) - bytes as f64;
+        self.last = now;
+        if self.tokens < 0.0 {
+            std::thread::sleep(Duration::from_secs_f64(-self.tokens / self.rate as f64));
+        }
+    }
+}
+
 /// Download a file with progress bar
+///
+/// Data is written to `<dest>.part` and renamed into place once complete.
+/// If a partial file from an interrupted download exists, the transfer is
+/// resumed with a `Range` request; the server's `ETag`/`Last-Modified`
+/// validator is sent as `If-Range` so a changed file restarts from scratch.
+/// The transfer is throttled to `VOIDBOX_MAX_RATE` when set.
 pub fn download_file(url: &str, dest: &Path, show_progress: bool) -> Result<(), DownloadError> {
-    let mut resp = ureq::get(url)
-        .header("User-Agent", crate::APP_NAME)
-        .call()
-        .map_err(|e| DownloadError::HttpError(e.to_string()))?;
+    download_file_with_progress(url, dest, show_progress, &mut |_, _| Ok(()))
+}
+
+/// Download a file like `download_file`, reporting `(downloaded, total)`
+/// bytes to `on_progress` as data arrives (`total` is 0 if unknown)
+///
+/// An error from `on_progress` aborts the download.
+pub fn download_file_with_progress(
+    url: &str,
+    dest: &Path,
+    show_progress: bool,
+    on_progress: &mut dyn FnMut(u64, u64) -> std::io::Result<()>,
+) -> Result<(), DownloadError> {
+    let part_path = partial_path(dest, "part");
+    let validator_path = partial_path(dest, "part.validator");
+    crate::debug!("Download {} -> {}", url, dest.display());
 
-    let total_size = resp
+    let existing = fs::metadata(&part_path).map(|m| m.len()).unwrap_or(0);
+    let validator = fs::read_to_string(&validator_path).ok();
+    let resume_from = match validator.as_deref() {
+        Some(v) if existing > 0 && !v.trim().is_empty() => existing,
+        _ => 0,
+    };
+
+    let mut request = http_get(url);
+    if resume_from > 0 {
+        request = request
+            .header("Range", format!("bytes={}-", resume_from))
+            .header("If-Range", validator.as_deref().unwrap_or_default().trim());
+    }
+
+    let mut resp = match request.call() {
+        Ok(resp) => resp,
+        Err(ureq::Error::StatusCode(416)) if resume_from > 0 => {
+            // Partial file doesn't match the remote anymore - start over
+            fs::remove_file(&part_path).ok();
+            fs::remove_file(&validator_path).ok();
+            return download_file_with_progress(url, dest, show_progress, on_progress);
+        }
+        Err(e) => return Err(DownloadError::HttpError(e.to_string())),
+    };
+
+    let content_length = resp
         .headers()
         .get("Content-Length")
         .and_then(|v| v.to_str().ok())
         .and_then(|s| s.parse::<u64>().ok())
         .unwrap_or(0);
 
+    crate::debug!("HTTP {} ({} bytes)", resp.status().as_u16(), content_length);
+    let resumed = resume_from > 0 && resp.status().as_u16() == 206;
+    let (mut out, mut downloaded) = if resumed {
+        crate::info!("Resuming download at {} bytes...", resume_from);
+        (
+            OpenOptions::new().append(true).open(&part_path)?,
+            resume_from,
+        )
+    } else {
+        let validator = resp
+            .headers()
+            .get("ETag")
+            .or_else(|| resp.headers().get("Last-Modified"))
+            .and_then(|v| v.to_str().ok())
+            .unwrap_or_default()
+            .to_string();
+        fs::write(&validator_path, validator)?;
+        (File::create(&part_path)?, 0)
+    };
+
+    let total_size = if content_length > 0 {
+        downloaded + content_length
+    } else {
+        0
+    };
+
     let pb = if show_progress && total_size > 0 {
         let pb = ProgressBar::new(total_size);
         pb.set_style(ProgressStyle::default_bar()
-            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} ({eta})")
+            .template("{spinner:.green} [{elapsed_precise}] [{bar:40.cyan/blue}] {bytes}/{total_bytes} {bytes_per_sec} ({eta})")
             .unwrap()
             .progress_chars("#>-"));
+        pb.set_position(downloaded);
         Some(pb)
     } else {
         None
     };
 
-    let mut out = File::create(dest)?;
     let mut reader = resp.body_mut().with_config().limit(1_000_000_000).reader();
     let mut buffer = vec![0u8; 8192];
-    let mut downloaded = 0u64;
+    let mut throttle = max_download_rate().map(Throttle::new);
 
     loop {
+        check_interrupted()?;
         let n = reader.read(&mut buffer)?;
         if n == 0 {
             break;
         }
         out.write_all(&buffer[..n])?;
         downloaded += n as u64;
+        if let Some(throttle) = &mut throttle {
+            throttle.consume(n);
+        }
 
         if let Some(ref pb) = pb {
             pb.set_position(downloaded);
         }
+        on_progress(downloaded, total_size)?;
     }
 
+    if total_size > 0 && downloaded < total_size {
+        return Err(DownloadError::Failed(format!(
+            "connection closed after {} of {} bytes (re-run to resume)",
+            downloaded, total_size
+        )));
+    }
+
+    drop(out);
+    fs::rename(&part_path, dest)?;
+    fs::remove_file(&validator_path).ok();
+
     if let Some(pb) = pb {
         pb.finish_with_message("Download complete");
     }
@@ -68,10 +236,48 @@ pub fn download_file(url: &str, dest: &Path, show_progress: bool) -> Result<(),
     Ok(())
 }
 
+/// Query the size of a remote file via a HEAD request (None if unknown)
+pub fn remote_file_size(url: &str) -> Option<u64> {
+    let resp = http_head(url).call().ok()?;
+
+    resp.headers()
+        .get("Content-Length")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|s| s.parse::<u64>().ok())
+}
+
+/// SHA-256 of a file as lowercase hex
+pub fn sha256_file(path: &Path) -> std::io::Result<String> {
+    use sha2::{Digest, Sha256};
+
+    let mut file = File::open(path)?;
+    let mut hasher = Sha256::new();
+    std::io::copy(&mut file, &mut hasher)?;
+    Ok(hex::encode(hasher.finalize()))
+}
+
+/// Delete `dest` and any partial download of it left by `download_file`
+pub fn remove_download(dest: &Path) {
+    for path in [
+        dest.to_path_buf(),
+        partial_path(dest, "part"),
+        partial_path(dest, "part.validator"),
+    ] {
+        fs::remove_file(path).ok();
+    }
+}
+
+/// Path of a download sidecar file (e.g. `app.zip` -> `app.zip.part`)
+fn partial_path(dest: &Path, suffix: &str) -> PathBuf {
+    let mut name = dest.as_os_str().to_os_string();
+    name.push(".");
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
 /// Download content to string
 pub fn download_string(url: &str) -> Result<String, DownloadError> {
-    let mut resp = ureq::get(url)
-        .header("User-Agent", crate::APP_NAME)
+    let mut resp = http_get(url)
         .call()
         .map_err(|e| DownloadError::HttpError(e.to_string()))?;
 
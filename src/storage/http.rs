@@ -0,0 +1,74 @@
+//! Shared HTTP agent
+//!
+//! All network requests go through one `ureq` agent so they share the same
+//! timeouts. Only connecting and waiting for response headers are bounded;
+//! reading the body is not, so large downloads on slow links still complete.
+//!
+//! GitHub API requests carry `VOIDBOX_GITHUB_TOKEN` (or `GITHUB_TOKEN`) when
+//! set, for the higher rate limit and access to private repos.
+
+use std::sync::OnceLock;
+use std::time::Duration;
+use ureq::typestate::WithoutBody;
+use ureq::{Agent, RequestBuilder};
+
+/// Maximum time to establish a connection
+pub const CONNECT_TIMEOUT: Duration = Duration::from_secs(3);
+
+/// Maximum time to wait for the response headers once connected
+pub const RESPONSE_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// Get the shared HTTP agent
+pub fn agent() -> &'static Agent {
+    static AGENT: OnceLock<Agent> = OnceLock::new();
+    AGENT.get_or_init(|| {
+        Agent::config_builder()
+            .timeout_connect(Some(CONNECT_TIMEOUT))
+            .timeout_recv_response(Some(RESPONSE_TIMEOUT))
+            .build()
+            .new_agent()
+    })
+}
+
+/// Prefix of GitHub API URLs that get the token
+const GITHUB_API_PREFIX: &str = "https://api.github.com/";
+
+/// Environment variables checked for a GitHub token, in order
+const GITHUB_TOKEN_ENVS: [&str; 2] = ["VOIDBOX_GITHUB_TOKEN", "GITHUB_TOKEN"];
+
+/// GitHub token from the environment, if any
+pub fn github_token() -> Option<String> {
+    GITHUB_TOKEN_ENVS
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .map(|token| token.trim().to_string())
+        .find(|token| !token.is_empty())
+}
+
+/// Add the voidbox User-Agent, and the GitHub token for GitHub API URLs
+///
+/// The token is only sent to the API host, never to asset download hosts.
+fn with_headers(url: &str, request: RequestBuilder<WithoutBody>) -> RequestBuilder<WithoutBody> {
+    let mut request = request.header("User-Agent", crate::APP_NAME);
+    if !url.starts_with(GITHUB_API_PREFIX) {
+        return request;
+    }
+    // Release asset API URLs return JSON metadata unless asked for the file
+    if url.contains("/releases/assets/") {
+        request = request.header("Accept", "application/octet-stream");
+    }
+    match github_token() {
+        Some(token) => request.header("Authorization", format!("Bearer {}", token)),
+        None => request,
+    }
+}
+
+/// Start a GET request with the voidbox User-Agent
+pub fn http_get(url: &str) -> RequestBuilder<WithoutBody> {
+    with_headers(url, agent().get(url))
+}
+
+/// Start a HEAD request with the voidbox User-Agent
+pub fn http_head(url: &str) -> RequestBuilder<WithoutBody> {
+    with_headers(url, agent().head(url))
+}
@@ -0,0 +1,82 @@
+//! Ctrl-C and SIGTERM during installs
+//!
+//! While an `InterruptGuard` is held, SIGINT and SIGTERM only set a flag.
+//! Downloads and extraction poll it through `check_interrupted`, so the
+//! signal unwinds through the normal error path and the install can clean
+//! up after itself. A second signal exits at once. Handlers are reset on
+//! exec, so apt and the container init keep their own signal handling.
+//! `voidbox serve` and the graphical installer raise the same flag to cancel
+//! an install.
+
+use nix::sys::signal::{SaFlags, SigAction, SigHandler, SigSet, Signal, sigaction};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+static INTERRUPTED: AtomicBool = AtomicBool::new(false);
+
+extern "C" fn on_signal(_: libc::c_int) {
+    if INTERRUPTED.swap(true, Ordering::SeqCst) {
+        // Second signal: the user does not want to wait for the cleanup
+        unsafe { libc::_exit(130) };
+    }
+}
+
+/// Turns SIGINT/SIGTERM into an interrupt flag, restoring the previous
+/// handlers when dropped
+pub struct InterruptGuard {
+    previous: Vec<(Signal, SigAction)>,
+}
+
+impl InterruptGuard {
+    pub fn install() -> Self {
+        let action = SigAction::new(
+            SigHandler::Handler(on_signal),
+            SaFlags::SA_RESTART,
+            SigSet::empty(),
+        );
+        let previous = [Signal::SIGINT, Signal::SIGTERM]
+            .into_iter()
+            .filter_map(|signal| {
+                let old = unsafe { sigaction(signal, &action) }.ok()?;
+                Some((signal, old))
+            })
+            .collect();
+        Self { previous }
+    }
+}
+
+impl Drop for InterruptGuard {
+    fn drop(&mut self) {
+        for (signal, action) in &self.previous {
+            let _ = unsafe { sigaction(*signal, action) };
+        }
+    }
+}
+
+/// Check whether SIGINT or SIGTERM arrived while a guard was held
+pub fn is_interrupted() -> bool {
+    INTERRUPTED.load(Ordering::SeqCst)
+}
+
+/// Stop the guarded operation as if SIGINT had arrived
+///
+/// Also works before the guard is installed, so a cancel that arrives while
+/// an install is still starting up is not lost.
+pub fn request_interrupt() {
+    INTERRUPTED.store(true, Ordering::SeqCst);
+}
+
+/// Forget an earlier interrupt before starting another operation in the
+/// same process
+pub fn clear_interrupt() {
+    INTERRUPTED.store(false, Ordering::SeqCst);
+}
+
+/// Fail once a signal has arrived
+///
+/// Not `ErrorKind::Interrupted`: std's read loops retry on that forever.
+pub fn check_interrupted() -> std::io::Result<()> {
+    if is_interrupted() {
+        return Err(std::io::Error::other("interrupted by signal"));
+    }
+    Ok(())
+}
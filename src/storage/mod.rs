@@ -1,11 +1,21 @@
 //! Storage management for Voidbox
 
+mod atomic;
 mod download;
 mod base;
 mod cleanup;
+mod http;
+mod interrupt;
 pub mod paths;
+mod space;
+mod store;
 
+pub use atomic::*;
 pub use base::*;
 pub use cleanup::*;
 pub use download::*;
+pub use http::*;
+pub use interrupt::*;
 pub use paths::*;
+pub use space::*;
+pub use store::*;
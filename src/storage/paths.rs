@@ -19,6 +19,16 @@ pub fn deps_dir() -> PathBuf {
     data_dir().join("deps")
 }
 
+/// Get the store directory (extracted app files shared between installs)
+pub fn store_dir() -> PathBuf {
+    data_dir().join("store")
+}
+
+/// Get the directory of a store entry
+pub fn store_entry_dir(store_id: &str) -> PathBuf {
+    store_dir().join(store_id)
+}
+
 /// Convert a base name + arch to a directory-friendly ID
 pub fn base_id(base: &str, arch: &str) -> String {
     let sanitized = base.replace(':', "-").replace('/', "-");
@@ -26,9 +36,12 @@ pub fn base_id(base: &str, arch: &str) -> String {
 }
 
 /// Convert dependency set to a shared layer ID
+///
+/// Uses SHA-256 (not `DefaultHasher`, whose output may change between Rust
+/// releases) so the same package set maps to the same layer across voidbox
+/// builds. Package order, duplicates and surrounding whitespace don't matter.
 pub fn deps_id(base: &str, arch: &str, packages: &[String]) -> String {
-    use std::collections::hash_map::DefaultHasher;
-    use std::hash::{Hash, Hasher};
+    use sha2::{Digest, Sha256};
 
     let mut normalized = packages
         .iter()
@@ -39,15 +52,17 @@ pub fn deps_id(base: &str, arch: &str, packages: &[String]) -> String {
     normalized.sort();
     normalized.dedup();
 
-    let mut hasher = DefaultHasher::new();
-    base.hash(&mut hasher);
-    arch.hash(&mut hasher);
+    let mut hasher = Sha256::new();
+    hasher.update(base.as_bytes());
+    hasher.update(b"\0");
+    hasher.update(arch.as_bytes());
     for pkg in &normalized {
-        pkg.hash(&mut hasher);
+        hasher.update(b"\0");
+        hasher.update(pkg.as_bytes());
     }
-    let hash = hasher.finish();
+    let hash = hex::encode(hasher.finalize());
 
-    format!("{}-deps-{:016x}", base_id(base, arch), hash)
+    format!("{}-deps-{}", base_id(base, arch), &hash[..12])
 }
 
 /// Get the base directory for a specific base + arch
@@ -105,6 +120,31 @@ pub fn app_work_dir(app_name: &str) -> PathBuf {
     app_dir(app_name).join("work")
 }
 
+/// Get app's log directory
+pub fn app_logs_dir(app_name: &str) -> PathBuf {
+    app_dir(app_name).join("logs")
+}
+
+/// Get the log file of an app's most recent run
+pub fn app_log_path(app_name: &str) -> PathBuf {
+    app_logs_dir(app_name).join("latest.log")
+}
+
+/// Get the file holding the PID of the app's running container init
+pub fn app_pid_path(app_name: &str) -> PathBuf {
+    app_dir(app_name).join("run.pid")
+}
+
+/// Get the marker recording that the denied-microphone note was shown
+pub fn app_mic_note_path(app_name: &str) -> PathBuf {
+    app_dir(app_name).join("mic-note-shown")
+}
+
+/// Get app's persistent home directory (`[runtime] persist_home`)
+pub fn app_home_dir(app_name: &str) -> PathBuf {
+    app_dir(app_name).join("home")
+}
+
 /// Get the manifests directory
 pub fn manifests_dir() -> PathBuf {
     data_dir().join("manifests")
@@ -125,6 +165,28 @@ pub fn app_settings_path(app_name: &str) -> PathBuf {
     settings_dir().join(format!("{}.toml", app_name))
 }
 
+/// Get the user config directory (~/.config/voidbox)
+pub fn config_dir() -> PathBuf {
+    dirs::config_dir()
+        .unwrap_or_else(|| PathBuf::from("."))
+        .join(crate::APP_NAME)
+}
+
+/// Get the global config path (user-wide options)
+pub fn global_config_path() -> PathBuf {
+    config_dir().join("config.toml")
+}
+
+/// Get the host bridge config path (user-level command allowlist)
+pub fn bridge_config_path() -> PathBuf {
+    config_dir().join("bridge.toml")
+}
+
+/// Get the trusted bundle signing keys file (hex public keys, one per line)
+pub fn trusted_keys_path() -> PathBuf {
+    config_dir().join("trusted-keys")
+}
+
 /// Get the icons directory
 pub fn icons_dir() -> PathBuf {
     data_dir().join("icons")
@@ -135,6 +197,11 @@ pub fn app_icon_path(app_name: &str) -> PathBuf {
     icons_dir().join(format!("{}.png", app_name))
 }
 
+/// Get a specific app's SVG icon path (used when an SVG can't be rasterized)
+pub fn app_icon_svg_path(app_name: &str) -> PathBuf {
+    icons_dir().join(format!("{}.svg", app_name))
+}
+
 /// Get the desktop files directory
 pub fn desktop_dir() -> PathBuf {
     dirs::data_local_dir()
@@ -147,6 +214,20 @@ pub fn app_desktop_path(app_name: &str) -> PathBuf {
     desktop_dir().join(format!("voidbox-{}.desktop", app_name))
 }
 
+/// Get the single-instance socket path for an app (None without XDG_RUNTIME_DIR)
+pub fn instance_socket_path(app_name: &str) -> Option<PathBuf> {
+    std::env::var_os("XDG_RUNTIME_DIR")
+        .map(|dir| PathBuf::from(dir).join(format!("voidbox-{}.sock", app_name)))
+}
+
+/// Control socket for GUI frontends (`voidbox serve`)
+pub fn control_socket_path() -> PathBuf {
+    std::env::var_os("XDG_RUNTIME_DIR")
+        .map(PathBuf::from)
+        .unwrap_or_else(data_dir)
+        .join("voidbox.sock")
+}
+
 /// Get the bin directory for symlinks
 pub fn bin_dir() -> PathBuf {
     dirs::home_dir()
@@ -189,6 +270,11 @@ pub fn database_path() -> PathBuf {
     data_dir().join("installed.json")
 }
 
+/// Lock file held while installing (see `InstallLock`)
+pub fn install_lock_path() -> PathBuf {
+    data_dir().join("install.lock")
+}
+
 /// Ensure all required directories exist
 pub fn ensure_dirs() -> std::io::Result<()> {
     std::fs::create_dir_all(data_dir())?;
@@ -202,3 +288,39 @@ pub fn ensure_dirs() -> std::io::Result<()> {
     std::fs::create_dir_all(bin_dir())?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn packages(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    #[test]
+    fn deps_id_ignores_order_duplicates_and_whitespace() {
+        let id = deps_id(
+            "ubuntu:24.04",
+            "amd64",
+            &packages(&["curl", "libnss3", "unzip"]),
+        );
+        // Pinned: a different ID would orphan every existing dependency layer
+        assert_eq!(id, "ubuntu-24.04-amd64-deps-6f63186ec4cc");
+        for variant in [
+            packages(&["unzip", "curl", "libnss3"]),
+            packages(&["curl", "curl", "libnss3", "unzip", "unzip"]),
+            packages(&["  curl", "libnss3 ", "\tunzip\n", ""]),
+        ] {
+            assert_eq!(deps_id("ubuntu:24.04", "amd64", &variant), id);
+        }
+    }
+
+    #[test]
+    fn deps_id_depends_on_packages_base_and_arch() {
+        let pkgs = packages(&["curl"]);
+        let id = deps_id("ubuntu:24.04", "amd64", &pkgs);
+        assert_ne!(deps_id("ubuntu:24.04", "amd64", &packages(&["wget"])), id);
+        assert_ne!(deps_id("ubuntu:22.04", "amd64", &pkgs), id);
+        assert_ne!(deps_id("ubuntu:24.04", "arm64", &pkgs), id);
+    }
+}
@@ -0,0 +1,90 @@
+//! Free disk space checks
+
+use nix::sys::statvfs::statvfs;
+use std::path::Path;
+use thiserror::Error;
+
+/// Rough ratio of extracted size to compressed archive size
+pub const EXTRACTION_MULTIPLIER: u64 = 3;
+
+#[derive(Error, Debug)]
+pub enum SpaceError {
+    #[error("Failed to query free space: {0}")]
+    QueryFailed(String),
+
+    #[error("Insufficient disk space: need {needed} bytes, {available} available")]
+    Insufficient { needed: u64, available: u64 },
+}
+
+/// Get the bytes available to unprivileged users on the filesystem holding `path`
+pub fn available_space(path: &Path) -> Result<u64, SpaceError> {
+    let stat =
+        statvfs(path).map_err(|e| SpaceError::QueryFailed(format!("{}: {}", path.display(), e)))?;
+    Ok(stat.blocks_available() as u64 * stat.fragment_size() as u64)
+}
+
+/// Ensure at least `required_bytes` are free on the filesystem holding `path`
+pub fn check_free_space(path: &Path, required_bytes: u64) -> Result<(), SpaceError> {
+    check_space_with(path, required_bytes, available_space)
+}
+
+/// `check_free_space` with the free-space query supplied by the caller
+fn check_space_with(
+    path: &Path,
+    required_bytes: u64,
+    query: impl FnOnce(&Path) -> Result<u64, SpaceError>,
+) -> Result<(), SpaceError> {
+    let available = query(path)?;
+    if available < required_bytes {
+        return Err(SpaceError::Insufficient {
+            needed: required_bytes,
+            available,
+        });
+    }
+    Ok(())
+}
+
+/// Estimate the space needed to download and extract an archive of `archive_size` bytes
+pub fn estimate_install_size(archive_size: u64) -> u64 {
+    archive_size.saturating_mul(1 + EXTRACTION_MULTIPLIER)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn free(bytes: u64) -> impl FnOnce(&Path) -> Result<u64, SpaceError> {
+        move |_| Ok(bytes)
+    }
+
+    #[test]
+    fn enough_space_passes() {
+        let path = Path::new("/data");
+        assert!(check_space_with(path, 100, free(100)).is_ok());
+        assert!(check_space_with(path, 100, free(1 << 30)).is_ok());
+    }
+
+    #[test]
+    fn too_little_space_reports_needed_and_available() {
+        match check_space_with(Path::new("/data"), 4096, free(1024)) {
+            Err(SpaceError::Insufficient { needed, available }) => {
+                assert_eq!((needed, available), (4096, 1024));
+            }
+            other => panic!("expected Insufficient, got {:?}", other),
+        }
+    }
+
+    #[test]
+    fn query_failure_is_passed_on() {
+        let result = check_space_with(Path::new("/missing"), 1, |path| {
+            Err(SpaceError::QueryFailed(path.display().to_string()))
+        });
+        assert!(matches!(result, Err(SpaceError::QueryFailed(p)) if p == "/missing"));
+    }
+
+    #[test]
+    fn install_estimate_includes_extraction() {
+        assert_eq!(estimate_install_size(100), 400);
+        assert_eq!(estimate_install_size(u64::MAX), u64::MAX);
+    }
+}
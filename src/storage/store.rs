@@ -0,0 +1,144 @@
+//! Content-addressed store for extracted app files
+//!
+//! After install, an app's `/opt/<install_dir>` tree is moved out of its
+//! layer to `store/<id>/opt/<install_dir>`, where `<id>` is a hash of the
+//! tree. The entry is mounted as an extra overlay lower layer between the app
+//! layer and the dependencies, so profiles and reinstalls of the same release
+//! share one copy on disk. Files the app changes at run time are copied up
+//! into its own layer as before.
+
+use super::atomic::InstallLock;
+use super::base::read_base_info_for_rootfs;
+use super::cleanup::remove_dir_all_force;
+use super::interrupt::check_interrupted;
+use super::paths;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io;
+use std::os::unix::ffi::OsStrExt;
+use std::os::unix::fs::PermissionsExt;
+use std::path::Path;
+use walkdir::WalkDir;
+
+/// Hash the extracted tree at `layer_dir/opt/<install_dir>` into a store ID
+///
+/// Covers paths, file types, modes, contents and symlink targets, but not
+/// timestamps, so extracting the same archive twice gives the same ID.
+/// Returns `None` if there is no such tree.
+pub fn app_tree_id(layer_dir: &Path, install_dir: &str) -> io::Result<Option<String>> {
+    let tree = layer_dir.join("opt").join(install_dir);
+    if !tree.is_dir() {
+        return Ok(None);
+    }
+
+    let mut hasher = Sha256::new();
+    hasher.update(install_dir.as_bytes());
+    for entry in WalkDir::new(&tree).min_depth(1).sort_by_file_name() {
+        check_interrupted()?;
+        let entry = entry.map_err(io::Error::other)?;
+        let relative = entry.path().strip_prefix(&tree).unwrap_or(entry.path());
+        let metadata = entry.path().symlink_metadata()?;
+
+        hasher.update(b"\0");
+        hasher.update(relative.as_os_str().as_bytes());
+        hasher.update(b"\0");
+        if metadata.file_type().is_symlink() {
+            hasher.update(b"l");
+            hasher.update(fs::read_link(entry.path())?.as_os_str().as_bytes());
+            continue;
+        }
+        hasher.update(metadata.permissions().mode().to_le_bytes());
+        if metadata.is_file() {
+            hasher.update(b"f");
+            io::copy(&mut File::open(entry.path())?, &mut hasher)?;
+        } else {
+            hasher.update(b"d");
+        }
+    }
+    let hash = hex::encode(hasher.finalize());
+
+    Ok(Some(format!(
+        "{}-{}",
+        install_dir.replace('/', "-"),
+        &hash[..16]
+    )))
+}
+
+/// Move the tree at `layer_dir/opt/<install_dir>` into store entry `id`
+///
+/// If the entry already exists the tree is a duplicate and is deleted.
+pub fn move_app_tree_to_store(layer_dir: &Path, install_dir: &str, id: &str) -> io::Result<()> {
+    let tree = layer_dir.join("opt").join(install_dir);
+    let entry = paths::store_entry_dir(id);
+    if entry.exists() {
+        crate::debug!("Store entry {} already present", id);
+        return remove_dir_all_force(&tree);
+    }
+
+    // Staged next to the entry so it only appears once complete
+    let staging = paths::store_dir().join(format!(".{}.tmp", id));
+    remove_dir_all_force(&staging)?;
+    let staged_tree = staging.join("opt").join(install_dir);
+    if let Some(parent) = staged_tree.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    fs::rename(&tree, &staged_tree)?;
+    fs::rename(&staging, &entry)?;
+    crate::debug!("Stored {} as {}", tree.display(), id);
+    Ok(())
+}
+
+/// Store IDs referenced by installed apps
+///
+/// Fails if any app's base info is unreadable, so a corrupt base.json can
+/// never cause an entry that is still in use to be pruned.
+pub fn store_ids_in_use() -> io::Result<HashSet<String>> {
+    let mut ids = HashSet::new();
+    let apps_dir = paths::apps_dir();
+    if !apps_dir.exists() {
+        return Ok(ids);
+    }
+
+    for entry in fs::read_dir(&apps_dir)? {
+        let entry = entry?;
+        if !entry.file_type()?.is_dir() {
+            continue;
+        }
+        let rootfs = entry.path().join("rootfs");
+        match read_base_info_for_rootfs(&rootfs) {
+            Ok(Some(info)) => ids.extend(info.store_id),
+            Ok(None) => {}
+            Err(e) => {
+                return Err(io::Error::other(format!(
+                    "could not read base info for {}: {}",
+                    entry.file_name().to_string_lossy(),
+                    e
+                )));
+            }
+        }
+    }
+    Ok(ids)
+}
+
+/// Delete store entry `id` unless an installed app still uses it
+///
+/// Holds the install lock, so an install can't start using the entry
+/// between the check and the delete.
+pub fn remove_unused_store_entry(id: &str) -> io::Result<()> {
+    let _lock = InstallLock::acquire()?;
+    let in_use = match store_ids_in_use() {
+        Ok(ids) => ids.contains(id),
+        Err(e) => {
+            crate::warn!("Warning: Keeping shared app files {}: {}", id, e);
+            true
+        }
+    };
+    let entry = paths::store_entry_dir(id);
+    if in_use || !entry.exists() {
+        return Ok(());
+    }
+
+    crate::info!("Removing unused shared app files...");
+    remove_dir_all_force(&entry)
+}